@@ -0,0 +1,100 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::sync::Arc;
+use tradebias::data::IndicatorCache;
+use tradebias::engines::evaluation::ExpressionBuilder;
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, Value};
+
+fn random_walk(bars: usize) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut price = 100.0;
+    let (mut open, mut close) = (Vec::with_capacity(bars), Vec::with_capacity(bars));
+    for _ in 0..bars {
+        open.push(price);
+        price = (price + rng.gen_range(-1.0..1.0f64)).max(1.0);
+        close.push(price);
+    }
+    let high: Vec<f64> = open.iter().zip(&close).map(|(o, c)| o.max(*c) + 0.5).collect();
+    let low: Vec<f64> = open.iter().zip(&close).map(|(o, c)| o.min(*c) - 0.5).collect();
+    df! { "open" => open, "high" => high, "low" => low, "close" => close }.unwrap()
+}
+
+fn call(function: &str, args: Vec<AstNode>) -> AstNode {
+    AstNode::Call { function: function.to_string(), args: args.into_iter().map(Box::new).collect() }
+}
+
+fn indicator(function: &str, input: AstNode, period: i64) -> AstNode {
+    call(function, vec![input, AstNode::Const(Value::Integer(period))])
+}
+
+fn close() -> AstNode {
+    call("Close", vec![])
+}
+
+/// Entry and exit conditions that share RSI(14) and EMA(50) several times over
+fn repeated_indicators() -> Vec<AstNode> {
+    let rsi = || indicator("RSI", close(), 14);
+    let ema = || indicator("EMA", close(), 50);
+    let threshold = |v: f64| AstNode::Const(Value::Float(v));
+    vec![
+        call("And", vec![call("gt_scalar", vec![rsi(), threshold(55.0)]), call("gt", vec![close(), ema()])]),
+        call("Or", vec![call("lt_scalar", vec![rsi(), threshold(45.0)]), call("lt", vec![close(), ema()])]),
+        call("And", vec![call("lt_scalar", vec![rsi(), threshold(45.0)]), call("lt", vec![close(), ema()])]),
+        call("Or", vec![call("gt_scalar", vec![rsi(), threshold(55.0)]), call("gt", vec![close(), ema()])]),
+    ]
+}
+
+/// A subtree added to itself `levels` times, 2^levels leaves when emitted inline
+fn doubling(levels: usize) -> Vec<AstNode> {
+    let mut node = indicator("SMA", close(), 10);
+    for _ in 0..levels {
+        node = call("Add", vec![node.clone(), node]);
+    }
+    vec![call("gt", vec![node, close()])]
+}
+
+fn inline(builder: &ExpressionBuilder, roots: &[AstNode], data: &DataFrame) -> DataFrame {
+    let exprs: Vec<Expr> = roots
+        .iter()
+        .enumerate()
+        .map(|(i, root)| builder.build(root, data).unwrap().alias(format!("root_{}", i)))
+        .collect();
+    data.clone().lazy().select(exprs).collect().unwrap()
+}
+
+fn compiled(builder: &ExpressionBuilder, roots: &[AstNode], data: &DataFrame) -> DataFrame {
+    let roots: Vec<Option<&AstNode>> = roots.iter().map(Some).collect();
+    let compiled = builder.compile(&roots, data, None).unwrap();
+    let exprs: Vec<Expr> = compiled
+        .roots
+        .iter()
+        .enumerate()
+        .map(|(i, root)| root.clone().unwrap().alias(format!("root_{}", i)))
+        .collect();
+    compiled.hoist(data.clone().lazy()).select(exprs).collect().unwrap()
+}
+
+fn bench_builders(c: &mut Criterion) {
+    let data = random_walk(20_000);
+    let builder = ExpressionBuilder::new(Arc::new(FunctionRegistry::new()), Arc::new(IndicatorCache::new(0)));
+    let cases = [("repeated_indicators", repeated_indicators()), ("doubling_6", doubling(6)), ("doubling_9", doubling(9))];
+
+    let mut group = c.benchmark_group("expression");
+    group.sample_size(20);
+    for (name, roots) in &cases {
+        group.bench_with_input(BenchmarkId::new("inline", name), roots, |b, roots| {
+            b.iter(|| black_box(inline(&builder, roots, &data)))
+        });
+        group.bench_with_input(BenchmarkId::new("compiled", name), roots, |b, roots| {
+            b.iter(|| black_box(compiled(&builder, roots, &data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_builders);
+criterion_main!(benches);
@@ -1,4 +1,6 @@
-use tradebias::config::backtesting::{BacktestingConfig, ValidationMethod};
+use tradebias::config::backtesting::{
+    BacktestingConfig, CarryCostModel, CommissionModel, EvaluationBudget, ExecutionTiming, IntrabarAssumption, MarginConfig, SlippageModel, ValidationMethod,
+};
 use tradebias::config::evolution::EvolutionConfig;
 use tradebias::data::IndicatorCache;
 use tradebias::engines::evaluation::Backtester;
@@ -86,9 +88,22 @@ fn main() {
         mutation_rate: 0.15,
         crossover_rate: 0.85,
         selection_method: tradebias::config::evolution::SelectionMethod::Tournament,
+        operators: tradebias::config::evolution::VariationOperators::Positional,
         elitism_count: (population_size as f64 * 0.1) as usize,
         max_tree_depth,
+        entry_orders: false,
+        entry_order_atr_period: 14,
         tournament_size: 7,
+        workers: 0,
+        checkpoint_every: 0,
+        checkpoint_path: "evolution.checkpoint".into(),
+        num_islands: 1,
+        migration_interval: 10,
+        migrants: 2,
+        adaptive_rates: false,
+        stagnation_generations: 5,
+        restart_after: 0,
+        stop_after: 0,
     };
 
     let backtesting_config = BacktestingConfig {
@@ -96,8 +111,14 @@ fn main() {
         train_test_split: 0.7,
         num_folds: 5,
         initial_capital: 10000.0,
-        commission: 0.001,
-        slippage: 0.001,
+        commission: CommissionModel::Percent { percent: 0.1 },
+        slippage: SlippageModel::Percent { percent: 0.1 },
+        execution_timing: ExecutionTiming::SameBarClose,
+        include_end_of_data_in_stats: true,
+        margin: MarginConfig::default(),
+        carry: CarryCostModel::None,
+        intrabar_assumption: IntrabarAssumption::Pessimistic,
+        budget: EvaluationBudget::unlimited(),
     };
 
     // Create components
@@ -120,17 +141,14 @@ fn main() {
     let engine_config = EngineEvolutionConfig {
         population_size,
         generations: num_generations,
-        genome_length: 100,
-        gene_range: 0..1000,
         mutation_rate: evolution_config.mutation_rate,
         crossover_rate: evolution_config.crossover_rate,
         elitism_rate: evolution_config.elitism_count as f64 / population_size as f64,
         tournament_size: evolution_config.tournament_size,
         hall_of_fame_size: 10,
-        fitness_objectives: vec!["return_pct".to_string()],
-        fitness_weights: vec![1.0],
-        min_fitness_threshold: 0.0,
         seed: None,
+        workers: evolution_config.workers,
+        ..Default::default()
     };
 
     let mut engine = EvolutionEngine::new(engine_config, backtester, semantic_mapper);
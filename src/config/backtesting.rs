@@ -8,8 +8,156 @@ pub struct BacktestingConfig {
     pub train_test_split: f64,
     pub num_folds: usize,
     pub initial_capital: f64,
-    pub commission: f64,
-    pub slippage: f64,
+    pub commission: CommissionModel,
+    pub slippage: SlippageModel,
+    pub execution_timing: ExecutionTiming,
+    /// Positions still open on the last bar are always closed there as `EndOfData`
+    /// trades and count towards equity. This controls whether they also count in
+    /// trade statistics such as `num_trades`, `win_rate` and `profit_factor`.
+    pub include_end_of_data_in_stats: bool,
+    pub margin: MarginConfig,
+    pub carry: CarryCostModel,
+    /// Which of a stop and a target inside the same bar is assumed to fill first
+    /// when no lower-timeframe data can tell
+    pub intrabar_assumption: IntrabarAssumption,
+    /// Limits past which a strategy is judged hopeless and its backtest stopped
+    pub budget: EvaluationBudget,
+}
+
+/// When to stop simulating a strategy early. A stopped run keeps the metrics of
+/// the bars simulated so far and is marked aborted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationBudget {
+    /// Stop once equity falls this far below its peak, in percent (25.0 = 25%)
+    pub max_drawdown_percent: Option<f64>,
+    /// Stop when fewer than this many lots have been opened by the checkpoint
+    pub min_trades: usize,
+    /// Fraction of the bars after which `min_trades` is checked
+    pub min_trades_checkpoint: f64,
+    /// Skip strategies whose signals never change
+    pub abort_on_constant_signal: bool,
+}
+
+impl Default for EvaluationBudget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl EvaluationBudget {
+    /// Never stop early
+    pub fn unlimited() -> Self {
+        Self {
+            max_drawdown_percent: None,
+            min_trades: 0,
+            min_trades_checkpoint: 0.5,
+            abort_on_constant_signal: false,
+        }
+    }
+
+    /// Number of bars after which `min_trades` is checked, or `None` when it never is
+    pub fn checkpoint_bars(&self, height: usize) -> Option<usize> {
+        let bars = (height as f64 * self.min_trades_checkpoint) as usize;
+        (self.min_trades > 0 && bars > 0).then_some(bars.min(height))
+    }
+}
+
+/// Leverage and margin requirements, as on perpetual futures.
+/// Initial margin is notional / `leverage`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginConfig {
+    /// Notional allowed per unit of equity; 1.0 is unlevered spot
+    pub leverage: f64,
+    /// Equity that must be kept against open notional, in percent (0.5 = 0.5%).
+    /// Below it every lot is closed as a margin call.
+    pub maintenance_margin_percent: f64,
+}
+
+impl Default for MarginConfig {
+    fn default() -> Self {
+        Self {
+            leverage: 1.0,
+            maintenance_margin_percent: 0.5,
+        }
+    }
+}
+
+impl MarginConfig {
+    /// Unlevered, with no maintenance requirement
+    pub fn spot() -> Self {
+        Self {
+            leverage: 1.0,
+            maintenance_margin_percent: 0.0,
+        }
+    }
+
+    /// Initial margin as a fraction of notional
+    pub fn initial_margin_rate(&self) -> f64 {
+        1.0 / self.leverage
+    }
+
+    /// Maintenance margin as a fraction of notional
+    pub fn maintenance_margin_rate(&self) -> f64 {
+        self.maintenance_margin_percent / 100.0
+    }
+}
+
+/// When a signal computed on a bar's close is filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExecutionTiming {
+    /// Fill at the signal bar's own close (optimistic: assumes trading at the price that produced the signal)
+    #[default]
+    SameBarClose,
+    /// Fill at the next bar's open
+    NextBarOpen,
+    /// Fill at the next bar's close
+    NextBarClose,
+}
+
+/// Order assumed when a lot's stop and target both trade within one bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IntrabarAssumption {
+    /// The stop filled first
+    #[default]
+    Pessimistic,
+    /// The target filled first
+    Optimistic,
+}
+
+/// Commission charged on each side of a trade.
+/// Percentages are expressed in percent (0.1 = 0.1%).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CommissionModel {
+    /// Percent of fill notional
+    Percent { percent: f64 },
+    /// Fixed amount per unit traded
+    PerUnit { amount: f64 },
+    /// Fixed amount per fill, regardless of size
+    PerTrade { amount: f64 },
+    None,
+}
+
+/// Adverse price movement applied to each market fill
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SlippageModel {
+    /// Percent of fill price
+    Percent { percent: f64 },
+    /// Whole ticks of the instrument's tick size
+    Ticks { ticks: f64, tick_size: f64 },
+    /// Fraction of the fill bar's high-low range
+    BarRange { fraction: f64 },
+    None,
+}
+
+/// Cost of carrying open positions from bar to bar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CarryCostModel {
+    /// Perpetual funding read from a data column, as a fraction of notional per bar
+    /// (0.0001 = 0.01%). Longs pay positive rates to shorts and receive negative ones.
+    FundingRate { column: String },
+    /// Annualised borrow rate in percent, charged on short notional only
+    BorrowRate { annual_percent: f64, bars_per_year: f64 },
+    None,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -27,8 +175,14 @@ impl Default for BacktestingConfig {
             train_test_split: 0.7,
             num_folds: 5,
             initial_capital: 10000.0,
-            commission: 0.001,
-            slippage: 0.0005,
+            commission: CommissionModel::Percent { percent: 0.1 },
+            slippage: SlippageModel::Percent { percent: 0.05 },
+            execution_timing: ExecutionTiming::SameBarClose,
+            include_end_of_data_in_stats: true,
+            margin: MarginConfig::default(),
+            carry: CarryCostModel::None,
+            intrabar_assumption: IntrabarAssumption::Pessimistic,
+            budget: EvaluationBudget::unlimited(),
         }
     }
 }
@@ -49,6 +203,37 @@ impl ConfigSection for BacktestingConfig {
                 "Initial capital must be positive".to_string()
             ));
         }
+        if self.margin.leverage < 1.0 {
+            return Err(TradebiasError::Configuration(
+                "Leverage must be at least 1".to_string()
+            ));
+        }
+        if self.margin.maintenance_margin_rate() < 0.0
+            || self.margin.maintenance_margin_rate() >= self.margin.initial_margin_rate()
+        {
+            return Err(TradebiasError::Configuration(
+                "Maintenance margin must be below initial margin".to_string()
+            ));
+        }
+        if let Some(percent) = self.budget.max_drawdown_percent {
+            if percent <= 0.0 || percent > 100.0 {
+                return Err(TradebiasError::Configuration(
+                    "Maximum drawdown must be between 0 and 100 percent".to_string()
+                ));
+            }
+        }
+        if self.budget.min_trades_checkpoint <= 0.0 || self.budget.min_trades_checkpoint > 1.0 {
+            return Err(TradebiasError::Configuration(
+                "Trade count checkpoint must be between 0 and 1".to_string()
+            ));
+        }
+        if let CarryCostModel::BorrowRate { bars_per_year, .. } = self.carry {
+            if bars_per_year <= 0.0 {
+                return Err(TradebiasError::Configuration(
+                    "Bars per year must be positive".to_string()
+                ));
+            }
+        }
         Ok(())
     }
 
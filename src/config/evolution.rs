@@ -1,6 +1,7 @@
 use super::traits::{ConfigSection, ConfigManifest, FieldManifest};
 use crate::error::TradebiasError;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvolutionConfig {
@@ -9,16 +10,78 @@ pub struct EvolutionConfig {
     pub mutation_rate: f64,
     pub crossover_rate: f64,
     pub selection_method: SelectionMethod,
+    pub operators: VariationOperators,
     pub elitism_count: usize,
     pub max_tree_depth: usize,
+    /// Let strategies enter with limit and stop orders as well as at market
+    pub entry_orders: bool,
+    /// ATR period the offsets of limit and stop entry orders are measured in
+    pub entry_order_atr_period: usize,
     pub tournament_size: usize,
+    /// Threads evaluating each generation; 0 uses one per core
+    pub workers: usize,
+    /// Generations between checkpoints a cancelled or crashed run can resume from; 0 disables them
+    pub checkpoint_every: usize,
+    /// File checkpoints are written to and resumed from; give concurrent runs their own
+    pub checkpoint_path: PathBuf,
+    /// Populations evolving side by side, each of `population_size`; 1 evolves a single one
+    pub num_islands: usize,
+    /// Generations between migrations along the ring of islands; 0 keeps them isolated
+    pub migration_interval: usize,
+    /// Best strategies each island sends per migration
+    pub migrants: usize,
+    /// Raise mutation and lower crossover while the search stalls or converges
+    pub adaptive_rates: bool,
+    /// Generations without improvement before the rates adapt
+    pub stagnation_generations: usize,
+    /// Generations without improvement before all but the elites are replaced
+    /// with random strategies; 0 never restarts
+    pub restart_after: usize,
+    /// Generations without improvement before the run ends early; 0 runs them all
+    pub stop_after: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How offspring are made from their parents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VariationOperators {
+    /// Single-point crossover of genomes, and each gene redrawn with the
+    /// mutation rate. Cheap, but a changed gene reshapes the rest of the tree.
+    Positional,
+    /// Subtree crossover of the decoded strategies, and with the mutation rate
+    /// one point, hoist, shrink or parameter mutation per child
+    Tree,
+}
+
+/// How parents are drawn from an evaluated population
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SelectionMethod {
+    /// Best of `tournament_size` random candidates
     Tournament,
+    /// Probability proportional to fitness
     Roulette,
+    /// Probability proportional to fitness rank
     Rank,
+    /// Roulette with evenly spaced pointers, spun once per population
+    StochasticUniversal,
+    /// Filters candidates by their return in each of `folds` consecutive slices
+    /// of the data, visited in random order
+    Lexicase { folds: usize },
+    /// Probability proportional to exp(fitness / temperature)
+    Boltzmann { temperature: f64 },
+}
+
+impl SelectionMethod {
+    pub fn validate(&self) -> Result<(), TradebiasError> {
+        match *self {
+            SelectionMethod::Lexicase { folds: 0 } => Err(TradebiasError::Configuration(
+                "Lexicase selection needs at least one fold".to_string()
+            )),
+            SelectionMethod::Boltzmann { temperature } if temperature.is_nan() || temperature <= 0.0 => Err(TradebiasError::Configuration(
+                "Boltzmann temperature must be positive".to_string()
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Default for EvolutionConfig {
@@ -29,9 +92,22 @@ impl Default for EvolutionConfig {
             mutation_rate: 0.15,
             crossover_rate: 0.85,
             selection_method: SelectionMethod::Tournament,
+            operators: VariationOperators::Positional,
             elitism_count: 10,
             max_tree_depth: 12,
+            entry_orders: false,
+            entry_order_atr_period: 14,
             tournament_size: 7,
+            workers: 0,
+            checkpoint_every: 0,
+            checkpoint_path: PathBuf::from("evolution.checkpoint"),
+            num_islands: 1,
+            migration_interval: 10,
+            migrants: 2,
+            adaptive_rates: false,
+            stagnation_generations: 5,
+            restart_after: 0,
+            stop_after: 0,
         }
     }
 }
@@ -57,6 +133,27 @@ impl ConfigSection for EvolutionConfig {
                 "Crossover rate must be between 0 and 1".to_string()
             ));
         }
+        self.selection_method.validate()?;
+        if self.entry_orders && self.entry_order_atr_period == 0 {
+            return Err(TradebiasError::Configuration(
+                "Entry order ATR period must be at least 1".to_string()
+            ));
+        }
+        if self.checkpoint_every > 0 && self.checkpoint_path.as_os_str().is_empty() {
+            return Err(TradebiasError::Configuration(
+                "Checkpoints need a file to be written to".to_string()
+            ));
+        }
+        if self.num_islands == 0 {
+            return Err(TradebiasError::Configuration(
+                "There must be at least one island".to_string()
+            ));
+        }
+        if self.adaptive_rates && self.stagnation_generations == 0 {
+            return Err(TradebiasError::Configuration(
+                "Stagnation needs at least one generation without improvement".to_string()
+            ));
+        }
         Ok(())
     }
 
@@ -7,13 +7,33 @@ pub struct TradeManagementConfig {
     pub stop_loss: StopLossConfig,
     pub take_profit: TakeProfitConfig,
     pub position_sizing: PositionSizing,
+    /// Maximum number of lots open at once. Above 1, repeated entry signals
+    /// pyramid into additional lots, each with its own stop and target.
     pub max_positions: usize,
+    /// Hold long and short lots at the same time. When false, an entry is
+    /// skipped while lots on the other side are open, and a single signed
+    /// signal of the opposite sign closes them. When true that signal opens a
+    /// new lot instead, so lots close only through exit rules, stops and targets.
+    pub allow_hedging: bool,
+    /// Move the stop to the entry price once a lot has moved this many R
+    /// (multiples of its initial stop distance) in its favour
+    pub breakeven_after_r: Option<f64>,
+    /// Close a lot at the close of the bar on which it has been held this many bars
+    pub max_bars_in_trade: Option<usize>,
 }
 
+/// Initial stop for each lot. Trailing variants ratchet at the end of every bar
+/// and never loosen; the new level applies from the following bar.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StopLossConfig {
     FixedPercent { percent: f64 },
     ATR { multiplier: f64, period: usize },
+    /// Trails the best price since entry (highest high for longs, lowest low for shorts) by `percent`
+    TrailingPercent { percent: f64 },
+    /// Trails the close by `multiplier` x ATR(`period`)
+    TrailingATR { multiplier: f64, period: usize },
+    /// Chandelier exit: trails the best price since entry by `multiplier` x ATR(`period`)
+    Chandelier { multiplier: f64, period: usize },
     None,
 }
 
@@ -21,14 +41,34 @@ pub enum StopLossConfig {
 pub enum TakeProfitConfig {
     FixedPercent { percent: f64 },
     RiskReward { ratio: f64 },
+    /// Scale out in steps measured in R (the initial stop distance). Whatever the
+    /// levels leave open is managed by the stop, e.g. a trailing stop.
+    Ladder { levels: Vec<ScaleOut> },
     None,
 }
 
+/// One rung of a scale-out ladder: close `percent` of the original size at `r_multiple` R
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScaleOut {
+    pub r_multiple: f64,
+    pub percent: f64,
+}
+
+/// How much to allocate to each new position.
+/// Percentages are expressed in percent (2.0 = 2%).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PositionSizing {
+    /// Fixed notional amount per trade
     Fixed { size: f64 },
+    /// Percent of current equity per trade
     Percent { percent: f64 },
-    Kelly { fraction: f64 },
+    /// Fractional Kelly, estimated from the strategy's last `lookback` completed trades.
+    /// Until enough trades exist, `warmup_percent` of equity is used instead.
+    Kelly { fraction: f64, lookback: usize, warmup_percent: f64 },
+    /// Risk `risk_percent` of equity per trade, measured against the stop distance.
+    /// Without a stop, `atr_multiplier` x ATR(`atr_period`) stands in for it.
+    /// An ATR stop's period takes precedence over `atr_period`.
+    VolatilityTarget { risk_percent: f64, atr_period: usize, atr_multiplier: f64 },
 }
 
 impl Default for TradeManagementConfig {
@@ -36,8 +76,38 @@ impl Default for TradeManagementConfig {
         Self {
             stop_loss: StopLossConfig::ATR { multiplier: 2.0, period: 14 },
             take_profit: TakeProfitConfig::RiskReward { ratio: 2.0 },
-            position_sizing: PositionSizing::Percent { percent: 0.02 },
+            position_sizing: PositionSizing::Percent { percent: 2.0 },
             max_positions: 5,
+            allow_hedging: false,
+            breakeven_after_r: None,
+            max_bars_in_trade: None,
+        }
+    }
+}
+
+impl TradeManagementConfig {
+    /// No stops or targets, one position at a time: positions close only on
+    /// an opposite signal, sized at 10% of cash.
+    pub fn signal_only() -> Self {
+        Self {
+            stop_loss: StopLossConfig::None,
+            take_profit: TakeProfitConfig::None,
+            position_sizing: PositionSizing::Percent { percent: 10.0 },
+            max_positions: 1,
+            allow_hedging: false,
+            breakeven_after_r: None,
+            max_bars_in_trade: None,
+        }
+    }
+
+    /// ATR period needed by the stop or the sizing mode, if either uses ATR
+    pub fn atr_period(&self) -> Option<usize> {
+        match (&self.stop_loss, &self.position_sizing) {
+            (StopLossConfig::ATR { period, .. }, _)
+            | (StopLossConfig::TrailingATR { period, .. }, _)
+            | (StopLossConfig::Chandelier { period, .. }, _) => Some(*period),
+            (_, PositionSizing::VolatilityTarget { atr_period, .. }) => Some(*atr_period),
+            _ => None,
         }
     }
 }
@@ -48,6 +118,11 @@ impl ConfigSection for TradeManagementConfig {
     }
 
     fn validate(&self) -> Result<(), TradebiasError> {
+        if self.max_positions == 0 {
+            return Err(TradebiasError::Configuration(
+                "Max positions must be at least 1".to_string()
+            ));
+        }
         Ok(())
     }
 
@@ -1,31 +1,162 @@
 use polars::prelude::*;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
 use std::sync::Mutex;
 
+use crate::error::Result;
+
+/// Hit, miss and eviction counts since the cache was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups answered from the cache, or 0 before any lookup
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Computed series keyed by the expression that produced them and the data it
+/// ran on, evicting the least recently used once full. Safe to share between
+/// threads evaluating in parallel.
 pub struct IndicatorCache {
-    data: Mutex<HashMap<String, Series>>,
+    data: Mutex<Lru>,
     capacity: usize,
 }
 
+#[derive(Default)]
+struct Lru {
+    /// Each series with the tick it was last used at
+    entries: HashMap<String, (Series, u64)>,
+    /// Keys by last use, oldest first
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl Lru {
+    fn touch(&mut self, key: &str) -> Option<Series> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (series, last_used) = self.entries.get_mut(key)?;
+        let key = self.recency.remove(last_used).expect("every entry has a recency");
+        *last_used = tick;
+        self.recency.insert(tick, key);
+        Some(series.clone())
+    }
+}
+
 impl IndicatorCache {
+    /// A cache holding at most `capacity` series; 0 disables it
     pub fn new(capacity: usize) -> Self {
         Self {
-            data: Mutex::new(HashMap::with_capacity(capacity)),
+            data: Mutex::new(Lru::default()),
             capacity,
         }
     }
 
     pub fn get(&self, key: &str) -> Option<Series> {
-        let data = self.data.lock().unwrap();
-        data.get(key).cloned()
+        let mut data = self.data.lock().unwrap();
+        let found = data.touch(key);
+        match found {
+            Some(_) => data.hits += 1,
+            None => data.misses += 1,
+        }
+        found
     }
 
     pub fn set(&self, key: String, value: Series) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut data = self.data.lock().unwrap();
+        if data.touch(&key).is_some() {
+            let tick = data.tick;
+            data.entries.insert(key, (value, tick));
+            return;
+        }
+        while data.entries.len() >= self.capacity {
+            let Some((_, oldest)) = data.recency.pop_first() else { break };
+            data.entries.remove(&oldest);
+            data.evictions += 1;
+        }
+        data.tick += 1;
+        let tick = data.tick;
+        data.recency.insert(tick, key.clone());
+        data.entries.insert(key, (value, tick));
+    }
+
+    /// The cached series for `key`, computing and storing it on a miss. The
+    /// lock is not held while computing, so threads missing the same key at
+    /// once may each compute it.
+    pub fn get_or_compute(&self, key: &str, compute: impl FnOnce() -> Result<Series>) -> Result<Series> {
+        if let Some(series) = self.get(key) {
+            return Ok(series);
+        }
+        let series = compute()?;
+        self.set(key.to_string(), series.clone());
+        Ok(series)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let data = self.data.lock().unwrap();
+        CacheStats {
+            hits: data.hits,
+            misses: data.misses,
+            evictions: data.evictions,
+            entries: data.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Drop every entry, keeping the statistics
+    pub fn clear(&self) {
         let mut data = self.data.lock().unwrap();
-        if data.len() >= self.capacity {
-            // A simple eviction strategy: clear the cache when full.
-            data.clear();
+        data.entries.clear();
+        data.recency.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A hash of every column's name and values, so results computed on one
+    /// dataset are never served for another
+    pub fn fingerprint(df: &DataFrame) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        df.height().hash(&mut hasher);
+        for column in df.get_columns() {
+            column.name().as_str().hash(&mut hasher);
+            column.dtype().to_string().hash(&mut hasher);
+            let series = column.as_materialized_series().to_physical_repr();
+            if let Ok(values) = series.str() {
+                values.iter().for_each(|v| v.hash(&mut hasher));
+            } else if let Ok(values) = series.bool() {
+                values.iter().for_each(|v| v.hash(&mut hasher));
+            } else if let Ok(values) = series.cast(&DataType::Float64) {
+                values.f64().unwrap().iter().for_each(|v| v.map(f64::to_bits).hash(&mut hasher));
+            } else {
+                series.iter().for_each(|v| v.to_string().hash(&mut hasher));
+            }
         }
-        data.insert(key, value);
+        hasher.finish()
     }
 }
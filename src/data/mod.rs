@@ -1,5 +1,5 @@
 pub mod cache;
 pub mod connectors;
 
-pub use cache::IndicatorCache;
+pub use cache::{CacheStats, IndicatorCache};
 pub use connectors::{CsvConnector, DataPreview, DatasetMetadata};
\ No newline at end of file
@@ -1,17 +1,220 @@
 use crate::{
+    config::backtesting::{
+        CarryCostModel, CommissionModel, EvaluationBudget, ExecutionTiming, IntrabarAssumption, MarginConfig,
+        SlippageModel,
+    },
+    config::trade_management::{StopLossConfig, TakeProfitConfig, TradeManagementConfig},
     data::IndicatorCache,
     error::{Result, TradebiasError},
-    engines::evaluation::{ExpressionBuilder, Portfolio},
+    engines::evaluation::{
+        portfolio::{BarSignals, OrderRequest, PriceBar},
+        vectorized::{self, SignalBar},
+        ExpressionBuilder, IndicatorColumns, Portfolio,
+    },
+    functions::indicators::ATR,
     functions::registry::FunctionRegistry,
-    types::{AstNode, StrategyResult},
+    functions::traits::{IndicatorArg, VectorizedIndicator},
+    types::{
+        AbortReason, AstNode, Direction, EntryOrder, ExitReason, IntrabarResolution, OrderKind, PriceReference,
+        StrategyResult, Trade,
+    },
     engines::generation::ast::StrategyAST,
 };
 use polars::prelude::*;
-use std::{collections::HashMap, sync::Arc};
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::{Arc, Mutex},
+};
 
 pub struct Backtester {
     expression_builder: Arc<ExpressionBuilder>,
     initial_balance: f64,
+    trade_management: TradeManagementConfig,
+    commission: CommissionModel,
+    slippage: SlippageModel,
+    execution_timing: ExecutionTiming,
+    include_end_of_data_in_stats: bool,
+    margin: MarginConfig,
+    carry: CarryCostModel,
+    intrabar_assumption: IntrabarAssumption,
+    /// Finer bars covering the same period as the data, walked to order intrabar fills
+    lower_timeframe: Option<DataFrame>,
+    fast_path: bool,
+    budget: EvaluationBudget,
+    /// The frame last fingerprinted and its fingerprint. Holding the frame keeps
+    /// its chunks alive, so a frame sharing all of them is the same data.
+    fingerprinted: Mutex<Option<(DataFrame, u64)>>,
+}
+
+/// Signal and price columns of one run, read a bar at a time
+struct BarColumns<'a> {
+    /// A rule's signed signal, or else separate entry and exit conditions
+    signal: Option<&'a Float64Chunked>,
+    rules: Option<[&'a BooleanChunked; 4]>,
+    entry_order: Option<EntryOrder>,
+    /// Long and short order prices when entering with orders
+    order_prices: Option<[&'a Float64Chunked; 2]>,
+    allow_hedging: bool,
+    close: &'a Float64Chunked,
+    open: Option<&'a Float64Chunked>,
+    high: &'a Float64Chunked,
+    low: &'a Float64Chunked,
+    atr: Option<&'a Float64Chunked>,
+    funding: Option<&'a Float64Chunked>,
+}
+
+impl<'a> BarColumns<'a> {
+    /// Read one strategy's columns, named with `prefix`, from `signals`
+    fn new(
+        data: &'a DataFrame,
+        signals: &'a DataFrame,
+        prefix: &str,
+        entry_order: Option<EntryOrder>,
+        funding_column: Option<&str>,
+        allow_hedging: bool,
+    ) -> Result<Self> {
+        let column = |name: &str| signals.column(&format!("{}{}", prefix, name));
+        let signal = match column("signal") {
+            Ok(signal) => Some(signal.f64()?),
+            Err(_) => None,
+        };
+        let rules = match signal {
+            Some(_) => None,
+            None => Some([
+                column("long_entry")?.bool()?,
+                column("long_exit")?.bool()?,
+                column("short_entry")?.bool()?,
+                column("short_exit")?.bool()?,
+            ]),
+        };
+        let order_prices = match entry_order {
+            Some(_) => Some([column("long_order_price")?.f64()?, column("short_order_price")?.f64()?]),
+            None => None,
+        };
+        let close = data.column("close")?;
+        let funding = match funding_column {
+            Some(column) => Some(data.column(column).map_err(|_| {
+                TradebiasError::Validation(format!("Funding rate column '{}' not found", column))
+            })?),
+            None => None,
+        };
+
+        Ok(Self {
+            signal,
+            rules,
+            entry_order,
+            order_prices,
+            allow_hedging,
+            close: close.f64()?,
+            open: data.column("open").ok().map(|open| open.f64()).transpose()?,
+            // Missing high/low fall back to the close
+            high: data.column("high").unwrap_or(close).f64()?,
+            low: data.column("low").unwrap_or(close).f64()?,
+            atr: signals.column("__atr").ok().map(|atr| atr.f64()).transpose()?,
+            funding: funding.map(|funding| funding.f64()).transpose()?,
+        })
+    }
+
+    /// Entry and exit conditions that fired at bar `i`, with the order to enter through
+    fn signals_at(&self, i: usize) -> BarSignals {
+        let mut bar_signals = match (self.signal, &self.rules) {
+            (Some(signal), _) => BarSignals::from_signal(signal.get(i).unwrap_or(0.0), self.allow_hedging),
+            (None, Some([long_entry, long_exit, short_entry, short_exit])) => BarSignals {
+                long_entry: long_entry.get(i).unwrap_or(false),
+                long_exit: long_exit.get(i).unwrap_or(false),
+                short_entry: short_entry.get(i).unwrap_or(false),
+                short_exit: short_exit.get(i).unwrap_or(false),
+                entry_order: None,
+            },
+            (None, None) => BarSignals::default(),
+        };
+        if let (Some(order), Some([long_price, short_price])) = (&self.entry_order, &self.order_prices) {
+            // An order whose price is still warming up is priced NaN and never placed
+            let price = match bar_signals.entry_direction() {
+                Some(Direction::Long) => long_price.get(i),
+                Some(Direction::Short) => short_price.get(i),
+                None => None,
+            };
+            bar_signals.entry_order = Some(OrderRequest {
+                kind: order.kind,
+                price: price.unwrap_or(f64::NAN),
+                expiry_bars: order.expiry_bars,
+            });
+        }
+        bar_signals
+    }
+
+    /// Prices of bar `i`. Without an open column, the previous close stands in for it.
+    fn prices_at(&self, i: usize) -> PriceBar {
+        let close = self.close.get(i).unwrap_or(0.0);
+        let open = match self.open {
+            Some(open) => open.get(i),
+            None => i.checked_sub(1).map(|previous| self.close.get(previous).unwrap_or(0.0)),
+        };
+        PriceBar {
+            open: open.unwrap_or(close),
+            high: self.high.get(i).unwrap_or(close),
+            low: self.low.get(i).unwrap_or(close),
+            close,
+            atr: self.atr.and_then(|atr| atr.get(i)),
+            funding_rate: self.funding.and_then(|funding| funding.get(i)),
+        }
+    }
+
+    /// Whether every bar carries the same entry and exit conditions
+    fn is_constant(&self, height: usize) -> bool {
+        let conditions = |i| BarSignals { entry_order: None, ..self.signals_at(i) };
+        let first = conditions(0);
+        (1..height).all(|i| conditions(i) == first)
+    }
+
+    /// Bars on which any entry or exit condition fired, found column-wise
+    fn fired(&self) -> Vec<usize> {
+        let fired = match (self.signal, &self.rules) {
+            (Some(signal), _) => &signal.gt(0.0) | &signal.lt(0.0),
+            (None, Some([long_entry, long_exit, short_entry, short_exit])) => {
+                &(&(*long_entry | *long_exit) | *short_entry) | *short_exit
+            }
+            (None, None) => return Vec::new(),
+        };
+        fired
+            .iter()
+            .enumerate()
+            .filter_map(|(i, fired)| fired.unwrap_or(false).then_some(i))
+            .collect()
+    }
+}
+
+/// Checks a run against an evaluation budget one bar at a time
+struct BudgetCheck<'a> {
+    budget: &'a EvaluationBudget,
+    height: usize,
+    checkpoint: Option<usize>,
+    peak: f64,
+}
+
+impl<'a> BudgetCheck<'a> {
+    fn new(budget: &'a EvaluationBudget, height: usize, initial_balance: f64) -> Self {
+        Self { budget, height, checkpoint: budget.checkpoint_bars(height), peak: initial_balance }
+    }
+
+    /// Why the run should stop after `bar`, given the bar's equity and the lots opened
+    /// so far. Drawdown is not checked on the last bar, whose equity already includes
+    /// closing every lot.
+    fn after_bar(&mut self, bar: usize, equity: f64, opened: impl FnOnce() -> usize) -> Option<AbortReason> {
+        self.peak = self.peak.max(equity);
+        if let Some(limit) = self.budget.max_drawdown_percent {
+            if bar + 1 < self.height && self.peak > 0.0 && (self.peak - equity) / self.peak * 100.0 > limit {
+                return Some(AbortReason::MaxDrawdown { bar });
+            }
+        }
+        if self.checkpoint == Some(bar + 1) && opened() < self.budget.min_trades {
+            return Some(AbortReason::TooFewTrades { bar });
+        }
+        None
+    }
 }
 
 impl Backtester {
@@ -23,49 +226,485 @@ impl Backtester {
         Self {
             expression_builder: Arc::new(ExpressionBuilder::new(registry, cache)),
             initial_balance,
+            trade_management: TradeManagementConfig::signal_only(),
+            commission: CommissionModel::None,
+            slippage: SlippageModel::None,
+            execution_timing: ExecutionTiming::SameBarClose,
+            include_end_of_data_in_stats: true,
+            margin: MarginConfig::spot(),
+            carry: CarryCostModel::None,
+            intrabar_assumption: IntrabarAssumption::Pessimistic,
+            lower_timeframe: None,
+            fast_path: true,
+            budget: EvaluationBudget::unlimited(),
+            fingerprinted: Mutex::new(None),
         }
     }
 
+    /// Charge commission and slippage on every fill
+    pub fn with_costs(mut self, commission: CommissionModel, slippage: SlippageModel) -> Self {
+        self.commission = commission;
+        self.slippage = slippage;
+        self
+    }
+
+    /// Choose when signals are filled: at the signal bar's close or on the next bar
+    pub fn with_execution_timing(mut self, execution_timing: ExecutionTiming) -> Self {
+        self.execution_timing = execution_timing;
+        self
+    }
+
+    /// Trade with leverage; positions are liquidated or margin-called when equity runs short
+    pub fn with_margin(mut self, margin: MarginConfig) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Charge funding (from a data column) or a borrow rate on held positions
+    pub fn with_carry(mut self, carry: CarryCostModel) -> Self {
+        self.carry = carry;
+        self
+    }
+
+    /// Which of a stop and a target inside one bar fills first when the lower
+    /// timeframe is missing or cannot tell
+    pub fn with_intrabar_assumption(mut self, assumption: IntrabarAssumption) -> Self {
+        self.intrabar_assumption = assumption;
+        self
+    }
+
+    /// Resolve stops and targets that share a bar by walking these finer bars.
+    /// Both frames need a `timestamp` column; each lower-timeframe bar belongs to
+    /// the bar whose period it starts in.
+    pub fn with_lower_timeframe(mut self, lower_timeframe: DataFrame) -> Self {
+        self.lower_timeframe = Some(lower_timeframe);
+        self
+    }
+
+    /// Stop strategies early once they breach `budget`, keeping the metrics of the
+    /// bars simulated so far and marking the result aborted
+    pub fn with_budget(mut self, budget: EvaluationBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Allow the vectorized path when the run qualifies (see `uses_fast_path`).
+    /// Its results are identical; turning it off is for checking that they are.
+    pub fn with_fast_path(mut self, enabled: bool) -> Self {
+        self.fast_path = enabled;
+        self
+    }
+
+    /// Whether positions force-closed on the last bar count in trade statistics.
+    /// They are closed and counted in equity either way.
+    pub fn with_end_of_data_in_stats(mut self, include: bool) -> Self {
+        self.include_end_of_data_in_stats = include;
+        self
+    }
+
+    /// Apply stop-loss, take-profit and position sizing rules from the given config to every run
+    pub fn with_trade_management(mut self, trade_management: TradeManagementConfig) -> Self {
+        self.trade_management = trade_management;
+        self
+    }
+
+    /// The cache of indicator results shared by every run of this backtester
+    pub fn cache(&self) -> &Arc<IndicatorCache> {
+        self.expression_builder.cache()
+    }
+
     pub fn run(&self, ast: &StrategyAST, data: &DataFrame) -> Result<StrategyResult> {
-        // Build the entire rule (not just the condition)
-        // The rule will return numeric signals: 1.0 for long, -1.0 for short, 0.0 for no action
-        let signal_expr = self.expression_builder.build(ast.root.as_ref(), data)?;
+        let mut results = self.run_batch(std::slice::from_ref(ast), data)?;
+        Ok(results.remove(0))
+    }
 
-        let signals = data
-            .clone()
-            .lazy()
-            .with_column(signal_expr.alias("signal"))
-            .collect()?;
+    /// Backtest every strategy in `asts` on `data`, returning results in the same order.
+    /// Their signals are computed in one query, so indicators and subtrees they share
+    /// are evaluated once; each strategy is then simulated on its own columns. A
+    /// strategy that fails to build fails the batch.
+    pub fn run_batch(&self, asts: &[StrategyAST], data: &DataFrame) -> Result<Vec<StrategyResult>> {
+        let prefixes: Vec<String> = (0..asts.len()).map(|i| format!("__s{}_", i)).collect();
+        let roots: Vec<Vec<Option<&AstNode>>> = asts.iter().map(Self::signal_roots).collect();
 
-        let signal_series = signals.column("signal")?;
-        let close_series = data.column("close")?;
+        let mut indicators = IndicatorColumns::with_fingerprint(self.fingerprint(data));
+        let compiled = self.expression_builder.compile(&roots.concat(), data, Some(&mut indicators))?;
 
-        let mut portfolio = Portfolio::new(self.initial_balance);
+        let mut built = compiled.roots.iter();
+        let mut columns = Vec::new();
+        for ((ast, roots), prefix) in asts.iter().zip(&roots).zip(&prefixes) {
+            let exprs: Vec<&Option<Expr>> = built.by_ref().take(roots.len()).collect();
+            match ast.root.as_ref() {
+                // Separate entry/exit rules become one boolean column each; a missing one never fires
+                AstNode::Strategy { entry_order, .. } => {
+                    let names = ["long_entry", "long_exit", "short_entry", "short_exit"];
+                    for (condition, name) in exprs.into_iter().zip(names) {
+                        let condition = match condition {
+                            Some(condition) => condition.clone().fill_null(lit(false)),
+                            None => lit(false),
+                        };
+                        columns.push(condition.alias(format!("{}{}", prefix, name)));
+                    }
+                    if let Some(order) = entry_order {
+                        let [long, short] = self.order_price_exprs(data, order)?;
+                        columns.push(long.alias(format!("{}long_order_price", prefix)));
+                        columns.push(short.alias(format!("{}short_order_price", prefix)));
+                    }
+                }
+                // The entire rule (not just the condition) returns numeric signals:
+                // 1.0 for long, -1.0 for short, 0.0 for no action
+                _ => {
+                    let signal = exprs[0].clone().expect("a present root is built");
+                    columns.push(signal.alias(format!("{}signal", prefix)));
+                }
+            }
+        }
+        if let Some(period) = self.trade_management.atr_period() {
+            columns.push(self.atr_expr(data, period)?.alias("__atr"));
+        }
 
-        for i in 0..signal_series.len() {
-            let signal = signal_series.f64()?.get(i).unwrap_or(0.0);
-            let price = close_series.f64()?.get(i).unwrap_or(0.0);
+        let signals = compiled.hoist(indicators.attach(data)?.lazy()).with_columns(columns).collect()?;
+        asts.par_iter()
+            .zip(&prefixes)
+            .map(|(ast, prefix)| self.simulate(ast, data, &signals, prefix))
+            .collect()
+    }
 
-            portfolio.process_bar(i, signal, price)?;
+    /// `IndicatorCache::fingerprint` of `data`, hashed only when it is not the frame
+    /// the previous run used
+    fn fingerprint(&self, data: &DataFrame) -> u64 {
+        let mut fingerprinted = self.fingerprinted.lock().unwrap();
+        if let Some((frame, fingerprint)) = fingerprinted.as_ref() {
+            if same_chunks(frame, data) {
+                return *fingerprint;
+            }
         }
+        let fingerprint = IndicatorCache::fingerprint(data);
+        *fingerprinted = Some((data.clone(), fingerprint));
+        fingerprint
+    }
 
-        let metrics = self.calculate_metrics(&portfolio)?;
+    /// The expressions a strategy's signals are built from: its four entry and exit
+    /// conditions, or the whole rule
+    fn signal_roots(ast: &StrategyAST) -> Vec<Option<&AstNode>> {
+        match ast.root.as_ref() {
+            AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+                [long_entry, long_exit, short_entry, short_exit].map(|c| c.as_deref()).to_vec()
+            }
+            root => vec![Some(root)],
+        }
+    }
+
+    /// Trade `ast` on its signal columns, named with `prefix`
+    fn simulate(&self, ast: &StrategyAST, data: &DataFrame, signals: &DataFrame, prefix: &str) -> Result<StrategyResult> {
+        let entry_order = match ast.root.as_ref() {
+            AstNode::Strategy { entry_order, .. } => *entry_order,
+            _ => None,
+        };
+        let funding_column = match &self.carry {
+            CarryCostModel::FundingRate { column } => Some(column.as_str()),
+            _ => None,
+        };
+        let bars = BarColumns::new(
+            data,
+            signals,
+            prefix,
+            entry_order,
+            funding_column,
+            self.trade_management.allow_hedging,
+        )?;
+
+        let run = if self.budget.abort_on_constant_signal && bars.is_constant(data.height()) {
+            Some((self.portfolio(), Some(AbortReason::ConstantSignal)))
+        } else if self.uses_fast_path(ast) {
+            self.run_vectorized(&bars, data.height())?
+        } else {
+            None
+        };
+        let (portfolio, aborted) = match run {
+            Some(run) => run,
+            None => self.run_bar_by_bar(&bars, data)?,
+        };
+
+        let mut metrics = self.calculate_metrics(&portfolio)?;
+        metrics.insert("aborted".to_string(), if aborted.is_some() { 1.0 } else { 0.0 });
 
         Ok(StrategyResult {
             ast: ast.root.as_ref().clone(),
             metrics,
             trades: portfolio.get_trades().to_vec(),
             equity_curve: portfolio.get_equity_curve().to_vec(),
+            ledger: portfolio.get_ledger().to_vec(),
+            order_events: portfolio.get_order_events().to_vec(),
             in_sample: true,
+            execution_timing: self.execution_timing,
+            intrabar: IntrabarResolution {
+                lower_timeframe: self.lower_timeframe.is_some(),
+                assumption: self.intrabar_assumption,
+                ambiguous: portfolio.intrabar_ambiguous,
+                resolved: portfolio.intrabar_resolved,
+            },
+            aborted,
         })
     }
 
+    /// Whether `run` takes the vectorized path for `ast`. It does when nothing but the
+    /// strategy's signals can open or close a lot: no stops, targets, time stops,
+    /// leverage, carry or entry orders, and a single lot at a time. Bars that would be
+    /// margin-called still send the run down the bar-by-bar path.
+    pub fn uses_fast_path(&self, ast: &StrategyAST) -> bool {
+        let trade_management = &self.trade_management;
+        self.fast_path
+            && trade_management.stop_loss == StopLossConfig::None
+            && trade_management.take_profit == TakeProfitConfig::None
+            && trade_management.max_bars_in_trade.is_none()
+            && trade_management.max_positions <= 1
+            && self.margin.leverage <= 1.0
+            && self.carry == CarryCostModel::None
+            && !matches!(ast.root.as_ref(), AstNode::Strategy { entry_order: Some(_), .. })
+    }
+
+    fn portfolio(&self) -> Portfolio {
+        Portfolio::new_with_trade_management(self.initial_balance, self.trade_management.clone())
+            .with_costs(self.commission.clone(), self.slippage.clone())
+            .with_execution_timing(self.execution_timing)
+            .with_margin(self.margin.clone())
+            .with_carry(self.carry.clone())
+            .with_intrabar_assumption(self.intrabar_assumption)
+    }
+
+    /// Feed every bar through the portfolio in turn, stopping early if the budget is breached
+    fn run_bar_by_bar(&self, bars: &BarColumns, data: &DataFrame) -> Result<(Portfolio, Option<AbortReason>)> {
+        let mut portfolio = self.portfolio();
+        let mut budget = BudgetCheck::new(&self.budget, data.height(), self.initial_balance);
+        let mut last = data.height().checked_sub(1);
+        let mut aborted = None;
+
+        let (sub_bars, paths) = match &self.lower_timeframe {
+            Some(lower) => Self::intrabar_paths(data, lower)?,
+            None => (Vec::new(), Vec::new()),
+        };
+
+        for i in 0..data.height() {
+            let path = paths.get(i).map_or(&[][..], |range| &sub_bars[range.clone()]);
+            portfolio.process_bar_with_path(i, bars.signals_at(i), &bars.prices_at(i), path)?;
+
+            let equity = portfolio.get_ledger().last().map_or(self.initial_balance, |entry| entry.equity);
+            aborted = budget.after_bar(i, equity, || portfolio.entries_opened());
+            if aborted.is_some() {
+                last = Some(i);
+                break;
+            }
+        }
+
+        if let Some(last) = last {
+            portfolio.close_at_end_of_data(last, &bars.prices_at(last))?;
+        }
+
+        Ok((portfolio, aborted))
+    }
+
+    /// Run the vectorized path over every bar, then replay the ledger against the
+    /// budget. A breached run is simulated again up to the bar it breached on, so it
+    /// ends exactly as the bar-by-bar path would have stopped it.
+    fn run_vectorized(&self, bars: &BarColumns, height: usize) -> Result<Option<(Portfolio, Option<AbortReason>)>> {
+        let Some(portfolio) = self.run_signals(bars, height)? else {
+            return Ok(None);
+        };
+
+        let mut budget = BudgetCheck::new(&self.budget, height, self.initial_balance);
+        let breach = portfolio.get_ledger().iter().find_map(|entry| {
+            let opened = || {
+                let entries = portfolio.get_trades().iter().filter(|t| t.entry_bar <= entry.bar);
+                entries.map(|t| t.entry_id).collect::<HashSet<_>>().len()
+            };
+            budget.after_bar(entry.bar, entry.equity, opened)
+        });
+
+        match breach {
+            Some(reason @ (AbortReason::MaxDrawdown { bar } | AbortReason::TooFewTrades { bar })) => {
+                Ok(self.run_signals(bars, bar + 1)?.map(|portfolio| (portfolio, Some(reason))))
+            }
+            _ => Ok(Some((portfolio, None))),
+        }
+    }
+
+    /// Over the first `height` bars, act only on those where a signal fired and mark the
+    /// rest column by column. `None` when a margin call means the run has to go bar by bar instead.
+    fn run_signals(&self, bars: &BarColumns, height: usize) -> Result<Option<Portfolio>> {
+        let mut portfolio = self.portfolio();
+
+        // Next-bar timings act on a signal one bar after it fires
+        let delay = match self.execution_timing {
+            ExecutionTiming::SameBarClose => 0,
+            ExecutionTiming::NextBarOpen | ExecutionTiming::NextBarClose => 1,
+        };
+        let signal_bars = bars
+            .fired()
+            .into_iter()
+            .filter(|&fired| fired + delay < height)
+            .map(|fired| SignalBar { bar: fired + delay, fired, signals: bars.signals_at(fired) });
+        let closes: Vec<f64> = bars.close.iter().take(height).map(|close| close.unwrap_or(0.0)).collect();
+
+        if !vectorized::run_signals(&mut portfolio, signal_bars, |i| bars.prices_at(i), &closes)? {
+            return Ok(None);
+        }
+
+        if let Some(last) = height.checked_sub(1) {
+            portfolio.close_at_end_of_data(last, &bars.prices_at(last))?;
+        }
+
+        Ok(Some(portfolio))
+    }
+
+    /// Bar start times in milliseconds from a `timestamp` column
+    fn bar_times(data: &DataFrame) -> Result<Vec<Option<i64>>> {
+        let timestamps = data.column("timestamp").map_err(|_| {
+            TradebiasError::Validation(
+                "Intrabar resolution needs a 'timestamp' column in both timeframes".to_string(),
+            )
+        })?;
+        // CSV files usually carry timestamps as text
+        if let Ok(text) = timestamps.str() {
+            return Ok(text
+                .into_iter()
+                .map(|t| {
+                    t.and_then(|t| {
+                        chrono::NaiveDateTime::parse_from_str(t, "%Y-%m-%d %H:%M:%S")
+                            .or_else(|_| chrono::NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M:%S"))
+                            .ok()
+                    })
+                    .map(|t| t.and_utc().timestamp_millis())
+                })
+                .collect());
+        }
+        let millis = timestamps
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+            .cast(&DataType::Int64)?;
+
+        Ok(millis.i64()?.into_iter().collect())
+    }
+
+    /// The lower timeframe's bars, and for each bar of `data` the range of them that
+    /// starts inside it. A bar ends where the next one starts; the last is taken to
+    /// be as long as the one before it.
+    fn intrabar_paths(data: &DataFrame, lower: &DataFrame) -> Result<(Vec<PriceBar>, Vec<Range<usize>>)> {
+        let bar_times = Self::bar_times(data)?;
+        let sub_times = Self::bar_times(lower)?;
+
+        // Minute data is often stored as integers
+        let price_series = |name: &str| -> Result<Column> {
+            let column = lower.column(name).or_else(|_| lower.column("close"))?;
+            Ok(column.cast(&DataType::Float64)?)
+        };
+        let (open, high, low, close) = (
+            price_series("open")?,
+            price_series("high")?,
+            price_series("low")?,
+            price_series("close")?,
+        );
+        let (open, high, low, close) = (open.f64()?, high.f64()?, low.f64()?, close.f64()?);
+        let sub_bars: Vec<PriceBar> = (0..lower.height())
+            .map(|j| {
+                let close = close.get(j).unwrap_or(f64::NAN);
+                PriceBar {
+                    open: open.get(j).unwrap_or(close),
+                    high: high.get(j).unwrap_or(close),
+                    low: low.get(j).unwrap_or(close),
+                    close,
+                    atr: None,
+                    funding_rate: None,
+                }
+            })
+            .collect();
+
+        let mut paths = Vec::with_capacity(bar_times.len());
+        let mut j = 0;
+        for (i, start) in bar_times.iter().enumerate() {
+            let end = match (bar_times.get(i + 1), i.checked_sub(1).and_then(|p| bar_times[p])) {
+                (Some(next), _) => *next,
+                (None, Some(previous)) => start.map(|s| 2 * s - previous),
+                (None, None) => None,
+            };
+            let (Some(start), Some(end)) = (*start, end) else {
+                paths.push(j..j);
+                continue;
+            };
+
+            while j < sub_times.len() && sub_times[j].is_none_or(|t| t < start) {
+                j += 1;
+            }
+            let first = j;
+            while j < sub_times.len() && sub_times[j].is_some_and(|t| t < end) {
+                j += 1;
+            }
+            paths.push(first..j);
+        }
+
+        Ok((sub_bars, paths))
+    }
+
+    /// A price column, or the close when the data does not have it
+    fn price_col(data: &DataFrame, name: &str) -> Expr {
+        if data.column(name).is_ok() {
+            col(name)
+        } else {
+            col("close")
+        }
+    }
+
+    /// ATR over the data's high/low/close, used for ATR stops and volatility sizing
+    fn atr_expr(&self, data: &DataFrame, period: usize) -> Result<Expr> {
+        ATR::new(period)
+            .calculate_vectorized(&[
+                IndicatorArg::Series(Self::price_col(data, "high")),
+                IndicatorArg::Series(Self::price_col(data, "low")),
+                IndicatorArg::Series(col("close")),
+                IndicatorArg::Scalar(period as f64),
+            ])
+            .map_err(|e| TradebiasError::IndicatorError(format!("ATR calculation failed: {}", e)))
+    }
+
+    /// Long and short order prices for each bar
+    fn order_price_exprs(&self, data: &DataFrame, order: &EntryOrder) -> Result<[Expr; 2]> {
+        let (below, above) = match order.reference {
+            PriceReference::Open => ("open", "open"),
+            PriceReference::High => ("high", "high"),
+            PriceReference::Low => ("low", "low"),
+            PriceReference::Close => ("close", "close"),
+            PriceReference::Extreme => ("low", "high"),
+        };
+        let offset = if order.atr_multiple > 0.0 {
+            self.atr_expr(data, order.atr_period.max(1))? * lit(order.atr_multiple)
+        } else {
+            lit(0.0)
+        };
+        let below = Self::price_col(data, below) - offset.clone();
+        let above = Self::price_col(data, above) + offset;
+
+        // Long limits and short stops rest below the market, the others above it
+        let (long, short) = match order.kind {
+            OrderKind::Limit => (below, above),
+            OrderKind::Stop => (above, below),
+        };
+
+        Ok([long, short])
+    }
+
     fn calculate_metrics(&self, portfolio: &Portfolio) -> Result<HashMap<String, f64>> {
         let mut metrics = HashMap::new();
 
-        let final_balance = portfolio.final_balance();
+        // Return, drawdown and Sharpe all come from the ledger's equity series
+        let equity_curve = portfolio.get_equity_curve();
+        let final_balance = equity_curve.last().copied().unwrap_or(self.initial_balance);
         let return_pct = (final_balance - self.initial_balance) / self.initial_balance * 100.0;
-        let trades = portfolio.get_trades();
+        let all_trades = portfolio.get_trades();
+        // Round trips the statistics below are computed over, so that partial
+        // closes of one entry count once
+        let trades = Trade::round_trips(
+            all_trades
+                .iter()
+                .filter(|t| self.include_end_of_data_in_stats || t.exit_reason != ExitReason::EndOfData),
+        );
 
         // Basic metrics
         metrics.insert("return_pct".to_string(), return_pct);
@@ -73,7 +712,7 @@ impl Backtester {
         metrics.insert("final_balance".to_string(), final_balance);
 
         // Drawdown (as percentage)
-        metrics.insert("max_drawdown".to_string(), portfolio.max_drawdown * 100.0);
+        metrics.insert("max_drawdown".to_string(), Self::max_drawdown(equity_curve) * 100.0);
 
         // Win rate
         if !trades.is_empty() {
@@ -101,9 +740,47 @@ impl Backtester {
         let sharpe_ratio = self.calculate_sharpe_ratio(portfolio);
         metrics.insert("sharpe_ratio".to_string(), sharpe_ratio);
 
+        // Gross figures alongside the net ones above, to show what costs take away
+        let total_fees: f64 = all_trades.iter().map(|t| t.fees).sum();
+        let total_slippage: f64 = all_trades.iter().map(|t| t.slippage).sum();
+        let total_carry: f64 = all_trades.iter().map(|t| t.carry).sum();
+        let gross_balance = final_balance + total_fees + total_slippage + total_carry;
+        let gross_return_pct = (gross_balance - self.initial_balance) / self.initial_balance * 100.0;
+
+        let gross_wins: f64 = trades.iter().map(|t| t.gross_profit()).filter(|p| *p > 0.0).sum();
+        let gross_losses: f64 = trades.iter().map(|t| t.gross_profit()).filter(|p| *p < 0.0).map(f64::abs).sum();
+        let gross_profit_factor = if gross_losses > 0.0 {
+            gross_wins / gross_losses
+        } else if gross_wins > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+
+        metrics.insert("total_fees".to_string(), total_fees);
+        metrics.insert("total_slippage".to_string(), total_slippage);
+        metrics.insert("total_carry".to_string(), total_carry);
+        metrics.insert("gross_return_pct".to_string(), gross_return_pct);
+        metrics.insert("gross_profit_factor".to_string(), gross_profit_factor);
+
         Ok(metrics)
     }
 
+    /// Largest peak-to-trough decline of the equity series, as a fraction of the peak
+    fn max_drawdown(equity_curve: &[f64]) -> f64 {
+        let mut peak = f64::MIN;
+        let mut max_drawdown: f64 = 0.0;
+
+        for &equity in equity_curve {
+            peak = peak.max(equity);
+            if peak > 0.0 {
+                max_drawdown = max_drawdown.max((peak - equity) / peak);
+            }
+        }
+
+        max_drawdown
+    }
+
     /// Calculate Sharpe ratio from equity curve
     /// Assumes daily returns, annualization factor = sqrt(252)
     fn calculate_sharpe_ratio(&self, portfolio: &Portfolio) -> f64 {
@@ -145,6 +822,19 @@ impl Backtester {
     }
 }
 
+/// Whether `b` has the same columns as `a`, built from the very same chunks
+fn same_chunks(a: &DataFrame, b: &DataFrame) -> bool {
+    a.height() == b.height()
+        && a.width() == b.width()
+        && a.get_columns().iter().zip(b.get_columns()).all(|(a, b)| {
+            let (a, b) = (a.as_materialized_series(), b.as_materialized_series());
+            a.name() == b.name()
+                && a.dtype() == b.dtype()
+                && a.chunks().len() == b.chunks().len()
+                && a.chunks().iter().zip(b.chunks()).all(|(a, b)| std::ptr::addr_eq(a.as_ref(), b.as_ref()))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,12 +875,224 @@ mod tests {
         let result = backtester.run(&ast, &df).unwrap();
 
         // Verify backtester ran successfully and produced metrics
-        // Note: With constant signal (always true -> 1.0 long), a position opens but never closes,
-        // so trades will be empty. This is expected behavior.
         assert!(result.metrics.contains_key("return_pct"), "Should have return_pct metric");
         assert!(result.equity_curve.len() > 0, "Should have equity curve");
 
-        // The constant signal should have opened a position but not closed it
-        assert_eq!(result.trades.len(), 0, "No completed trades with constant 1.0 signal");
+        // The constant signal opens a position that never closes on a signal,
+        // so it is closed at the last bar instead
+        assert_eq!(result.trades.len(), 1, "Open position should be closed at end of data");
+        assert_eq!(result.trades[0].exit_reason, ExitReason::EndOfData);
+        assert_eq!(result.trades[0].exit_bar, 7);
+        assert_eq!(result.metrics["num_trades"], 1.0);
+        assert_eq!(result.metrics["final_balance"], *result.equity_curve.last().unwrap());
+    }
+
+    #[test]
+    fn test_backtester_excludes_end_of_data_from_stats() {
+        let df = df! {
+            "close" => &[100.0, 101.0, 102.0, 103.0],
+        }
+        .unwrap();
+
+        let ast = StrategyAST {
+            root: Box::new(AstNode::Rule {
+                condition: Box::new(AstNode::Const(Value::Bool(true))),
+                action: Box::new(AstNode::Const(Value::Float(1.0))),
+            }),
+            metadata: StrategyMetadata::default(),
+        };
+
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(100));
+        let backtester = Backtester::new(registry, cache, 10000.0).with_end_of_data_in_stats(false);
+
+        let result = backtester.run(&ast, &df).unwrap();
+
+        // The trade is still recorded and its profit is in equity
+        assert_eq!(result.trades.len(), 1);
+        assert!(result.metrics["return_pct"] > 0.0);
+        assert_eq!(result.metrics["num_trades"], 0.0);
+        assert_eq!(result.metrics["win_rate"], 0.0);
+    }
+
+    #[test]
+    fn test_backtester_applies_stop_loss() {
+        use crate::config::trade_management::{StopLossConfig, TakeProfitConfig, TradeManagementConfig};
+        use crate::types::ExitReason;
+
+        let df = df! {
+            "close" => &[100.0, 99.0, 97.0, 96.0],
+            "high" => &[100.5, 100.0, 98.0, 97.0],
+            "low" => &[99.5, 98.5, 96.5, 95.0],
+        }
+        .unwrap();
+
+        let ast = StrategyAST {
+            root: Box::new(AstNode::Rule {
+                condition: Box::new(AstNode::Const(Value::Bool(true))),
+                action: Box::new(AstNode::Const(Value::Float(1.0))),
+            }),
+            metadata: StrategyMetadata::default(),
+        };
+
+        let trade_management = TradeManagementConfig {
+            stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+            take_profit: TakeProfitConfig::None,
+            ..TradeManagementConfig::signal_only()
+        };
+
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(100));
+        let backtester = Backtester::new(registry, cache, 10000.0)
+            .with_trade_management(trade_management);
+
+        let result = backtester.run(&ast, &df).unwrap();
+
+        // Entry at 100 with a 98 stop; bar 2's low of 96.5 triggers it
+        assert_eq!(result.trades[0].exit_reason, ExitReason::StopLoss);
+        assert_eq!(result.trades[0].exit_bar, 2);
+        assert_eq!(result.trades[0].exit_price, 98.0);
+    }
+
+    #[test]
+    fn test_backtester_reports_gross_and_net() {
+        use crate::config::backtesting::{CommissionModel, SlippageModel};
+        use crate::config::trade_management::{TakeProfitConfig, TradeManagementConfig};
+
+        let df = df! {
+            "close" => &[100.0, 110.0],
+        }
+        .unwrap();
+
+        // Always long, closed by a 5% take-profit on the second (last) bar
+        let ast = StrategyAST {
+            root: Box::new(AstNode::Rule {
+                condition: Box::new(AstNode::Const(Value::Bool(true))),
+                action: Box::new(AstNode::Const(Value::Float(1.0))),
+            }),
+            metadata: StrategyMetadata::default(),
+        };
+
+        let trade_management = TradeManagementConfig {
+            take_profit: TakeProfitConfig::FixedPercent { percent: 5.0 },
+            ..TradeManagementConfig::signal_only()
+        };
+
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(100));
+        let backtester = Backtester::new(registry, cache, 10000.0)
+            .with_trade_management(trade_management)
+            .with_costs(CommissionModel::PerTrade { amount: 5.0 }, SlippageModel::None);
+
+        let result = backtester.run(&ast, &df).unwrap();
+
+        // One round trip pays 5 on each side
+        assert_eq!(result.trades[0].fees, 10.0);
+        assert_eq!(result.metrics["total_fees"], 10.0);
+
+        let net = result.metrics["return_pct"];
+        let gross = result.metrics["gross_return_pct"];
+        assert!((gross - net - 0.1).abs() < 1e-9, "gross {} net {}", gross, net);
+    }
+
+    #[test]
+    fn test_backtester_next_bar_open_timing() {
+        let df = df! {
+            "open" => &[99.0, 101.0, 102.0],
+            "close" => &[100.0, 101.5, 103.0],
+        }
+        .unwrap();
+
+        let ast = StrategyAST {
+            root: Box::new(AstNode::Rule {
+                condition: Box::new(AstNode::Const(Value::Bool(true))),
+                action: Box::new(AstNode::Const(Value::Float(1.0))),
+            }),
+            metadata: StrategyMetadata::default(),
+        };
+
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(100));
+        let backtester = Backtester::new(registry, cache, 10000.0)
+            .with_execution_timing(ExecutionTiming::NextBarOpen);
+
+        let result = backtester.run(&ast, &df).unwrap();
+
+        // Bar 0's signal fills at bar 1's open, so bar 0 carries no exposure
+        assert_eq!(result.execution_timing, ExecutionTiming::NextBarOpen);
+        // The curve starts with the initial balance, then one point per bar
+        assert_eq!(result.equity_curve[1], 10000.0);
+        assert!(result.equity_curve[2] > 10000.0);
+    }
+
+    #[test]
+    fn test_backtester_counts_round_trips() {
+        use crate::config::trade_management::{ScaleOut, StopLossConfig, TakeProfitConfig, TradeManagementConfig};
+
+        let df = df! {
+            "close" => &[100.0, 102.5, 101.0],
+        }
+        .unwrap();
+
+        let ast = StrategyAST {
+            root: Box::new(AstNode::Rule {
+                condition: Box::new(AstNode::Const(Value::Bool(true))),
+                action: Box::new(AstNode::Const(Value::Float(1.0))),
+            }),
+            metadata: StrategyMetadata::default(),
+        };
+
+        // Half off at 1R (102), the rest closed at end of data
+        let trade_management = TradeManagementConfig {
+            stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+            take_profit: TakeProfitConfig::Ladder {
+                levels: vec![ScaleOut { r_multiple: 1.0, percent: 50.0 }],
+            },
+            ..TradeManagementConfig::signal_only()
+        };
+
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(100));
+        let backtester = Backtester::new(registry, cache, 10000.0)
+            .with_trade_management(trade_management);
+
+        let result = backtester.run(&ast, &df).unwrap();
+
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!(result.metrics["num_trades"], 1.0);
+        assert_eq!(result.metrics["win_rate"], 100.0);
+    }
+
+    #[test]
+    fn test_backtester_reads_funding_column() {
+        use crate::config::backtesting::CarryCostModel;
+
+        let df = df! {
+            "close" => &[100.0, 100.0, 100.0],
+            "funding" => &[0.001, 0.001, 0.001],
+        }
+        .unwrap();
+
+        let ast = StrategyAST {
+            root: Box::new(AstNode::Rule {
+                condition: Box::new(AstNode::Const(Value::Bool(true))),
+                action: Box::new(AstNode::Const(Value::Float(1.0))),
+            }),
+            metadata: StrategyMetadata::default(),
+        };
+
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(100));
+        let backtester = Backtester::new(registry.clone(), cache.clone(), 10000.0)
+            .with_carry(CarryCostModel::FundingRate { column: "funding".to_string() });
+
+        // 1000 notional held through bars 1 and 2, then closed at end of data
+        let result = backtester.run(&ast, &df).unwrap();
+        assert!((result.trades[0].carry - 2.0).abs() < 1e-9);
+        assert!((result.metrics["total_carry"] - 2.0).abs() < 1e-9);
+
+        let missing = Backtester::new(registry, cache, 10000.0)
+            .with_carry(CarryCostModel::FundingRate { column: "missing".to_string() });
+        assert!(missing.run(&ast, &df).is_err());
     }
 }
@@ -6,8 +6,94 @@ use crate::{
     types::{AstNode, Value},
 };
 use polars::prelude::*;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Indicator results materialised for one data frame. Expressions built with
+/// `build_cached` read them as columns, so evaluate those expressions on the
+/// frame `attach` returns.
+pub struct IndicatorColumns {
+    fingerprint: u64,
+    columns: Vec<Column>,
+    /// Column name of each cache key already attached
+    names: HashMap<String, String>,
+}
+
+impl IndicatorColumns {
+    pub fn new(df: &DataFrame) -> Self {
+        Self::with_fingerprint(IndicatorCache::fingerprint(df))
+    }
+
+    /// Reuse a fingerprint already computed for the frame
+    pub fn with_fingerprint(fingerprint: u64) -> Self {
+        Self { fingerprint, columns: Vec::new(), names: HashMap::new() }
+    }
+
+    /// `df` with the materialised columns appended
+    pub fn attach(&self, df: &DataFrame) -> Result<DataFrame> {
+        let mut frame = df.clone();
+        frame.hstack_mut(&self.columns)?;
+        Ok(frame)
+    }
+
+    /// A column holding `expr` evaluated on `df`, taken from `cache` when the
+    /// same subtree has already run on the same data
+    fn materialize(&mut self, cache: &IndicatorCache, node: &AstNode, expr: Expr, df: &DataFrame) -> Result<Expr> {
+        let key = format!("{:016x}:{}", self.fingerprint, subtree_key(node));
+        if let Some(name) = self.names.get(&key) {
+            return Ok(col(name.as_str()));
+        }
+
+        let name = format!("__cached_{}", self.columns.len());
+        let series = cache.get_or_compute(&key, || {
+            let frame = self.attach(df)?.lazy().select([expr.alias("value")]).collect()?;
+            Ok(frame.column("value")?.as_materialized_series().clone())
+        })?;
+        self.columns.push(series.with_name(name.as_str().into()).into_column());
+        self.names.insert(key, name.clone());
+        Ok(col(name.as_str()))
+    }
+}
+
+/// Root expressions whose repeated subtrees are hoisted into intermediate columns
+pub struct CompiledExprs {
+    /// Hoisted columns in stages; each stage reads only columns from earlier ones
+    pub stages: Vec<Vec<Expr>>,
+    /// One expression per root, `None` where the root was missing
+    pub roots: Vec<Option<Expr>>,
+}
+
+impl CompiledExprs {
+    /// `frame` with every hoisted column added, ready for the root expressions
+    pub fn hoist(&self, frame: LazyFrame) -> LazyFrame {
+        self.stages.iter().fold(frame, |frame, stage| frame.with_columns(stage.clone()))
+    }
+}
+
+/// What a build can reuse instead of emitting a subtree again
+#[derive(Default)]
+struct Scope<'a> {
+    indicators: Option<&'a mut IndicatorColumns>,
+    /// Column name and stage of each hoisted subtree, by key
+    hoisted: HashMap<String, (String, usize)>,
+}
+
+/// Identifies a subtree by its structure, constants included
+fn subtree_key(node: &AstNode) -> String {
+    format!("{:?}", node)
+}
+
+fn children(node: &AstNode) -> Vec<&AstNode> {
+    match node {
+        AstNode::Const(_) => Vec::new(),
+        AstNode::Call { args, .. } => args.iter().map(|arg| arg.as_ref()).collect(),
+        AstNode::Rule { condition, action } => vec![condition, action],
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            [long_entry, long_exit, short_entry, short_exit].into_iter().flatten().map(|c| c.as_ref()).collect()
+        }
+    }
+}
+
 pub struct ExpressionBuilder {
     registry: Arc<FunctionRegistry>,
     cache: Arc<IndicatorCache>,
@@ -18,11 +104,133 @@ impl ExpressionBuilder {
         Self { registry, cache }
     }
 
+    pub fn cache(&self) -> &Arc<IndicatorCache> {
+        &self.cache
+    }
+
     pub fn build(&self, ast: &AstNode, df: &DataFrame) -> Result<Expr> {
+        self.build_node(ast, df, &mut Scope::default())
+    }
+
+    /// Build `ast`, evaluating each indicator call once per subtree and dataset
+    /// through the shared cache into `columns`
+    pub fn build_cached(&self, ast: &AstNode, df: &DataFrame, columns: &mut IndicatorColumns) -> Result<Expr> {
+        self.build_node(ast, df, &mut Scope { indicators: Some(columns), ..Default::default() })
+    }
+
+    /// Build an optional entry/exit condition; a missing one is always false
+    pub fn build_condition(
+        &self,
+        condition: Option<&AstNode>,
+        df: &DataFrame,
+        columns: &mut IndicatorColumns,
+    ) -> Result<Expr> {
+        match condition {
+            Some(node) => Ok(self.build_cached(node, df, columns)?.fill_null(lit(false))),
+            None => Ok(lit(false)),
+        }
+    }
+
+    /// Build several roots together, computing each subtree they repeat once.
+    /// Repeated subtrees become intermediate columns added by `CompiledExprs::hoist`;
+    /// with `columns`, indicator calls are materialised through the cache instead.
+    pub fn compile(
+        &self,
+        roots: &[Option<&AstNode>],
+        df: &DataFrame,
+        columns: Option<&mut IndicatorColumns>,
+    ) -> Result<CompiledExprs> {
+        let mut scope = Scope { indicators: columns, ..Default::default() };
+        let mut counts = HashMap::new();
+        for root in roots.iter().flatten() {
+            self.count_subtrees(root, &scope, &mut counts);
+        }
+
+        let mut stages = Vec::new();
+        for root in roots.iter().flatten() {
+            self.hoist_repeated(root, df, &counts, &mut scope, &mut stages)?;
+        }
+        let roots = roots
+            .iter()
+            .map(|root| root.map(|root| self.build_node(root, df, &mut scope)).transpose())
+            .collect::<Result<_>>()?;
+
+        Ok(CompiledExprs { stages, roots })
+    }
+
+    /// Indicator calls evaluated eagerly into `IndicatorColumns` rather than inline
+    fn is_materialized(&self, node: &AstNode, scope: &Scope) -> bool {
+        match node {
+            AstNode::Call { function, .. } => {
+                scope.indicators.is_some() && self.registry.get_indicator(function).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    fn count_subtrees(&self, node: &AstNode, scope: &Scope, counts: &mut HashMap<String, usize>) {
+        if matches!(node, AstNode::Call { args, .. } if !args.is_empty()) {
+            *counts.entry(subtree_key(node)).or_insert(0) += 1;
+        }
+        if self.is_materialized(node, scope) {
+            return;
+        }
+        for child in children(node) {
+            self.count_subtrees(child, scope, counts);
+        }
+    }
+
+    /// Hoist every subtree of `node` that occurs more than once, innermost first.
+    /// Returns how many stages must run before `node` can be evaluated.
+    fn hoist_repeated(
+        &self,
+        node: &AstNode,
+        df: &DataFrame,
+        counts: &HashMap<String, usize>,
+        scope: &mut Scope,
+        stages: &mut Vec<Vec<Expr>>,
+    ) -> Result<usize> {
+        if self.is_materialized(node, scope) {
+            return Ok(0);
+        }
+        let key = subtree_key(node);
+        if let Some((_, stage)) = scope.hoisted.get(&key) {
+            return Ok(stage + 1);
+        }
+
+        let mut ready = 0;
+        for child in children(node) {
+            ready = ready.max(self.hoist_repeated(child, df, counts, scope, stages)?);
+        }
+        if counts.get(&key).copied().unwrap_or(0) < 2 {
+            return Ok(ready);
+        }
+
+        let name = format!("__shared_{}", scope.hoisted.len());
+        let expr = self.build_node(node, df, scope)?;
+        if stages.len() <= ready {
+            stages.resize_with(ready + 1, Vec::new);
+        }
+        stages[ready].push(expr.alias(name.as_str()));
+        scope.hoisted.insert(key, (name, ready));
+        Ok(ready + 1)
+    }
+
+    fn build_node(&self, ast: &AstNode, df: &DataFrame, scope: &mut Scope) -> Result<Expr> {
         match ast {
             AstNode::Const(value) => self.build_const(value),
-            AstNode::Call { function, args } => self.build_call(function, args, df),
-            AstNode::Rule { condition, action } => self.build_rule(condition, action, df),
+            AstNode::Call { function, args } => {
+                if !scope.hoisted.is_empty() {
+                    if let Some((name, _)) = scope.hoisted.get(&subtree_key(ast)) {
+                        return Ok(col(name.as_str()));
+                    }
+                }
+                self.build_call(ast, function, args, df, scope)
+            }
+            AstNode::Rule { condition, action } => self.build_rule(condition, action, df, scope),
+            AstNode::Strategy { .. } => Err(TradebiasError::Validation(
+                "Strategy nodes have no single expression; build each condition separately".to_string(),
+            )),
         }
     }
 
@@ -35,7 +243,14 @@ impl ExpressionBuilder {
         })
     }
 
-    fn build_call(&self, function: &str, args: &[Box<AstNode>], df: &DataFrame) -> Result<Expr> {
+    fn build_call(
+        &self,
+        node: &AstNode,
+        function: &str,
+        args: &[Box<AstNode>],
+        df: &DataFrame,
+        scope: &mut Scope,
+    ) -> Result<Expr> {
         // Handle data accessors (OHLCV columns) as special case
         match function {
             "Open" => return Ok(col("open")),
@@ -46,16 +261,23 @@ impl ExpressionBuilder {
             _ => {}
         }
 
-        // Note: Caching is disabled because we return expressions directly rather than
-        // evaluating them. Caching Series and then converting to lit() causes stack
-        // overflow with deeply nested expressions. Expression-level caching would
-        // require caching Expr objects, which is not straightforward.
-        // Performance impact is minimal for max_depth <= 3.
-
+        // Cached indicators come back as columns of the frame rather than lit(series),
+        // which overflows the stack with deeply nested expressions. Primitives are
+        // cheap elementwise operations and stay inline.
         if let Some(indicator) = self.registry.get_indicator(function) {
-            self.build_indicator_call(indicator.as_ref(), args, df)
+            if scope.indicators.is_none() {
+                return self.build_indicator_call(indicator.as_ref(), args, df, scope);
+            }
+            // Materialised on the frame alone, before any hoisted column exists
+            let hoisted = std::mem::take(&mut scope.hoisted);
+            let expr = self.build_indicator_call(indicator.as_ref(), args, df, scope);
+            scope.hoisted = hoisted;
+            match scope.indicators.as_deref_mut() {
+                Some(columns) => columns.materialize(&self.cache, node, expr?, df),
+                None => expr,
+            }
         } else if let Some(primitive) = self.registry.get_primitive(function) {
-            self.build_primitive_call(primitive.as_ref(), args, df)
+            self.build_primitive_call(primitive.as_ref(), args, df, scope)
         } else {
             Err(TradebiasError::IndicatorError(format!(
                 "Function {} not found",
@@ -64,9 +286,15 @@ impl ExpressionBuilder {
         }
     }
 
-    fn build_rule(&self, condition: &AstNode, action: &AstNode, df: &DataFrame) -> Result<Expr> {
-        let cond_expr = self.build(condition, df)?;
-        let action_expr = self.build(action, df)?;
+    fn build_rule(
+        &self,
+        condition: &AstNode,
+        action: &AstNode,
+        df: &DataFrame,
+        scope: &mut Scope,
+    ) -> Result<Expr> {
+        let cond_expr = self.build_node(condition, df, scope)?;
+        let action_expr = self.build_node(action, df, scope)?;
         Ok(when(cond_expr).then(action_expr).otherwise(lit(0.0)))
     }
 
@@ -75,6 +303,7 @@ impl ExpressionBuilder {
         indicator: &dyn Indicator,
         args: &[Box<AstNode>],
         df: &DataFrame,
+        scope: &mut Scope,
     ) -> Result<Expr> {
         // Build args and convert to IndicatorArg based on input types
         let input_types = indicator.input_types();
@@ -91,22 +320,22 @@ impl ExpressionBuilder {
                                 Value::Integer(v) => IndicatorArg::Scalar(*v as f64),
                                 Value::Float(v) => IndicatorArg::Scalar(*v),
                                 _ => {
-                                    let arg_expr = self.build(arg, df)?;
+                                    let arg_expr = self.build_node(arg, df, scope)?;
                                     IndicatorArg::Series(arg_expr)
                                 }
                             }
                         } else {
-                            let arg_expr = self.build(arg, df)?;
+                            let arg_expr = self.build_node(arg, df, scope)?;
                             IndicatorArg::Series(arg_expr)
                         }
                     }
                     _ => {
-                        let arg_expr = self.build(arg, df)?;
+                        let arg_expr = self.build_node(arg, df, scope)?;
                         IndicatorArg::Series(arg_expr)
                     }
                 }
             } else {
-                let arg_expr = self.build(arg, df)?;
+                let arg_expr = self.build_node(arg, df, scope)?;
                 IndicatorArg::Series(arg_expr)
             };
 
@@ -130,18 +359,10 @@ impl ExpressionBuilder {
         primitive: &dyn Primitive,
         args: &[Box<AstNode>],
         df: &DataFrame,
+        scope: &mut Scope,
     ) -> Result<Expr> {
-        let arg_exprs: Result<Vec<Expr>> = args.iter().map(|arg| self.build(arg, df)).collect();
+        let arg_exprs: Result<Vec<Expr>> = args.iter().map(|arg| self.build_node(arg, df, scope)).collect();
         primitive.execute(&arg_exprs?)
             .map_err(|e| TradebiasError::IndicatorError(format!("Primitive execution failed: {}", e)))
     }
-
-    fn create_cache_key(
-        &self,
-        function: &str,
-        args: &[Box<AstNode>],
-        _df: &DataFrame,
-    ) -> Result<String> {
-        Ok(format!("{}-{:?}", function, args))
-    }
 }
@@ -1,7 +1,8 @@
 pub mod backtester;
 pub mod expression;
 pub mod portfolio;
+pub mod vectorized;
 
 pub use backtester::Backtester;
-pub use expression::ExpressionBuilder;
+pub use expression::{CompiledExprs, ExpressionBuilder, IndicatorColumns};
 pub use portfolio::Portfolio;
@@ -1,132 +1,1155 @@
 use crate::{
+    config::backtesting::{
+        CarryCostModel, CommissionModel, ExecutionTiming, IntrabarAssumption, MarginConfig, SlippageModel,
+    },
+    config::trade_management::{PositionSizing, StopLossConfig, TakeProfitConfig, TradeManagementConfig},
     error::Result,
-    types::{Direction, ExitReason, Trade},
+    types::{Direction, ExitReason, LedgerEntry, OrderEvent, OrderKind, OrderStatus, Trade},
 };
 
+/// Completed trades required before Kelly sizing trusts its own estimate
+const KELLY_MIN_TRADES: usize = 10;
+
+/// Prices for a single bar, plus the ATR value when trade management needs one
+/// and the funding rate when the data carries one.
+#[derive(Debug, Clone, Copy)]
+pub struct PriceBar {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub atr: Option<f64>,
+    /// Funding paid by longs over this bar, as a fraction of notional
+    pub funding_rate: Option<f64>,
+}
+
+impl PriceBar {
+    /// A bar with the given prices and no ATR or funding.
+    pub fn new(open: f64, high: f64, low: f64, close: f64) -> Self {
+        Self {
+            open,
+            high,
+            low,
+            close,
+            atr: None,
+            funding_rate: None,
+        }
+    }
+
+    /// A bar with no intrabar range, for when only the close is known.
+    pub fn from_close(price: f64) -> Self {
+        Self::new(price, price, price, price)
+    }
+}
+
+/// Entry and exit conditions that fired on one bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BarSignals {
+    pub long_entry: bool,
+    pub long_exit: bool,
+    pub short_entry: bool,
+    pub short_exit: bool,
+    /// Enter with a resting order rather than at market
+    pub entry_order: Option<OrderRequest>,
+}
+
+/// A limit or stop entry order requested by a signal, already priced for the
+/// direction it will be placed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderRequest {
+    pub kind: OrderKind,
+    pub price: f64,
+    /// Bars the order may fill on before it expires
+    pub expiry_bars: usize,
+}
+
+/// An entry order resting in the book until it fills or expires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingOrder {
+    pub order_id: usize,
+    pub direction: Direction,
+    pub kind: OrderKind,
+    pub price: f64,
+    /// Last bar the order may fill on
+    pub expires_after: usize,
+}
+
+impl BarSignals {
+    /// Interpret a single signed signal: positive enters long and negative enters short.
+    /// Unless hedging, each also exits the opposite side.
+    pub fn from_signal(signal: f64, allow_hedging: bool) -> Self {
+        let long = signal > 0.0;
+        let short = signal < 0.0;
+        Self {
+            long_entry: long,
+            long_exit: short && !allow_hedging,
+            short_entry: short,
+            short_exit: long && !allow_hedging,
+            entry_order: None,
+        }
+    }
+
+    /// Direction to open a lot in. Entries on both sides at once cancel out.
+    pub fn entry_direction(&self) -> Option<Direction> {
+        match (self.long_entry, self.short_entry) {
+            (true, false) => Some(Direction::Long),
+            (false, true) => Some(Direction::Short),
+            _ => None,
+        }
+    }
+}
+
 pub struct Portfolio {
     pub initial_capital: f64,
     pub cash: f64,
-    pub position: Option<Position>,
+    /// Open lots, oldest first. Holds at most `trade_management.max_positions`.
+    pub positions: Vec<Position>,
     pub trades: Vec<Trade>,
     pub equity_curve: Vec<f64>,
+    /// One mark-to-market entry per processed bar; `equity_curve` is built from it
+    pub ledger: Vec<LedgerEntry>,
 
     // P&L and Drawdown Tracking
     pub realized_pnl: f64,
     pub unrealized_pnl: f64,
     pub total_pnl: f64,
     pub current_position_value: f64,
+    pub margin_used: f64,
+    pub maintenance_margin: f64,
     pub peak_equity: f64,
     pub max_drawdown: f64,
     pub current_drawdown: f64,
+
+    pub trade_management: TradeManagementConfig,
+    pub commission: CommissionModel,
+    pub slippage: SlippageModel,
+    pub execution_timing: ExecutionTiming,
+    pub margin: MarginConfig,
+    pub carry: CarryCostModel,
+    pub intrabar_assumption: IntrabarAssumption,
+    /// Stop-or-target fills that the bar's open could not decide
+    pub intrabar_ambiguous: usize,
+    /// Of those, fills decided by walking lower-timeframe bars
+    pub intrabar_resolved: usize,
+
+    /// Signals (and their bar's ATR) waiting to be executed on the next bar
+    pending_signals: Option<(BarSignals, Option<f64>)>,
+    /// Id given to the next entry, so partial closes can be traced back to it
+    next_entry_id: usize,
+
+    /// Limit and stop entry orders waiting to fill, oldest first
+    pub pending_orders: Vec<PendingOrder>,
+    /// Every placement, fill, expiry and cancellation of an entry order
+    pub order_events: Vec<OrderEvent>,
+    next_order_id: usize,
 }
 
+/// One lot in the position book, with its own entry, stop and target.
+#[derive(Debug, Clone)]
 pub struct Position {
+    pub entry_id: usize,
     pub direction: Direction,
     pub entry_bar: usize,
     pub entry_price: f64,
     pub size: f64,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+    /// Remaining scale-out levels as (price, size), nearest first
+    pub scale_outs: Vec<(f64, f64)>,
+    /// Commission paid on entry, already deducted from cash
+    pub entry_fees: f64,
+    /// Cost of entry slippage in currency, already reflected in `entry_price`
+    pub entry_slippage: f64,
+    /// Funding or borrow cost accrued so far, already deducted from cash
+    pub carry: f64,
+    /// Initial stop distance (1R), if the lot had a stop at entry
+    pub initial_risk: Option<f64>,
+    /// Best price reached since entry: highest high for longs, lowest low for shorts
+    pub best_price: f64,
+    /// Reason reported if `stop_loss` is hit, which changes as the stop is moved
+    pub stop_reason: ExitReason,
+    /// Price at which the lot's own margin is exhausted, when trading with leverage
+    pub liquidation_price: Option<f64>,
 }
 
 impl Portfolio {
     pub fn new(initial_capital: f64) -> Self {
+        Self::new_with_trade_management(initial_capital, TradeManagementConfig::signal_only())
+    }
+
+    /// Create a Portfolio that applies the given stop, target and sizing rules
+    pub fn new_with_trade_management(
+        initial_capital: f64,
+        trade_management: TradeManagementConfig,
+    ) -> Self {
         Self {
             initial_capital,
             cash: initial_capital,
-            position: None,
+            positions: Vec::new(),
             trades: Vec::new(),
             equity_curve: vec![initial_capital],
+            ledger: Vec::new(),
             realized_pnl: 0.0,
             unrealized_pnl: 0.0,
             total_pnl: 0.0,
             current_position_value: 0.0,
+            margin_used: 0.0,
+            maintenance_margin: 0.0,
             peak_equity: initial_capital,
             max_drawdown: 0.0,
             current_drawdown: 0.0,
+            trade_management,
+            commission: CommissionModel::None,
+            slippage: SlippageModel::None,
+            execution_timing: ExecutionTiming::SameBarClose,
+            margin: MarginConfig::spot(),
+            carry: CarryCostModel::None,
+            intrabar_assumption: IntrabarAssumption::Pessimistic,
+            intrabar_ambiguous: 0,
+            intrabar_resolved: 0,
+            pending_signals: None,
+            next_entry_id: 0,
+            pending_orders: Vec::new(),
+            order_events: Vec::new(),
+            next_order_id: 0,
         }
     }
 
+    /// Charge the given commission and slippage on every fill
+    pub fn with_costs(mut self, commission: CommissionModel, slippage: SlippageModel) -> Self {
+        self.commission = commission;
+        self.slippage = slippage;
+        self
+    }
+
+    /// Choose when a bar's signal is filled
+    pub fn with_execution_timing(mut self, execution_timing: ExecutionTiming) -> Self {
+        self.execution_timing = execution_timing;
+        self
+    }
+
+    /// Trade with leverage, subject to liquidation and margin calls
+    pub fn with_margin(mut self, margin: MarginConfig) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Charge funding or borrow costs on positions held from bar to bar
+    pub fn with_carry(mut self, carry: CarryCostModel) -> Self {
+        self.carry = carry;
+        self
+    }
+
+    /// Choose whether a stop or a target inside the same bar fills first
+    /// when there is no lower-timeframe path to decide
+    pub fn with_intrabar_assumption(mut self, assumption: IntrabarAssumption) -> Self {
+        self.intrabar_assumption = assumption;
+        self
+    }
+
     pub fn process_bar(&mut self, bar: usize, signal: f64, price: f64) -> Result<()> {
-        if self.position.is_none() && signal != 0.0 {
-            self.open_position(bar, signal, price)?;
-        } else if self.position.is_some() {
-            self.check_exit(bar, signal, price)?;
+        self.process_price_bar(bar, signal, &PriceBar::from_close(price))
+    }
+
+    /// Process a bar with its full price range, so stops and targets can trigger intrabar.
+    /// `signal` is the strategy's output at this bar's close, read as in [`BarSignals::from_signal`].
+    pub fn process_price_bar(&mut self, bar: usize, signal: f64, prices: &PriceBar) -> Result<()> {
+        let signals = BarSignals::from_signal(signal, self.trade_management.allow_hedging);
+        self.process_bar_signals(bar, signals, prices)
+    }
+
+    /// Process a bar given separate entry and exit conditions, evaluated at this bar's
+    /// close. With a next-bar execution timing they are held and acted on when the
+    /// following bar arrives.
+    pub fn process_bar_signals(&mut self, bar: usize, signals: BarSignals, prices: &PriceBar) -> Result<()> {
+        self.process_bar_with_path(bar, signals, prices, &[])
+    }
+
+    /// Process one bar together with its lower-timeframe bars, in time order.
+    /// The path is walked to tell whether a stop or a target inside the bar traded first.
+    pub fn process_bar_with_path(
+        &mut self,
+        bar: usize,
+        signals: BarSignals,
+        prices: &PriceBar,
+        path: &[PriceBar],
+    ) -> Result<()> {
+        let (signals, atr) = match self.execution_timing {
+            ExecutionTiming::SameBarClose => (signals, prices.atr),
+            ExecutionTiming::NextBarOpen | ExecutionTiming::NextBarClose => self
+                .pending_signals
+                .replace((signals, prices.atr))
+                .unwrap_or_default(),
+        };
+
+        // A bar that closes any lot does not also open one
+        if self.execution_timing == ExecutionTiming::NextBarOpen {
+            // Orders fill at the open, then the rest of the bar can hit stops
+            let exited = self.check_exit(bar, &signals, prices.open, prices)?;
+            if let Some(direction) = signals.entry_direction().filter(|_| !exited) {
+                match signals.entry_order {
+                    Some(order) => self.place_order(bar, direction, order, true),
+                    None => {
+                        self.enter_position(bar, direction, prices.open, atr, prices)?;
+                    }
+                }
+            }
+
+            // Resting orders, including one placed at this open, trade the bar's range
+            self.check_orders(bar, prices)?;
+            self.check_stops(bar, prices, path)?;
+            self.check_time_stops(bar, prices)?;
+        } else {
+            // Orders placed on earlier bars trade this bar's range
+            self.check_orders(bar, prices)?;
+            let stopped = self.check_stops(bar, prices, path)?;
+            let exited = self.check_exit(bar, &signals, prices.close, prices)?;
+            let timed_out = self.check_time_stops(bar, prices)?;
+
+            if let Some(direction) = signals
+                .entry_direction()
+                .filter(|_| !stopped && !exited && !timed_out)
+            {
+                match signals.entry_order {
+                    Some(order) => self.place_order(bar, direction, order, false),
+                    None => {
+                        self.enter_position(bar, direction, prices.close, atr, prices)?;
+                    }
+                }
+            }
         }
 
-        // Calculate unrealized P&L with the current price
-        self.calculate_unrealized_pnl(price);
+        self.update_stops(bar, prices);
+        self.accrue_carry(bar, prices);
 
-        // Update drawdown with the current equity
+        // Mark open lots to the close and record the bar
+        self.calculate_unrealized_pnl(prices.close);
+        self.check_margin(bar, prices)?;
         self.update_drawdown();
+        let entry = self.ledger_entry(bar);
+        self.ledger.push(entry);
+        self.equity_curve.push(entry.equity);
+
+        Ok(())
+    }
+
+    /// Whether `apply_signals` would do anything: a lot on a side whose exit fired,
+    /// or an entry that `can_enter` allows
+    pub(crate) fn acts_on(&self, signals: &BarSignals) -> bool {
+        let exits = self.positions.iter().any(|p| match p.direction {
+            Direction::Long => signals.long_exit,
+            Direction::Short => signals.short_exit,
+        });
+        exits || signals.entry_direction().is_some_and(|direction| self.can_enter(direction))
+    }
+
+    /// Act on signals (already delayed by the execution timing) with nothing else able
+    /// to trade: close lots on their exit signal, otherwise open one on an entry.
+    /// This is `process_bar_with_path` without orders, stops, carry or the mark,
+    /// for the vectorized path, which only uses it when those can have no effect.
+    pub(crate) fn apply_signals(
+        &mut self,
+        bar: usize,
+        signals: &BarSignals,
+        atr: Option<f64>,
+        prices: &PriceBar,
+    ) -> Result<()> {
+        let price = match self.execution_timing {
+            ExecutionTiming::NextBarOpen => prices.open,
+            ExecutionTiming::SameBarClose | ExecutionTiming::NextBarClose => prices.close,
+        };
 
-        // Update the equity curve
-        self.equity_curve.push(self.equity());
+        let exited = self.check_exit(bar, signals, price, prices)?;
+        if let Some(direction) = signals.entry_direction().filter(|_| !exited) {
+            self.enter_position(bar, direction, price, atr, prices)?;
+        }
 
         Ok(())
     }
 
+    /// Account state as of the last mark
+    fn ledger_entry(&self, bar: usize) -> LedgerEntry {
+        LedgerEntry {
+            bar,
+            cash: self.cash,
+            position_value: self.current_position_value,
+            margin_used: self.margin_used,
+            maintenance_margin: self.maintenance_margin,
+            realized_pnl: self.realized_pnl,
+            unrealized_pnl: self.unrealized_pnl,
+            equity: self.equity(),
+        }
+    }
+
     pub fn open_position(&mut self, bar: usize, signal: f64, price: f64) -> Result<()> {
         let direction = if signal > 0.0 {
             Direction::Long
         } else {
             Direction::Short
         };
-        let quantity = (self.cash * 0.1) / price;
+        self.enter_position(bar, direction, price, None, &PriceBar::from_close(price))?;
+        Ok(())
+    }
+
+    /// Whether a new lot in `direction` may be opened: the book is not full,
+    /// and it would not hedge an opposite position unless hedging is allowed.
+    fn can_enter(&self, direction: Direction) -> bool {
+        self.positions.len() < self.trade_management.max_positions.max(1)
+            && (self.trade_management.allow_hedging
+                || self.positions.iter().all(|p| p.direction == direction))
+    }
+
+    /// Open a new lot at market at `price` within `prices`' bar, after slippage.
+    /// Returns whether a lot was opened.
+    fn enter_position(
+        &mut self,
+        bar: usize,
+        direction: Direction,
+        price: f64,
+        atr: Option<f64>,
+        prices: &PriceBar,
+    ) -> Result<bool> {
+        let slip = self.slippage_per_unit(price, prices);
+        self.fill_position(bar, direction, price, slip, atr)
+    }
+
+    /// Open a new lot at `price` moved `slip` against it. Nothing happens
+    /// when `can_enter` refuses the lot or it sizes to nothing.
+    fn fill_position(
+        &mut self,
+        bar: usize,
+        direction: Direction,
+        price: f64,
+        slip: f64,
+        atr: Option<f64>,
+    ) -> Result<bool> {
+        if !self.can_enter(direction) {
+            return Ok(false);
+        }
+
+        // Buying fills higher, selling short fills lower
+        let price = match direction {
+            Direction::Long => price + slip,
+            Direction::Short => price - slip,
+        };
+        let stop_distance = self.stop_distance(price, atr);
+        let target_distance = self.target_distance(price, stop_distance);
+
+        let quantity = self.position_size(price, stop_distance, atr);
+        if quantity <= 0.0 {
+            return Ok(false);
+        }
 
         match direction {
             Direction::Long => self.cash -= quantity * price,
             Direction::Short => self.cash += quantity * price, // Add proceeds from short sale
         }
 
-        self.position = Some(Position {
+        let entry_fees = self.commission_for(quantity, price);
+        self.cash -= entry_fees;
+        self.realized_pnl -= entry_fees;
+
+        let (stop_loss, take_profit) = match direction {
+            Direction::Long => (
+                stop_distance.map(|d| price - d),
+                target_distance.map(|d| price + d),
+            ),
+            Direction::Short => (
+                stop_distance.map(|d| price + d),
+                target_distance.map(|d| price - d),
+            ),
+        };
+
+        // Scale-out sizes are fractions of the original size; they need a stop to measure R
+        let mut scale_outs = Vec::new();
+        if let (TakeProfitConfig::Ladder { levels }, Some(risk)) =
+            (&self.trade_management.take_profit, stop_distance)
+        {
+            let side = match direction {
+                Direction::Long => 1.0,
+                Direction::Short => -1.0,
+            };
+            let mut levels = levels.clone();
+            levels.sort_by(|a, b| a.r_multiple.total_cmp(&b.r_multiple));
+            for level in levels.iter().filter(|l| l.r_multiple > 0.0 && l.percent > 0.0) {
+                scale_outs.push((price + side * level.r_multiple * risk, quantity * level.percent / 100.0));
+            }
+        }
+
+        let entry_id = self.next_entry_id;
+        self.next_entry_id += 1;
+
+        self.positions.push(Position {
+            entry_id,
             direction,
             entry_bar: bar,
             entry_price: price,
             size: quantity,
+            stop_loss,
+            take_profit,
+            scale_outs,
+            entry_fees,
+            entry_slippage: slip * quantity,
+            carry: 0.0,
+            initial_risk: stop_distance,
+            best_price: price,
+            liquidation_price: self.liquidation_price(direction, price),
+            stop_reason: match self.trade_management.stop_loss {
+                StopLossConfig::TrailingPercent { .. }
+                | StopLossConfig::TrailingATR { .. }
+                | StopLossConfig::Chandelier { .. } => ExitReason::TrailingStop,
+                _ => ExitReason::StopLoss,
+            },
+        });
+
+        Ok(true)
+    }
+
+    /// Rest an entry order in `direction`, replacing any order already waiting on
+    /// that side. An order placed at the open may fill on the same bar; one placed
+    /// at the close is live from the next bar.
+    fn place_order(&mut self, bar: usize, direction: Direction, order: OrderRequest, at_open: bool) {
+        if !order.price.is_finite() || order.price <= 0.0 || !self.can_enter(direction) {
+            return;
+        }
+
+        self.cancel_orders(bar, |o| o.direction == direction);
+
+        let pending = PendingOrder {
+            order_id: self.next_order_id,
+            direction,
+            kind: order.kind,
+            price: order.price,
+            expires_after: bar + order.expiry_bars.max(1) - usize::from(at_open),
+        };
+        self.next_order_id += 1;
+        self.pending_orders.push(pending);
+        self.record_order(&pending, bar, pending.price, OrderStatus::Placed);
+    }
+
+    /// Fill resting orders that `prices` trades through, then expire any
+    /// that have run out of bars. Returns whether any order filled.
+    fn check_orders(&mut self, bar: usize, prices: &PriceBar) -> Result<bool> {
+        let mut filled = false;
+        let mut i = 0;
+
+        while i < self.pending_orders.len() {
+            let order = self.pending_orders[i];
+
+            if let Some(price) = Self::order_fill(&order, prices) {
+                self.pending_orders.remove(i);
+                // A triggered stop becomes a market order; a limit fills at its price
+                let slip = match order.kind {
+                    OrderKind::Limit => 0.0,
+                    OrderKind::Stop => self.slippage_per_unit(price, prices),
+                };
+                if self.fill_position(bar, order.direction, price, slip, prices.atr)? {
+                    let entry_price = self.positions.last().map_or(price, |p| p.entry_price);
+                    self.record_order(&order, bar, entry_price, OrderStatus::Filled);
+                    filled = true;
+                } else {
+                    self.record_order(&order, bar, order.price, OrderStatus::Cancelled);
+                }
+            } else if bar >= order.expires_after {
+                self.pending_orders.remove(i);
+                self.record_order(&order, bar, order.price, OrderStatus::Expired);
+            } else {
+                i += 1;
+            }
+        }
+
+        Ok(filled)
+    }
+
+    /// The price `order` fills at within `prices`' bar, if it is reached.
+    /// A bar that opens through the order price fills at the open.
+    fn order_fill(order: &PendingOrder, prices: &PriceBar) -> Option<f64> {
+        // Buy limits and sell stops wait below the market; buy stops and sell limits above it
+        let below = matches!(
+            (order.direction, order.kind),
+            (Direction::Long, OrderKind::Limit) | (Direction::Short, OrderKind::Stop)
+        );
+
+        if below {
+            (prices.low <= order.price).then(|| order.price.min(prices.open))
+        } else {
+            (prices.high >= order.price).then(|| order.price.max(prices.open))
+        }
+    }
+
+    /// Withdraw the resting orders matching `predicate`
+    fn cancel_orders<F>(&mut self, bar: usize, predicate: F)
+    where
+        F: Fn(&PendingOrder) -> bool,
+    {
+        let (cancelled, kept): (Vec<_>, Vec<_>) =
+            self.pending_orders.drain(..).partition(|o| predicate(o));
+        self.pending_orders = kept;
+        for order in cancelled {
+            self.record_order(&order, bar, order.price, OrderStatus::Cancelled);
+        }
+    }
+
+    fn record_order(&mut self, order: &PendingOrder, bar: usize, price: f64, status: OrderStatus) {
+        self.order_events.push(OrderEvent {
+            order_id: order.order_id,
+            bar,
+            direction: order.direction,
+            kind: order.kind,
+            price,
+            status,
         });
+    }
+
+    /// Number of units to buy or sell short for a new lot. Notional is capped
+    /// at current equity less the notional of lots already open, and a
+    /// non-positive result means no entry.
+    fn position_size(&self, price: f64, stop_distance: Option<f64>, atr: Option<f64>) -> f64 {
+        if !price.is_finite() || price <= 0.0 {
+            return 0.0;
+        }
+
+        let equity = self.sizing_equity();
+        let leverage = self.margin.leverage.max(1.0);
+        // Percent and Kelly allocate margin, which leverage turns into a larger notional.
+        // Fixed is already a notional and VolatilityTarget a risk budget, so neither scales.
+        let notional = match self.trade_management.position_sizing {
+            PositionSizing::Fixed { size } => size,
+            PositionSizing::Percent { percent } => equity * percent / 100.0 * leverage,
+            PositionSizing::Kelly { fraction, lookback, warmup_percent } => {
+                match self.kelly_estimate(lookback) {
+                    Some(kelly) => equity * fraction * kelly * leverage,
+                    None => equity * warmup_percent / 100.0 * leverage,
+                }
+            }
+            PositionSizing::VolatilityTarget { risk_percent, atr_multiplier, .. } => {
+                let risk_per_unit = stop_distance.or_else(|| {
+                    atr.filter(|a| a.is_finite() && *a > 0.0)
+                        .map(|a| a * atr_multiplier)
+                });
+                match risk_per_unit {
+                    Some(risk) if risk > 0.0 => equity * risk_percent / 100.0 / risk * price,
+                    _ => 0.0,
+                }
+            }
+        };
+
+        // Initial margin for all lots may not exceed equity
+        notional.min(equity * leverage - self.open_notional()).max(0.0) / price
+    }
+
+    /// Price at which a lot entered at `price` has lost its initial margin down to the
+    /// maintenance requirement. Unlevered lots are never liquidated.
+    fn liquidation_price(&self, direction: Direction, price: f64) -> Option<f64> {
+        if self.margin.leverage <= 1.0 {
+            return None;
+        }
+
+        let buffer = self.margin.initial_margin_rate() - self.margin.maintenance_margin_rate();
+        match direction {
+            Direction::Long => Some(price * (1.0 - buffer)),
+            Direction::Short => Some(price * (1.0 + buffer)),
+        }
+    }
+
+    /// After marking to market, close everything as a margin call if equity no longer
+    /// covers the maintenance requirement of the open lots.
+    fn check_margin(&mut self, bar: usize, prices: &PriceBar) -> Result<()> {
+        if self.positions.is_empty() || self.equity() >= self.maintenance_margin {
+            return Ok(());
+        }
+
+        let slip = self.slippage_per_unit(prices.close, prices);
+        self.exit_where(bar, prices.close, slip, ExitReason::MarginCall, |_| true)?;
+        self.calculate_unrealized_pnl(prices.close);
 
         Ok(())
     }
 
-    fn check_exit(&mut self, bar: usize, signal: f64, price: f64) -> Result<()> {
-        if let Some(pos) = &self.position {
-            let should_exit = match pos.direction {
-                Direction::Long => signal < 0.0,
-                Direction::Short => signal > 0.0,
+    /// Entry notional of all open lots, long and short alike
+    fn open_notional(&self) -> f64 {
+        self.positions.iter().map(|p| p.size * p.entry_price).sum()
+    }
+
+    /// Commission for one fill of `size` units at `price`
+    fn commission_for(&self, size: f64, price: f64) -> f64 {
+        match self.commission {
+            CommissionModel::Percent { percent } => size * price * percent / 100.0,
+            CommissionModel::PerUnit { amount } => size * amount,
+            CommissionModel::PerTrade { amount } => amount,
+            CommissionModel::None => 0.0,
+        }
+    }
+
+    /// Adverse price movement per unit when filling a market order at `price`
+    fn slippage_per_unit(&self, price: f64, prices: &PriceBar) -> f64 {
+        match self.slippage {
+            SlippageModel::Percent { percent } => price * percent / 100.0,
+            SlippageModel::Ticks { ticks, tick_size } => ticks * tick_size,
+            SlippageModel::BarRange { fraction } => (prices.high - prices.low).max(0.0) * fraction,
+            SlippageModel::None => 0.0,
+        }
+    }
+
+    /// Equity available for sizing: realised capital plus any open P&L
+    fn sizing_equity(&self) -> f64 {
+        let open_pnl = if self.positions.is_empty() { 0.0 } else { self.unrealized_pnl };
+        self.initial_capital + self.realized_pnl + open_pnl
+    }
+
+    /// Kelly fraction `W - (1 - W) / R` from the most recent completed trades,
+    /// using per-trade returns so that position size does not skew the payoff ratio.
+    /// Only trades already closed are used, so the estimate never sees the future,
+    /// and partial closes of one entry count as a single trade.
+    fn kelly_estimate(&self, lookback: usize) -> Option<f64> {
+        let round_trips = Trade::round_trips(&self.trades);
+        let recent = &round_trips[round_trips.len().saturating_sub(lookback)..];
+        if recent.len() < KELLY_MIN_TRADES {
+            return None;
+        }
+
+        let returns: Vec<f64> = recent
+            .iter()
+            .map(|t| t.profit / (t.entry_price * t.size))
+            .filter(|r| r.is_finite())
+            .collect();
+        let wins: Vec<f64> = returns.iter().copied().filter(|r| *r > 0.0).collect();
+        let losses: Vec<f64> = returns.iter().copied().filter(|r| *r < 0.0).map(f64::abs).collect();
+
+        if returns.is_empty() || wins.is_empty() {
+            return Some(0.0);
+        }
+        if losses.is_empty() {
+            return Some(1.0);
+        }
+
+        let win_rate = wins.len() as f64 / returns.len() as f64;
+        let avg_win = wins.iter().sum::<f64>() / wins.len() as f64;
+        let avg_loss = losses.iter().sum::<f64>() / losses.len() as f64;
+        let payoff = avg_win / avg_loss;
+
+        Some((win_rate - (1.0 - win_rate) / payoff).clamp(0.0, 1.0))
+    }
+
+    /// Distance from entry to the initial stop, in price units.
+    /// `FixedPercent` is expressed in percent (2.0 = 2%).
+    fn stop_distance(&self, price: f64, atr: Option<f64>) -> Option<f64> {
+        match self.trade_management.stop_loss {
+            StopLossConfig::FixedPercent { percent }
+            | StopLossConfig::TrailingPercent { percent } => Some(price * percent / 100.0),
+            StopLossConfig::ATR { multiplier, .. }
+            | StopLossConfig::TrailingATR { multiplier, .. }
+            | StopLossConfig::Chandelier { multiplier, .. } => atr
+                .filter(|a| a.is_finite() && *a > 0.0)
+                .map(|a| a * multiplier),
+            StopLossConfig::None => None,
+        }
+    }
+
+    /// Distance from entry to the target. `RiskReward` needs a stop to measure R against.
+    fn target_distance(&self, price: f64, stop_distance: Option<f64>) -> Option<f64> {
+        match self.trade_management.take_profit {
+            TakeProfitConfig::FixedPercent { percent } => Some(price * percent / 100.0),
+            TakeProfitConfig::RiskReward { ratio } => stop_distance.map(|d| d * ratio),
+            // Ladders close in steps, see `check_scale_outs`
+            TakeProfitConfig::Ladder { .. } | TakeProfitConfig::None => None,
+        }
+    }
+
+    /// Check every lot's stop and target against the bar's high/low, returning
+    /// whether any lot closed. A bar that gaps through a level fills at the open
+    /// instead of the level. Stops fill as market orders and pay slippage;
+    /// targets are resting limits and do not.
+    fn check_stops(&mut self, bar: usize, prices: &PriceBar, path: &[PriceBar]) -> Result<bool> {
+        let mut exited = false;
+        let mut i = 0;
+
+        while i < self.positions.len() {
+            // Profits taken before the stop leave only the remainder to be stopped
+            let target_first = self.target_first(i, prices, path);
+            if target_first && self.check_scale_outs(bar, i, prices)? {
+                exited = true;
+                if self.positions[i].size <= 0.0 {
+                    self.positions.remove(i);
+                    continue;
+                }
+            }
+
+            if let Some((price, reason)) = Self::stop_exit(&self.positions[i], prices, target_first) {
+                let slip = match reason {
+                    ExitReason::TakeProfit => 0.0,
+                    _ => self.slippage_per_unit(price, prices),
+                };
+                let pos = self.positions.remove(i);
+                self.exit_position(bar, pos, price, slip, reason)?;
+                exited = true;
+            } else if !target_first && self.check_scale_outs(bar, i, prices)? {
+                exited = true;
+                if self.positions[i].size <= 0.0 {
+                    self.positions.remove(i);
+                } else {
+                    i += 1;
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        Ok(exited)
+    }
+
+    /// Take partial profits at every scale-out level of lot `index` that the bar reaches,
+    /// each as its own `TakeProfit` trade. Levels fill like targets: at the level, or at
+    /// the open on a gap, without slippage. Returns whether any level filled.
+    fn check_scale_outs(&mut self, bar: usize, index: usize, prices: &PriceBar) -> Result<bool> {
+        let mut filled = false;
+
+        while let Some(&(level, size)) = self.positions[index].scale_outs.first() {
+            let pos = &self.positions[index];
+            let price = match pos.direction {
+                Direction::Long if prices.high >= level => level.max(prices.open),
+                Direction::Short if prices.low <= level => level.min(prices.open),
+                _ => break,
             };
 
-            if should_exit {
-                self.close_position(bar, price, ExitReason::Signal)?;
+            self.positions[index].scale_outs.remove(0);
+            let piece = self.split_position(index, size);
+            self.exit_position(bar, piece, price, 0.0, ExitReason::TakeProfit)?;
+            filled = true;
+        }
+
+        Ok(filled)
+    }
+
+    /// Split up to `size` units off lot `index`, with a matching share of its entry costs
+    fn split_position(&mut self, index: usize, size: f64) -> Position {
+        let pos = &mut self.positions[index];
+        let size = size.min(pos.size);
+        let share = if pos.size > 0.0 { size / pos.size } else { 1.0 };
+
+        let mut piece = pos.clone();
+        piece.size = size;
+        piece.entry_fees = pos.entry_fees * share;
+        piece.entry_slippage = pos.entry_slippage * share;
+        piece.carry = pos.carry * share;
+
+        pos.size -= size;
+        pos.entry_fees -= piece.entry_fees;
+        pos.entry_slippage -= piece.entry_slippage;
+        pos.carry -= piece.carry;
+        // Guard against a sliver left over from rounding
+        if pos.size <= f64::EPSILON * size.max(1.0) {
+            pos.size = 0.0;
+        }
+
+        piece
+    }
+
+    /// The protective level nearest the market, the stop or the liquidation price,
+    /// with the reason reported when it is hit
+    fn protective_stop(pos: &Position) -> Option<(f64, ExitReason)> {
+        match (pos.stop_loss, pos.liquidation_price) {
+            (Some(stop), Some(liq))
+                if (pos.direction == Direction::Long && liq > stop)
+                    || (pos.direction == Direction::Short && liq < stop) =>
+            {
+                Some((liq, ExitReason::Liquidation))
             }
+            (None, Some(liq)) => Some((liq, ExitReason::Liquidation)),
+            (stop, _) => stop.map(|s| (s, pos.stop_reason)),
         }
+    }
 
-        Ok(())
+    /// Whether lot `index` reached its first profit level (target or scale-out)
+    /// before its stop within this bar. Only a bar that reaches both can say yes.
+    /// A bar that opens through one of them reached it first; otherwise the
+    /// lower-timeframe `path` is walked, and failing that `intrabar_assumption` decides.
+    fn target_first(&mut self, index: usize, prices: &PriceBar, path: &[PriceBar]) -> bool {
+        let pos = &self.positions[index];
+        let (stop, target) = match (
+            Self::protective_stop(pos),
+            pos.scale_outs.first().map(|&(level, _)| level).or(pos.take_profit),
+        ) {
+            (Some((stop, _)), Some(target)) => (stop, target),
+            _ => return false,
+        };
+
+        // Whether a bar reaches the stop and the target, and whether it opens through either
+        let direction = pos.direction;
+        let touches = |b: &PriceBar| match direction {
+            Direction::Long => (b.low <= stop, b.high >= target, b.open <= stop, b.open >= target),
+            Direction::Short => (b.high >= stop, b.low <= target, b.open >= stop, b.open <= target),
+        };
+
+        match touches(prices) {
+            (true, true, false, false) => {}
+            (true, true, _, opened_at_target) => return opened_at_target,
+            _ => return false,
+        }
+
+        self.intrabar_ambiguous += 1;
+        for sub_bar in path {
+            let target_first = match touches(sub_bar) {
+                (true, false, ..) => false,
+                (false, true, ..) => true,
+                (true, true, true, _) => false,
+                (true, true, _, true) => true,
+                // Both inside one lower-timeframe bar as well
+                (true, true, ..) => break,
+                (false, false, ..) => continue,
+            };
+            self.intrabar_resolved += 1;
+            return target_first;
+        }
+
+        self.intrabar_assumption == IntrabarAssumption::Optimistic
     }
 
-    pub fn close_position(&mut self, bar: usize, price: f64, reason: ExitReason) -> Result<()> {
-        if let Some(pos) = self.position.take() {
-            let profit = match pos.direction {
-                Direction::Long => (price - pos.entry_price) * pos.size,
-                Direction::Short => (pos.entry_price - price) * pos.size,
+    /// The level at which `pos` leaves the bar, if its stop or target is touched.
+    /// When both are, the stop fills unless `target_first`.
+    /// A liquidation price closer than the stop is hit first.
+    fn stop_exit(pos: &Position, prices: &PriceBar, target_first: bool) -> Option<(f64, ExitReason)> {
+        let protective = Self::protective_stop(pos);
+
+        let (stop, target) = match pos.direction {
+            Direction::Long => (
+                protective
+                    .filter(|&(s, _)| prices.low <= s)
+                    .map(|(s, reason)| (s.min(prices.open), reason)),
+                pos.take_profit
+                    .filter(|&t| prices.high >= t)
+                    .map(|t| (t.max(prices.open), ExitReason::TakeProfit)),
+            ),
+            Direction::Short => (
+                protective
+                    .filter(|&(s, _)| prices.high >= s)
+                    .map(|(s, reason)| (s.max(prices.open), reason)),
+                pos.take_profit
+                    .filter(|&t| prices.low <= t)
+                    .map(|t| (t.min(prices.open), ExitReason::TakeProfit)),
+            ),
+        };
+
+        if target_first {
+            target.or(stop)
+        } else {
+            stop.or(target)
+        }
+    }
+
+    /// Close lots that have been held `max_bars_in_trade` bars at this bar's close,
+    /// returning whether any closed
+    fn check_time_stops(&mut self, bar: usize, prices: &PriceBar) -> Result<bool> {
+        let max_bars = match self.trade_management.max_bars_in_trade {
+            Some(max_bars) => max_bars,
+            None => return Ok(false),
+        };
+
+        let slip = self.slippage_per_unit(prices.close, prices);
+        self.exit_where(bar, prices.close, slip, ExitReason::TimeStop, |p| {
+            bar.saturating_sub(p.entry_bar) >= max_bars
+        })
+    }
+
+    /// Charge each lot held through the bar its carry on the notional at the close.
+    /// Lots closed during the bar, or filled at its close, pay nothing for it.
+    fn accrue_carry(&mut self, bar: usize, prices: &PriceBar) {
+        let fills_at_open = self.execution_timing == ExecutionTiming::NextBarOpen;
+
+        for pos in self.positions.iter_mut() {
+            if pos.entry_bar == bar && !fills_at_open {
+                continue;
+            }
+
+            // Fraction of notional this lot pays for the bar
+            let funding = prices.funding_rate.unwrap_or(0.0);
+            let rate = match (&self.carry, pos.direction) {
+                (CarryCostModel::FundingRate { .. }, Direction::Long) => funding,
+                (CarryCostModel::FundingRate { .. }, Direction::Short) => -funding,
+                (CarryCostModel::BorrowRate { annual_percent, bars_per_year }, Direction::Short) => {
+                    annual_percent / 100.0 / bars_per_year
+                }
+                (CarryCostModel::BorrowRate { .. }, Direction::Long) | (CarryCostModel::None, _) => 0.0,
+            };
+            if rate == 0.0 || !rate.is_finite() {
+                continue;
+            }
+
+            let cost = rate * pos.size * prices.close;
+            pos.carry += cost;
+            self.cash -= cost;
+            self.realized_pnl -= cost;
+        }
+    }
+
+    /// Ratchet trailing stops and apply the breakeven move after the bar has traded.
+    /// Levels only ever tighten, and take effect from the next bar. A lot filled at
+    /// this bar's close has not seen its range, so it is left alone.
+    fn update_stops(&mut self, bar: usize, prices: &PriceBar) {
+        let fills_at_open = self.execution_timing == ExecutionTiming::NextBarOpen;
+        let atr = prices.atr.filter(|a| a.is_finite() && *a > 0.0);
+
+        for pos in self.positions.iter_mut() {
+            if pos.entry_bar == bar && !fills_at_open {
+                continue;
+            }
+
+            pos.best_price = match pos.direction {
+                Direction::Long => pos.best_price.max(prices.high),
+                Direction::Short => pos.best_price.min(prices.low),
+            };
+            // Signed so that subtracting moves a level against the lot's direction
+            let side = match pos.direction {
+                Direction::Long => 1.0,
+                Direction::Short => -1.0,
             };
 
-            match pos.direction {
-                Direction::Long => self.cash += price * pos.size,
-                Direction::Short => self.cash -= price * pos.size, // Deduct cost to buy back shares
+            let trail = match self.trade_management.stop_loss {
+                StopLossConfig::TrailingPercent { percent } => {
+                    Some(pos.best_price - side * pos.best_price * percent / 100.0)
+                }
+                StopLossConfig::TrailingATR { multiplier, .. } => {
+                    atr.map(|a| prices.close - side * a * multiplier)
+                }
+                StopLossConfig::Chandelier { multiplier, .. } => {
+                    atr.map(|a| pos.best_price - side * a * multiplier)
+                }
+                _ => None,
+            };
+            if let Some(level) = trail {
+                Self::tighten_stop(pos, level, ExitReason::TrailingStop);
+            }
+
+            if let (Some(after_r), Some(risk)) = (self.trade_management.breakeven_after_r, pos.initial_risk) {
+                let excursion = side * (pos.best_price - pos.entry_price);
+                if risk > 0.0 && excursion >= after_r * risk {
+                    Self::tighten_stop(pos, pos.entry_price, ExitReason::Breakeven);
+                }
             }
-            self.realized_pnl += profit;
+        }
+    }
+
+    /// Move `pos`'s stop to `level` if that is tighter than where it is now
+    fn tighten_stop(pos: &mut Position, level: f64, reason: ExitReason) {
+        let tighter = match (pos.direction, pos.stop_loss) {
+            (_, None) => true,
+            (Direction::Long, Some(stop)) => level > stop,
+            (Direction::Short, Some(stop)) => level < stop,
+        };
+        if tighter && level.is_finite() {
+            pos.stop_loss = Some(level);
+            pos.stop_reason = reason;
+        }
+    }
+
+    /// Close every lot whose side's exit condition fired, returning whether any closed
+    fn check_exit(&mut self, bar: usize, signals: &BarSignals, price: f64, prices: &PriceBar) -> Result<bool> {
+        if !signals.long_exit && !signals.short_exit {
+            return Ok(false);
+        }
+
+        let slip = self.slippage_per_unit(price, prices);
+        self.exit_where(bar, price, slip, ExitReason::Signal, |p| match p.direction {
+            Direction::Long => signals.long_exit,
+            Direction::Short => signals.short_exit,
+        })
+    }
+
+    /// Close every lot still open on the final bar at its close as an `EndOfData` trade,
+    /// restating that bar's ledger entry and equity point net of the exit costs.
+    /// Entry orders still resting are cancelled.
+    pub fn close_at_end_of_data(&mut self, bar: usize, prices: &PriceBar) -> Result<()> {
+        self.cancel_orders(bar, |_| true);
+
+        if self.positions.is_empty() {
+            return Ok(());
+        }
 
-            self.trades.push(Trade {
-                entry_bar: pos.entry_bar,
-                exit_bar: bar,
-                entry_price: pos.entry_price,
-                exit_price: price,
-                direction: pos.direction,
-                size: pos.size,
-                profit,
-                exit_reason: reason,
-                fees: 0.0,
-            });
+        let slip = self.slippage_per_unit(prices.close, prices);
+        self.exit_where(bar, prices.close, slip, ExitReason::EndOfData, |_| true)?;
+
+        self.calculate_unrealized_pnl(prices.close);
+        self.update_drawdown();
+        let entry = self.ledger_entry(bar);
+        if let (Some(last), Some(point)) = (self.ledger.last_mut(), self.equity_curve.last_mut()) {
+            *last = entry;
+            *point = entry.equity;
+        }
+
+        Ok(())
+    }
+
+    /// Close every open lot at `price`
+    pub fn close_position(&mut self, bar: usize, price: f64, reason: ExitReason) -> Result<()> {
+        let slip = self.slippage_per_unit(price, &PriceBar::from_close(price));
+        self.exit_where(bar, price, slip, reason, |_| true)?;
+        Ok(())
+    }
+
+    /// Close the lots matching `predicate`, oldest first, returning whether any closed
+    fn exit_where(
+        &mut self,
+        bar: usize,
+        price: f64,
+        slip: f64,
+        reason: ExitReason,
+        predicate: impl Fn(&Position) -> bool,
+    ) -> Result<bool> {
+        let (closing, open): (Vec<Position>, Vec<Position>) =
+            std::mem::take(&mut self.positions).into_iter().partition(|p| predicate(p));
+        self.positions = open;
+
+        let exited = !closing.is_empty();
+        for pos in closing {
+            self.exit_position(bar, pos, price, slip, reason)?;
+        }
+
+        Ok(exited)
+    }
+
+    /// Close `pos` at `price` moved `slip` against it, charging exit commission.
+    /// `Trade::profit` is net of commission on both sides and of slippage.
+    fn exit_position(
+        &mut self,
+        bar: usize,
+        pos: Position,
+        price: f64,
+        slip: f64,
+        reason: ExitReason,
+    ) -> Result<()> {
+        // Selling a long fills lower, buying back a short fills higher
+        let price = match pos.direction {
+            Direction::Long => price - slip,
+            Direction::Short => price + slip,
+        };
+        let price_pnl = match pos.direction {
+            Direction::Long => (price - pos.entry_price) * pos.size,
+            Direction::Short => (pos.entry_price - price) * pos.size,
+        };
+        let exit_fees = self.commission_for(pos.size, price);
+
+        match pos.direction {
+            Direction::Long => self.cash += price * pos.size,
+            Direction::Short => self.cash -= price * pos.size, // Deduct cost to buy back shares
         }
+        self.cash -= exit_fees;
+        // Entry fees and carry were realised as they were paid
+        self.realized_pnl += price_pnl - exit_fees;
+
+        self.trades.push(Trade {
+            entry_id: pos.entry_id,
+            entry_bar: pos.entry_bar,
+            exit_bar: bar,
+            entry_price: pos.entry_price,
+            exit_price: price,
+            direction: pos.direction,
+            size: pos.size,
+            profit: price_pnl - pos.entry_fees - exit_fees - pos.carry,
+            exit_reason: reason,
+            fees: pos.entry_fees + exit_fees,
+            slippage: pos.entry_slippage + slip * pos.size,
+            carry: pos.carry,
+        });
 
         Ok(())
     }
@@ -135,51 +1158,69 @@ impl Portfolio {
         &self.trades
     }
 
+    pub fn get_order_events(&self) -> &[OrderEvent] {
+        &self.order_events
+    }
+
     pub fn get_equity_curve(&self) -> &[f64] {
         &self.equity_curve
     }
 
+    /// Equity at the last mark. Open lots count at market value, so this only
+    /// equals cash once everything is closed.
     pub fn final_balance(&self) -> f64 {
-        self.cash
+        self.equity()
+    }
+
+    pub fn get_ledger(&self) -> &[LedgerEntry] {
+        &self.ledger
+    }
+
+    /// Lots opened so far, including those since closed
+    pub fn entries_opened(&self) -> usize {
+        self.next_entry_id
     }
 
     // P&L and Drawdown Calculation Methods
 
-    /// Calculate unrealized P&L for the open position.
+    /// Mark all open lots to `current_price`: unrealized P&L, position value and margin.
     pub fn calculate_unrealized_pnl(&mut self, current_price: f64) {
-        if let Some(position) = &self.position {
+        self.unrealized_pnl = 0.0;
+        self.current_position_value = 0.0;
+        self.margin_used = 0.0;
+        self.maintenance_margin = 0.0;
+
+        for position in &self.positions {
             let entry_value = position.size * position.entry_price;
             let current_value = position.size * current_price;
 
-            let pnl = match position.direction {
+            self.unrealized_pnl += match position.direction {
                 Direction::Long => current_value - entry_value,
                 Direction::Short => entry_value - current_value,
             };
 
-            self.unrealized_pnl = pnl;
-
             // For long positions, position value is positive (asset)
             // For short positions, position value is negative (liability)
-            self.current_position_value = match position.direction {
+            self.current_position_value += match position.direction {
                 Direction::Long => current_value,
                 Direction::Short => -current_value,
             };
-        } else {
-            self.unrealized_pnl = 0.0;
-            self.current_position_value = 0.0;
+            self.margin_used += current_value * self.margin.initial_margin_rate();
+            self.maintenance_margin += current_value * self.margin.maintenance_margin_rate();
         }
 
         self.total_pnl = self.realized_pnl + self.unrealized_pnl;
     }
 
-    /// Get total portfolio value (cash + position at current price).
+    /// Get total portfolio value; the same figure as `equity`.
     pub fn total_value(&self) -> f64 {
-        self.cash + self.current_position_value
+        self.equity()
     }
 
-    /// Get equity (initial capital + total P&L).
+    /// Mark-to-market equity: cash plus the signed value of open lots at the last mark.
+    /// Equal to initial capital plus realised and unrealised P&L.
     pub fn equity(&self) -> f64 {
-        self.initial_capital + self.total_pnl
+        self.cash + self.current_position_value
     }
 
     /// Update drawdown based on the current equity.
@@ -0,0 +1,179 @@
+use crate::{
+    engines::evaluation::portfolio::{BarSignals, Portfolio, PriceBar},
+    error::Result,
+    types::{Direction, LedgerEntry},
+};
+use polars::prelude::*;
+
+/// A bar on which signals fired, or acts on them under a next-bar timing
+#[derive(Debug, Clone, Copy)]
+pub struct SignalBar {
+    /// Bar the signals are acted on
+    pub bar: usize,
+    /// Bar they fired on, whose ATR sizes the entry
+    pub fired: usize,
+    pub signals: BarSignals,
+}
+
+/// The book as a signal bar leaves it, which holds until the next one
+#[derive(Debug, Clone, Copy)]
+struct Book {
+    bar: usize,
+    cash: f64,
+    realized_pnl: f64,
+    /// Direction, size and entry price of the open lot
+    lot: Option<(Direction, f64, f64)>,
+}
+
+impl Book {
+    fn of(bar: usize, portfolio: &Portfolio) -> Self {
+        Self {
+            bar,
+            cash: portfolio.cash,
+            realized_pnl: portfolio.realized_pnl,
+            lot: portfolio.positions.first().map(|p| (p.direction, p.size, p.entry_price)),
+        }
+    }
+}
+
+/// Run `portfolio` over bars closing at `closes` when only signals can open or close
+/// its single lot. The portfolio acts on those of `signal_bars` that can change the
+/// book, one by one; every bar is then marked against the book the last of them
+/// left with columnar operations, which also give the equity curve and drawdown.
+/// `prices` gives a bar's prices when one is acted on.
+///
+/// Returns `false` if some bar would have been margin-called, which only the
+/// stateful path models. `portfolio` is then left part-way and must be discarded.
+pub fn run_signals(
+    portfolio: &mut Portfolio,
+    signal_bars: impl IntoIterator<Item = SignalBar>,
+    prices: impl Fn(usize) -> PriceBar,
+    closes: &[f64],
+) -> Result<bool> {
+    // Only the last book a bar leaves is marked
+    let mut books: Vec<Book> = Vec::new();
+    for SignalBar { bar, fired, signals } in signal_bars {
+        if !portfolio.acts_on(&signals) {
+            continue;
+        }
+        portfolio.apply_signals(bar, &signals, prices(fired).atr, &prices(bar))?;
+        if books.last().is_some_and(|last| last.bar == bar) {
+            books.pop();
+        }
+        books.push(Book::of(bar, portfolio));
+    }
+    let Some(&last_close) = closes.last() else {
+        return Ok(true);
+    };
+
+    let marks = marks(portfolio, &books, closes)?;
+    let column = |name: &str| -> Result<Vec<f64>> {
+        Ok(marks.column(name)?.f64()?.into_no_null_iter().collect())
+    };
+    let (cash, position_value, margin_used) = (column("cash")?, column("position_value")?, column("margin_used")?);
+    let (maintenance_margin, realized_pnl) = (column("maintenance_margin")?, column("realized_pnl")?);
+    let (unrealized_pnl, equity) = (column("unrealized_pnl")?, column("equity")?);
+
+    let margin_called = marks
+        .column("has_lot")?
+        .bool()?
+        .into_no_null_iter()
+        .zip(equity.iter().zip(&maintenance_margin))
+        .any(|(has_lot, (equity, maintenance))| has_lot && equity < maintenance);
+    if margin_called {
+        return Ok(false);
+    }
+
+    // Drawdown as marking each bar through `update_drawdown` leaves it
+    let peak = column("peak")?;
+    let drawdowns: Vec<Option<f64>> = marks.column("drawdown")?.f64()?.into_iter().collect();
+    if let Some(&peak) = peak.last() {
+        portfolio.peak_equity = peak;
+    }
+    if let Some(drawdown) = drawdowns.iter().rev().find_map(|drawdown| *drawdown) {
+        portfolio.current_drawdown = drawdown;
+    }
+    portfolio.max_drawdown = drawdowns.iter().flatten().fold(portfolio.max_drawdown, |max, &d| if d > max { d } else { max });
+
+    portfolio.cash = cash[cash.len() - 1];
+    portfolio.calculate_unrealized_pnl(last_close);
+    portfolio.ledger = (0..closes.len())
+        .map(|bar| LedgerEntry {
+            bar,
+            cash: cash[bar],
+            position_value: position_value[bar],
+            margin_used: margin_used[bar],
+            maintenance_margin: maintenance_margin[bar],
+            realized_pnl: realized_pnl[bar],
+            unrealized_pnl: unrealized_pnl[bar],
+            equity: equity[bar],
+        })
+        .collect();
+    portfolio.equity_curve.extend(equity);
+
+    Ok(true)
+}
+
+/// Every bar's book, carried forward from the signal bar that left it, marked at
+/// its close as `calculate_unrealized_pnl` would, with the running equity peak
+/// and drawdown
+fn marks(portfolio: &Portfolio, books: &[Book], closes: &[f64]) -> Result<DataFrame> {
+    let bars = closes.len() as u64;
+    let prices = df! {
+        "bar" => (0..bars).collect::<Vec<u64>>(),
+        "close" => closes,
+    }?;
+    // Without a lot the book is marked as an empty long one
+    let lot = |book: &Book| book.lot.unwrap_or((Direction::Long, 0.0, 0.0));
+    let changes = df! {
+        "bar" => books.iter().map(|book| book.bar as u64).collect::<Vec<u64>>(),
+        "cash" => books.iter().map(|book| book.cash).collect::<Vec<f64>>(),
+        "realized_pnl" => books.iter().map(|book| book.realized_pnl).collect::<Vec<f64>>(),
+        "has_lot" => books.iter().map(|book| book.lot.is_some()).collect::<Vec<bool>>(),
+        "long" => books.iter().map(|book| lot(book).0 == Direction::Long).collect::<Vec<bool>>(),
+        "size" => books.iter().map(|book| lot(book).1).collect::<Vec<f64>>(),
+        "entry_price" => books.iter().map(|book| lot(book).2).collect::<Vec<f64>>(),
+    }?;
+
+    let carried = |name: &str, initial: Expr| col(name).fill_null_with_strategy(FillNullStrategy::Forward(None)).fill_null(initial);
+    let current_value = col("size") * col("close");
+    let entry_value = col("size") * col("entry_price");
+    let peak = col("equity").cum_max(false);
+    let peak = when(peak.clone().gt(lit(portfolio.peak_equity))).then(peak).otherwise(lit(portfolio.peak_equity));
+
+    let marks = prices
+        .lazy()
+        .join(
+            changes.lazy(),
+            [col("bar")],
+            [col("bar")],
+            JoinArgs { maintain_order: MaintainOrderJoin::Left, ..JoinArgs::new(JoinType::Left) },
+        )
+        .with_columns([
+            carried("cash", lit(portfolio.initial_capital)),
+            carried("realized_pnl", lit(0.0)),
+            carried("has_lot", lit(false)),
+            carried("long", lit(true)),
+            carried("size", lit(0.0)),
+            carried("entry_price", lit(0.0)),
+        ])
+        .with_columns([
+            when(col("long"))
+                .then(current_value.clone() - entry_value.clone())
+                .otherwise(entry_value - current_value.clone())
+                .alias("unrealized_pnl"),
+            when(col("long")).then(current_value.clone()).otherwise(-current_value.clone()).alias("position_value"),
+            (current_value.clone() * lit(portfolio.margin.initial_margin_rate())).alias("margin_used"),
+            (current_value * lit(portfolio.margin.maintenance_margin_rate())).alias("maintenance_margin"),
+        ])
+        .with_column((col("cash") + col("position_value")).alias("equity"))
+        .with_column(peak.alias("peak"))
+        .with_column(
+            when(col("peak").gt(lit(0.0)))
+                .then((col("peak") - col("equity")) / col("peak"))
+                .otherwise(lit(NULL).cast(DataType::Float64))
+                .alias("drawdown"),
+        )
+        .collect()?;
+    Ok(marks)
+}
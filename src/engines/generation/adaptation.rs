@@ -0,0 +1,163 @@
+use crate::error::{Result, TradebiasError};
+use serde::{Deserialize, Serialize};
+
+/// How mutation and crossover rates follow the search's progress, and when a
+/// stalled run restarts or stops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptationConfig {
+    /// Generations without a better best fitness before the search counts as stalled
+    pub patience: usize,
+    /// Share of distinct strategies in a generation below which it counts as converged
+    pub min_diversity: f64,
+    /// Factor the rates move by each generation: mutation up and crossover down
+    /// while stalled or converged, back towards the configured rates otherwise
+    pub step: f64,
+    pub max_mutation_rate: f64,
+    pub min_crossover_rate: f64,
+    /// Replace all but each island's elites with random genomes after this many
+    /// generations without improvement; the hall of fame is kept
+    pub restart_after: Option<usize>,
+    /// End the run after this many generations without improvement
+    pub stop_after: Option<usize>,
+}
+
+impl Default for AdaptationConfig {
+    fn default() -> Self {
+        Self {
+            patience: 5,
+            min_diversity: 0.5,
+            step: 1.25,
+            max_mutation_rate: 0.6,
+            min_crossover_rate: 0.5,
+            restart_after: None,
+            stop_after: None,
+        }
+    }
+}
+
+impl AdaptationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.step >= 1.0 && self.step.is_finite()) {
+            return Err(TradebiasError::Configuration("Adaptation step must be at least 1".to_string()));
+        }
+        for (name, value) in [
+            ("Minimum diversity", self.min_diversity),
+            ("Maximum mutation rate", self.max_mutation_rate),
+            ("Minimum crossover rate", self.min_crossover_rate),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(TradebiasError::Configuration(format!("{} must be between 0 and 1", name)));
+            }
+        }
+        if self.restart_after == Some(0) || self.stop_after == Some(0) {
+            return Err(TradebiasError::Configuration(
+                "Restart and stop need at least one generation without improvement".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rates the next generation is bred with, and the progress they respond to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdaptedRates {
+    pub mutation_rate: f64,
+    pub crossover_rate: f64,
+    /// Share of distinct strategies in the generation just evaluated
+    pub diversity: f64,
+    /// Generations since the best fitness last improved
+    pub stagnant_generations: usize,
+}
+
+/// Progress behind the adapted rates, carried across generations and checkpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptationState {
+    /// Multipliers on each island's configured rates
+    mutation_scale: f64,
+    crossover_scale: f64,
+    best_fitness: f64,
+    since_improvement: usize,
+    since_restart: usize,
+}
+
+impl Default for AdaptationState {
+    fn default() -> Self {
+        Self {
+            mutation_scale: 1.0,
+            crossover_scale: 1.0,
+            best_fitness: f64::NEG_INFINITY,
+            since_improvement: 0,
+            since_restart: 0,
+        }
+    }
+}
+
+impl AdaptationState {
+    /// Record a generation's best fitness and diversity and adapt the rates to
+    /// them, returning the rates `mutation_rate` and `crossover_rate` become
+    pub fn update(
+        &mut self,
+        config: &AdaptationConfig,
+        best_fitness: f64,
+        diversity: f64,
+        mutation_rate: f64,
+        crossover_rate: f64,
+    ) -> AdaptedRates {
+        if best_fitness > self.best_fitness {
+            self.best_fitness = best_fitness;
+            self.since_improvement = 0;
+            self.since_restart = 0;
+        } else {
+            self.since_improvement += 1;
+            self.since_restart += 1;
+        }
+
+        if self.since_improvement >= config.patience || diversity < config.min_diversity {
+            self.mutation_scale *= config.step;
+            self.crossover_scale /= config.step;
+        } else {
+            self.mutation_scale = (self.mutation_scale / config.step).max(1.0);
+            self.crossover_scale = (self.crossover_scale * config.step).min(1.0);
+        }
+        // Past the limits further steps would only delay the way back
+        if mutation_rate > 0.0 {
+            self.mutation_scale = self.mutation_scale.min((config.max_mutation_rate / mutation_rate).max(1.0));
+        }
+        if crossover_rate > 0.0 {
+            self.crossover_scale = self.crossover_scale.max((config.min_crossover_rate / crossover_rate).min(1.0));
+        }
+
+        AdaptedRates {
+            mutation_rate: self.mutation_rate(config, mutation_rate),
+            crossover_rate: self.crossover_rate(config, crossover_rate),
+            diversity,
+            stagnant_generations: self.since_improvement,
+        }
+    }
+
+    /// `mutation_rate` adapted, never raised past the configured maximum
+    pub fn mutation_rate(&self, config: &AdaptationConfig, mutation_rate: f64) -> f64 {
+        (mutation_rate * self.mutation_scale).min(config.max_mutation_rate.max(mutation_rate))
+    }
+
+    /// `crossover_rate` adapted, never lowered past the configured minimum
+    pub fn crossover_rate(&self, config: &AdaptationConfig, crossover_rate: f64) -> f64 {
+        (crossover_rate * self.crossover_scale).max(config.min_crossover_rate.min(crossover_rate))
+    }
+
+    pub fn should_restart(&self, config: &AdaptationConfig) -> bool {
+        config.restart_after.is_some_and(|after| self.since_restart >= after)
+    }
+
+    pub fn should_stop(&self, config: &AdaptationConfig) -> bool {
+        config.stop_after.is_some_and(|after| self.since_improvement >= after)
+    }
+
+    /// Give a restarted population the full `restart_after` to improve, with the
+    /// configured rates
+    pub fn restarted(&mut self) {
+        self.since_restart = 0;
+        self.mutation_scale = 1.0;
+        self.crossover_scale = 1.0;
+    }
+}
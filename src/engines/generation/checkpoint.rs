@@ -0,0 +1,48 @@
+use crate::engines::generation::{adaptation::AdaptationState, evolution_engine::EvolutionConfig, hall_of_fame::HallOfFame, islands::IslandState};
+use crate::error::{Result, TradebiasError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where and how often `EvolutionEngine::run` saves its state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    /// Save after every this many generations
+    pub every: usize,
+}
+
+/// Everything a run needs to carry on from the start of a generation exactly as
+/// if it had never stopped
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Generation the saved populations are evaluated as next
+    pub generation: usize,
+    /// A single island unless the run uses an island model
+    pub islands: Vec<IslandState>,
+    pub hall_of_fame: HallOfFame,
+    pub config: EvolutionConfig,
+    /// Where rate adaptation stood; unused unless the config adapts rates
+    pub adaptation: AdaptationState,
+}
+
+impl Checkpoint {
+    /// Write to `path`, replacing an earlier checkpoint only once this one is
+    /// complete so that a crash mid-write leaves the previous one intact
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let bytes = bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(|e| TradebiasError::Generation(format!("Failed to encode checkpoint: {}", e)))?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".tmp");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let (checkpoint, _) = bincode::serde::decode_from_slice(&bytes, bincode::config::standard()).map_err(|e| {
+            TradebiasError::DataLoading(format!("Invalid checkpoint {}: {}", path.display(), e))
+        })?;
+        Ok(checkpoint)
+    }
+}
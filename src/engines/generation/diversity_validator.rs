@@ -16,9 +16,8 @@ impl DiversityValidator {
     pub fn validate(&self, ast: &StrategyAST) -> bool {
         let mut indicator_params: HashMap<String, Vec<i32>> = HashMap::new();
 
-        if let AstNode::Rule { condition, action } = ast.as_node() {
-            self.collect_indicator_params(condition, &mut indicator_params);
-            self.collect_indicator_params(action, &mut indicator_params);
+        if let node @ (AstNode::Rule { .. } | AstNode::Strategy { .. }) = ast.as_node() {
+            self.collect_indicator_params(node, &mut indicator_params);
         }
 
         // Check each indicator type
@@ -60,6 +59,11 @@ impl DiversityValidator {
                 self.collect_indicator_params(condition, collector);
                 self.collect_indicator_params(action, collector);
             }
+            AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+                for condition in [long_entry, long_exit, short_entry, short_exit].into_iter().flatten() {
+                    self.collect_indicator_params(condition, collector);
+                }
+            }
         }
     }
 
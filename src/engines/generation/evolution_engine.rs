@@ -1,19 +1,31 @@
+use crate::config::evolution::{SelectionMethod, VariationOperators};
 use crate::engines::evaluation::Backtester;
 use crate::engines::generation::{
+    adaptation::{AdaptationConfig, AdaptationState, AdaptedRates},
+    checkpoint::{Checkpoint, CheckpointConfig},
+    islands::{self, IslandModel, IslandState},
     hall_of_fame::{EliteStrategy, HallOfFame, get_canonical_ast_string},
     operators::{*, pareto_tournament_selection},
+    tree_operators::{subtree_crossover, tree_mutation},
     semantic_mapper::SemanticMapper,
     genome::Genome,
     ast::StrategyAST,
     pareto::{ObjectiveConfig, OptimizationDirection},
 };
 use crate::error::TradebiasError;
+use crate::types::StrategyResult;
 use polars::prelude::*;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use rand::SeedableRng;
-use rand::rngs::StdRng;
+use rand_chacha::ChaCha12Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvolutionConfig {
     pub population_size: usize,
     pub generations: usize,
@@ -23,6 +35,10 @@ pub struct EvolutionConfig {
     pub crossover_rate: f64,
     pub elitism_rate: f64,
     pub tournament_size: usize,
+    /// How parents are drawn from each evaluated generation
+    pub selection: SelectionMethod,
+    /// How offspring are made from their parents
+    pub operators: VariationOperators,
     pub hall_of_fame_size: usize,
 
     // Multi-objective optimization configuration
@@ -35,6 +51,47 @@ pub struct EvolutionConfig {
 
     pub min_fitness_threshold: f64,
     pub seed: Option<u64>,
+    /// Threads evaluating each generation; 0 uses one per core.
+    /// Results do not depend on it.
+    pub workers: usize,
+    /// Save the run's state periodically so `EvolutionEngine::resume` can continue it
+    pub checkpoint: Option<CheckpointConfig>,
+    /// Evolve several populations that exchange genomes, rather than one
+    pub islands: Option<IslandModel>,
+    /// Adapt mutation and crossover rates to stagnation and diversity, and
+    /// restart or stop a stalled run
+    pub adaptation: Option<AdaptationConfig>,
+}
+
+impl Default for EvolutionConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 500,
+            generations: 100,
+            genome_length: 100,
+            gene_range: 0..1000,
+            mutation_rate: 0.15,
+            crossover_rate: 0.85,
+            elitism_rate: 0.02,
+            tournament_size: 7,
+            selection: SelectionMethod::Tournament,
+            operators: VariationOperators::Positional,
+            hall_of_fame_size: 10,
+            objective_configs: vec![
+                ObjectiveConfig { metric_name: "return_pct".to_string(), direction: OptimizationDirection::Maximize },
+                ObjectiveConfig { metric_name: "max_drawdown".to_string(), direction: OptimizationDirection::Minimize },
+            ],
+            use_pareto: false,
+            fitness_objectives: vec!["return_pct".to_string()],
+            fitness_weights: vec![1.0],
+            min_fitness_threshold: 0.0,
+            seed: None,
+            workers: 0,
+            checkpoint: None,
+            islands: None,
+            adaptation: None,
+        }
+    }
 }
 
 pub struct EvolutionEngine {
@@ -42,13 +99,31 @@ pub struct EvolutionEngine {
     backtester: Backtester,
     semantic_mapper: SemanticMapper,
     hall_of_fame: HallOfFame,
-    rng: StdRng,
+    /// The generator behind `StdRng`, held directly so its state can be saved.
+    /// Seeds the islands when a run starts.
+    rng: ChaCha12Rng,
+    /// Generation, islands and rate adaptation a resumed run starts from
+    resume_from: Option<(usize, Vec<IslandState>, AdaptationState)>,
 }
 
+/// What an island breeds with besides the engine's shared settings
+struct IslandContext {
+    semantic_mapper: SemanticMapper,
+    /// The island's configured mutation rate, which adaptation scales
+    base_mutation_rate: f64,
+    mutation_rate: f64,
+    crossover_rate: f64,
+}
+
+/// A genome with its fitness, strategy, metrics and return in each lexicase fold
+type Evaluated = (Genome, f64, StrategyAST, HashMap<String, f64>, Vec<f64>);
+
 pub trait ProgressCallback: Send {
     fn on_generation_start(&mut self, generation: usize);
     fn on_generation_complete(&mut self, generation: usize, best_fitness: f64, hall_of_fame_size: usize);
     fn on_strategy_evaluated(&mut self, strategy_num: usize, total: usize);
+    /// Rates the generation after `generation` is bred with, when rates adapt
+    fn on_rates_adapted(&mut self, _generation: usize, _rates: &AdaptedRates) {}
 }
 
 impl EvolutionEngine {
@@ -58,8 +133,8 @@ impl EvolutionEngine {
         semantic_mapper: SemanticMapper,
     ) -> Self {
         let rng = match config.seed {
-            Some(seed) => StdRng::seed_from_u64(seed),
-            None => StdRng::from_entropy(),
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => ChaCha12Rng::from_entropy(),
         };
 
         // Create HallOfFame based on optimization mode
@@ -78,27 +153,65 @@ impl EvolutionEngine {
             semantic_mapper,
             hall_of_fame,
             rng,
+            resume_from: None,
         }
     }
 
+    /// An engine that carries on from the checkpoint at `path` when `run`, with the
+    /// config it was saved with. It finishes exactly as the interrupted run would have.
+    pub fn resume(
+        path: &Path,
+        backtester: Backtester,
+        semantic_mapper: SemanticMapper,
+    ) -> Result<Self, TradebiasError> {
+        let checkpoint = Checkpoint::load(path)?;
+        Ok(Self {
+            config: checkpoint.config,
+            backtester,
+            semantic_mapper,
+            hall_of_fame: checkpoint.hall_of_fame,
+            rng: ChaCha12Rng::from_entropy(),
+            resume_from: Some((checkpoint.generation, checkpoint.islands, checkpoint.adaptation)),
+        })
+    }
+
+    /// The config the engine runs with; for a resumed engine, the checkpoint's
+    pub fn config(&self) -> &EvolutionConfig {
+        &self.config
+    }
+
     /// Run the evolution process
     pub fn run<C: ProgressCallback>(
         &mut self,
         data: &DataFrame,
         mut callback: C,
     ) -> Result<Vec<EliteStrategy>, TradebiasError> {
-        // Initialize population
-        let mut population = self.initialize_population();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.config.workers)
+            .build()
+            .map_err(|e| TradebiasError::Configuration(format!("Failed to start evaluation workers: {}", e)))?;
+
+        self.config.selection.validate()?;
+        if let Some(adaptation) = &self.config.adaptation {
+            adaptation.validate()?;
+        }
+        let mut contexts = self.island_contexts()?;
+
+        // Initialize populations, unless resuming them
+        let (start, mut islands, mut adaptation) = match self.resume_from.take() {
+            Some(resumed) => resumed,
+            None => (0, self.initialize_islands(contexts.len()), AdaptationState::default()),
+        };
 
         // Evolution loop
-        for generation in 0..self.config.generations {
+        for generation in start..self.config.generations {
             callback.on_generation_start(generation);
 
-            // Evaluate fitness for all individuals
-            let evaluated = self.evaluate_population(&population, data, &mut callback)?;
+            // Evaluate fitness for all individuals of every island
+            let evaluated = self.evaluate_islands(&pool, &islands, &contexts, data, &mut callback)?;
 
-            // Update Hall of Fame
-            for (genome, fitness, ast, metrics) in &evaluated {
+            // Update the Hall of Fame all islands share
+            for (genome, fitness, ast, metrics, _) in evaluated.iter().flatten() {
                 let canonical_string = get_canonical_ast_string(ast);
                 let elite = EliteStrategy {
                     ast: ast.clone(),
@@ -106,6 +219,7 @@ impl EvolutionEngine {
                     fitness: *fitness,
                     metrics: metrics.clone(),
                     canonical_string,
+                    aborted: is_aborted(metrics),
                     pareto_rank: 0,        // Will be set by HallOfFame
                     crowding_distance: 0.0, // Will be set by HallOfFame
                 };
@@ -115,64 +229,251 @@ impl EvolutionEngine {
             // Get best fitness for progress tracking
             let best_fitness = evaluated
                 .iter()
-                .map(|(_, f, _, _)| *f)
+                .flatten()
+                .map(|(_, f, _, _, _)| *f)
                 .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
                 .unwrap_or(0.0);
 
             callback.on_generation_complete(generation, best_fitness, self.hall_of_fame.len());
 
+            let mut stalled = false;
+            if let Some(config) = &self.config.adaptation {
+                let rates = adaptation.update(
+                    config,
+                    best_fitness,
+                    diversity(&evaluated),
+                    self.config.mutation_rate,
+                    self.config.crossover_rate,
+                );
+                for context in &mut contexts {
+                    context.mutation_rate = adaptation.mutation_rate(config, context.base_mutation_rate);
+                    context.crossover_rate = rates.crossover_rate;
+                }
+                callback.on_rates_adapted(generation, &rates);
+                stalled = adaptation.should_stop(config);
+            }
+
             // Check termination
-            if generation == self.config.generations - 1 {
+            if stalled || generation == self.config.generations - 1 {
                 break;
             }
 
-            // Create next generation
-            population = self.create_next_generation(&evaluated);
+            // Create each island's next generation in parallel
+            pool.install(|| {
+                islands.par_iter_mut().zip(&evaluated).zip(&contexts).for_each(|((island, evaluated), context)| {
+                    island.population = self.create_next_generation(evaluated, context, &mut island.rng);
+                })
+            });
+
+            if let Some(model) = &self.config.islands {
+                if model.migration_interval > 0 && (generation + 1) % model.migration_interval == 0 {
+                    let emigrants: Vec<Vec<Genome>> = evaluated
+                        .iter()
+                        .map(|evaluated| {
+                            let best = self.best_first(evaluated).into_iter().take(model.migrants);
+                            best.map(|i| evaluated[i].0.clone()).collect()
+                        })
+                        .collect();
+                    islands::migrate(&mut islands, &emigrants, model.topology);
+                }
+            }
+
+            if let Some(config) = &self.config.adaptation {
+                if adaptation.should_restart(config) {
+                    self.restart(&mut islands);
+                    adaptation.restarted();
+                    for context in &mut contexts {
+                        context.mutation_rate = context.base_mutation_rate;
+                        context.crossover_rate = self.config.crossover_rate;
+                    }
+                }
+            }
+
+            if let Some(checkpoint) = &self.config.checkpoint {
+                if checkpoint.every > 0 && (generation + 1) % checkpoint.every == 0 {
+                    self.save_checkpoint(&checkpoint.path, generation + 1, &islands, &adaptation)?;
+                }
+            }
         }
 
         Ok(self.hall_of_fame.get_all().to_vec())
     }
 
-    fn initialize_population(&mut self) -> Vec<Genome> {
-        (0..self.config.population_size)
-            .map(|_| {
-                random_genome(
-                    self.config.genome_length,
-                    self.config.gene_range.clone(),
-                    &mut self.rng,
-                )
+    /// Save the state `run` is in just before evaluating `islands` as `generation`
+    fn save_checkpoint(
+        &self,
+        path: &Path,
+        generation: usize,
+        islands: &[IslandState],
+        adaptation: &AdaptationState,
+    ) -> Result<(), TradebiasError> {
+        Checkpoint {
+            generation,
+            islands: islands.to_vec(),
+            hall_of_fame: self.hall_of_fame.clone(),
+            config: self.config.clone(),
+            adaptation: adaptation.clone(),
+        }
+        .save(path)
+    }
+
+    /// Replace every island's genomes after its elites with random immigrants.
+    /// The hall of fame keeps everything found so far.
+    fn restart(&self, islands: &mut [IslandState]) {
+        let elite_count = (self.config.population_size as f64 * self.config.elitism_rate) as usize;
+        for island in islands {
+            let IslandState { population, rng } = island;
+            for genome in population.iter_mut().skip(elite_count) {
+                *genome = random_genome(self.config.genome_length, self.config.gene_range.clone(), rng);
+            }
+        }
+    }
+
+    /// Mapper and mutation rate of each island, or of the single population
+    fn island_contexts(&self) -> Result<Vec<IslandContext>, TradebiasError> {
+        let Some(model) = &self.config.islands else {
+            return Ok(vec![IslandContext {
+                semantic_mapper: self.semantic_mapper.clone(),
+                base_mutation_rate: self.config.mutation_rate,
+                mutation_rate: self.config.mutation_rate,
+                crossover_rate: self.config.crossover_rate,
+            }]);
+        };
+        if model.islands.is_empty() {
+            return Err(TradebiasError::Configuration("The island model needs at least one island".to_string()));
+        }
+
+        Ok(model
+            .islands
+            .iter()
+            .map(|settings| {
+                let mut semantic_mapper = self.semantic_mapper.clone();
+                if let Some(max_depth) = settings.max_depth {
+                    semantic_mapper = semantic_mapper.with_max_depth(max_depth);
+                }
+                if let Some(indicators) = &settings.indicators {
+                    semantic_mapper = semantic_mapper.with_indicators(indicators.clone());
+                }
+                let mutation_rate = settings.mutation_rate.unwrap_or(self.config.mutation_rate);
+                IslandContext {
+                    semantic_mapper,
+                    base_mutation_rate: mutation_rate,
+                    mutation_rate,
+                    crossover_rate: self.config.crossover_rate,
+                }
+            })
+            .collect())
+    }
+
+    /// Random populations for `count` islands. A single population draws from the
+    /// engine's generator; several islands each get a generator seeded from it.
+    fn initialize_islands(&mut self, count: usize) -> Vec<IslandState> {
+        let rngs: Vec<ChaCha12Rng> = match self.config.islands {
+            None => vec![self.rng.clone()],
+            Some(_) => (0..count).map(|_| ChaCha12Rng::seed_from_u64(self.rng.gen())).collect(),
+        };
+
+        rngs.into_iter()
+            .map(|mut rng| {
+                let population = (0..self.config.population_size)
+                    .map(|_| random_genome(self.config.genome_length, self.config.gene_range.clone(), &mut rng))
+                    .collect();
+                IslandState { population, rng }
             })
             .collect()
     }
 
-    fn evaluate_population<C: ProgressCallback>(
-        &mut self,
-        population: &[Genome],
+    /// Backtest every genome of every island together on `pool`, returning each
+    /// island's results in population order. The generation is split into one
+    /// batch per worker, each backtested in a single query. Evaluation uses no
+    /// randomness, so the outcome does not depend on the number of workers.
+    /// Completions are reported to `callback` from this thread as a running count.
+    fn evaluate_islands<C: ProgressCallback>(
+        &self,
+        pool: &rayon::ThreadPool,
+        islands: &[IslandState],
+        contexts: &[IslandContext],
         data: &DataFrame,
         callback: &mut C,
-    ) -> Result<Vec<(Genome, f64, StrategyAST, HashMap<String, f64>)>, TradebiasError> {
-        let mut results = Vec::new();
+    ) -> Result<Vec<Vec<Evaluated>>, TradebiasError> {
+        let genomes: Vec<(usize, &Genome)> = islands
+            .iter()
+            .enumerate()
+            .flat_map(|(i, island)| island.population.iter().map(move |genome| (i, genome)))
+            .collect();
+        let batch_size = genomes.len().div_ceil(pool.current_num_threads()).max(1);
+        let (done_tx, done_rx) = mpsc::channel();
+
+        let results = std::thread::scope(|scope| {
+            let workers = scope.spawn(|| {
+                pool.install(|| {
+                    genomes
+                        .par_chunks(batch_size)
+                        .map_with(done_tx, |done_tx, batch| {
+                            let results = self.evaluate_batch(batch, contexts, data);
+                            for _ in batch {
+                                let _ = done_tx.send(());
+                            }
+                            results
+                        })
+                        .collect::<Result<Vec<_>, TradebiasError>>()
+                })
+            });
+
+            // Ends once every worker's sender is dropped, including on an early error
+            for evaluated in 1..=genomes.len() {
+                if done_rx.recv().is_err() {
+                    break;
+                }
+                callback.on_strategy_evaluated(evaluated, genomes.len());
+            }
 
-        for (i, genome) in population.iter().enumerate() {
-            callback.on_strategy_evaluated(i + 1, population.len());
+            workers.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })?;
 
-            // Generate AST from genome
-            println!("  [{}] Generating AST...", i + 1);
-            let ast = self.semantic_mapper.create_strategy_ast(genome)?;
-            println!("  [{}] AST generated: {}", i + 1, ast.root.to_formula_short(60));
+        let mut evaluated: Vec<Vec<Evaluated>> = islands.iter().map(|_| Vec::new()).collect();
+        for ((island, _), result) in genomes.iter().zip(results.into_iter().flatten()) {
+            evaluated[*island].push(result);
+        }
+        Ok(evaluated)
+    }
 
-            // Run backtest
-            println!("  [{}] Running backtest...", i + 1);
-            let backtest_result = self.backtester.run(&ast, data)?;
-            println!("  [{}] Backtest complete", i + 1);
+    /// Decode each island's genomes in `batch` with its mapper and backtest them
+    /// all with one `run_batch`
+    fn evaluate_batch(
+        &self,
+        batch: &[(usize, &Genome)],
+        contexts: &[IslandContext],
+        data: &DataFrame,
+    ) -> Result<Vec<Evaluated>, TradebiasError> {
+        let asts = batch
+            .iter()
+            .map(|&(island, genome)| contexts[island].semantic_mapper.create_strategy_ast(genome))
+            .collect::<Result<Vec<_>, TradebiasError>>()?;
+        let results = self.backtester.run_batch(&asts, data)?;
 
-            // Calculate fitness
-            let fitness = self.calculate_fitness(&backtest_result.metrics);
+        Ok(batch
+            .iter()
+            .zip(asts)
+            .zip(results)
+            .map(|((&(_, genome), ast), result)| self.evaluated(genome, ast, result))
+            .collect())
+    }
 
-            results.push((genome.clone(), fitness, ast, backtest_result.metrics));
-        }
+    /// What selection needs from `genome`'s backtest
+    fn evaluated(&self, genome: &Genome, ast: StrategyAST, backtest_result: StrategyResult) -> Evaluated {
+        // An aborted run's partial metrics only say how it failed, so it ranks last
+        let fitness = match backtest_result.aborted {
+            Some(_) => f64::NEG_INFINITY,
+            None => self.calculate_fitness(&backtest_result.metrics),
+        };
+        let folds = match (self.config.selection, backtest_result.aborted) {
+            (SelectionMethod::Lexicase { folds }, None) => fold_returns(&backtest_result.equity_curve, folds),
+            (SelectionMethod::Lexicase { folds }, Some(_)) => vec![f64::NEG_INFINITY; folds],
+            _ => Vec::new(),
+        };
 
-        Ok(results)
+        (genome.clone(), fitness, ast, backtest_result.metrics, folds)
     }
 
     fn calculate_fitness(&self, metrics: &HashMap<String, f64>) -> f64 {
@@ -187,29 +488,72 @@ impl EvolutionEngine {
         fitness
     }
 
-    fn create_next_generation(
-        &mut self,
-        evaluated: &[(Genome, f64, StrategyAST, HashMap<String, f64>)],
-    ) -> Vec<Genome> {
+    fn create_next_generation(&self, evaluated: &[Evaluated], context: &IslandContext, rng: &mut ChaCha12Rng) -> Vec<Genome> {
         let mut next_generation = Vec::new();
 
         if self.config.use_pareto {
             // Pareto-based selection
-            self.create_next_generation_pareto(evaluated, &mut next_generation)
+            self.create_next_generation_pareto(evaluated, &mut next_generation, context, rng)
         } else {
             // Single-objective selection
-            self.create_next_generation_single(evaluated, &mut next_generation)
+            self.create_next_generation_single(evaluated, &mut next_generation, context, rng)
         }
     }
 
+    /// Indices of `evaluated`, best first: by fitness, or by Pareto rank and then
+    /// crowding distance
+    fn best_first(&self, evaluated: &[Evaluated]) -> Vec<usize> {
+        use crate::engines::generation::pareto::{calculate_crowding_distance, fast_non_dominated_sort};
+
+        if !self.config.use_pareto {
+            let mut order: Vec<usize> = (0..evaluated.len()).collect();
+            order.sort_by(|&a, &b| evaluated[b].1.partial_cmp(&evaluated[a].1).unwrap_or(std::cmp::Ordering::Equal));
+            return order;
+        }
+
+        let mut individuals = self.pareto_individuals(evaluated);
+        let directions: Vec<_> = self.config.objective_configs.iter().map(|c| c.direction).collect();
+        for front in &fast_non_dominated_sort(&mut individuals, &directions) {
+            calculate_crowding_distance(&mut individuals, front);
+        }
+        individuals.sort_by(|a, b| {
+            a.rank.cmp(&b.rank).then(
+                b.crowding_distance.partial_cmp(&a.crowding_distance).unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+        individuals.into_iter().map(|individual| individual.data).collect()
+    }
+
+    /// Each evaluated strategy's objectives; an aborted one gets the worst of each
+    fn pareto_individuals(
+        &self,
+        evaluated: &[Evaluated],
+    ) -> Vec<crate::engines::generation::pareto::MultiObjectiveIndividual<usize>> {
+        use crate::engines::generation::pareto::{MultiObjectiveIndividual, extract_objectives};
+
+        evaluated
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, _, metrics, _))| {
+                let objectives = match is_aborted(metrics) {
+                    true => worst_objectives(&self.config.objective_configs),
+                    false => extract_objectives(metrics, &self.config.objective_configs),
+                };
+                MultiObjectiveIndividual::new(i, objectives)
+            })
+            .collect()
+    }
+
     fn create_next_generation_single(
-        &mut self,
-        evaluated: &[(Genome, f64, StrategyAST, HashMap<String, f64>)],
+        &self,
+        evaluated: &[Evaluated],
         next_generation: &mut Vec<Genome>,
+        context: &IslandContext,
+        rng: &mut ChaCha12Rng,
     ) -> Vec<Genome> {
         let population_fitness: Vec<(Genome, f64)> = evaluated
             .iter()
-            .map(|(g, f, _, _)| (g.clone(), *f))
+            .map(|(g, f, _, _, _)| (g.clone(), *f))
             .collect();
 
         // Elitism: copy top performers
@@ -221,64 +565,19 @@ impl EvolutionEngine {
             next_generation.push(genome.clone());
         }
 
-        // Generate offspring
-        while next_generation.len() < self.config.population_size {
-            if self.rng.gen::<f64>() < self.config.crossover_rate {
-                // Crossover
-                let parent1 = tournament_selection(
-                    &population_fitness,
-                    self.config.tournament_size,
-                    &mut self.rng,
-                );
-                let parent2 = tournament_selection(
-                    &population_fitness,
-                    self.config.tournament_size,
-                    &mut self.rng,
-                );
-
-                let (mut child1, mut child2) = crossover(&parent1, &parent2, &mut self.rng);
-
-                // Apply mutation
-                mutate(&mut child1, self.config.mutation_rate, self.config.gene_range.clone(), &mut self.rng);
-                mutate(&mut child2, self.config.mutation_rate, self.config.gene_range.clone(), &mut self.rng);
-
-                next_generation.push(child1);
-                if next_generation.len() < self.config.population_size {
-                    next_generation.push(child2);
-                }
-            } else {
-                // Reproduction (copy)
-                let parent = tournament_selection(
-                    &population_fitness,
-                    self.config.tournament_size,
-                    &mut self.rng,
-                );
-                let mut child = parent;
-                mutate(&mut child, self.config.mutation_rate, self.config.gene_range.clone(), &mut self.rng);
-                next_generation.push(child);
-            }
-        }
-
-        next_generation.truncate(self.config.population_size);
-        next_generation.clone()
+        let mut selector = ParentSelector::new(self.config.selection, self.config.tournament_size, population_fitness, evaluated);
+        self.breed(next_generation, |rng| selector.select(rng), context, rng)
     }
 
     fn create_next_generation_pareto(
-        &mut self,
-        evaluated: &[(Genome, f64, StrategyAST, HashMap<String, f64>)],
+        &self,
+        evaluated: &[Evaluated],
         next_generation: &mut Vec<Genome>,
+        context: &IslandContext,
+        rng: &mut ChaCha12Rng,
     ) -> Vec<Genome> {
-        use crate::engines::generation::pareto::{MultiObjectiveIndividual, extract_objectives};
-
         // Convert to MultiObjectiveIndividual and calculate Pareto ranks
-        let mut individuals: Vec<MultiObjectiveIndividual<usize>> = evaluated
-            .iter()
-            .enumerate()
-            .map(|(i, (_, _, _, metrics))| {
-                let objectives = extract_objectives(metrics, &self.config.objective_configs);
-                MultiObjectiveIndividual::new(i, objectives)
-            })
-            .collect();
+        let mut individuals = self.pareto_individuals(evaluated);
 
         let directions: Vec<_> = self.config.objective_configs
             .iter()
@@ -295,7 +594,7 @@ impl EvolutionEngine {
         let population_pareto: Vec<(Genome, usize, f64)> = individuals
             .iter()
             .map(|ind| {
-                let (genome, _, _, _) = &evaluated[ind.data];
+                let (genome, _, _, _, _) = &evaluated[ind.data];
                 (genome.clone(), ind.rank, ind.crowding_distance)
             })
             .collect();
@@ -317,25 +616,50 @@ impl EvolutionEngine {
         }
 
         // Generate offspring using Pareto tournament selection
+        if self.config.selection == SelectionMethod::Tournament {
+            let tournament_size = self.config.tournament_size;
+            return self.breed(
+                next_generation,
+                |rng| pareto_tournament_selection(&population_pareto, tournament_size, rng),
+                context,
+                rng,
+            );
+        }
+
+        // Other methods score each genome by its place in the rank-then-crowding
+        // order: the best scores N, the worst 1
+        let n = sorted.len();
+        let population_scores: Vec<(Genome, f64)> = sorted
+            .into_iter()
+            .enumerate()
+            .map(|(place, (genome, _, _))| (genome, (n - place) as f64))
+            .collect();
+        let mut selector = ParentSelector::new(self.config.selection, self.config.tournament_size, population_scores, evaluated);
+        self.breed(next_generation, |rng| selector.select(rng), context, rng)
+    }
+
+    /// Fill `next_generation` with offspring of parents drawn by `select`
+    fn breed(
+        &self,
+        next_generation: &mut Vec<Genome>,
+        mut select: impl FnMut(&mut ChaCha12Rng) -> Genome,
+        context: &IslandContext,
+        rng: &mut ChaCha12Rng,
+    ) -> Vec<Genome> {
         while next_generation.len() < self.config.population_size {
-            if self.rng.gen::<f64>() < self.config.crossover_rate {
+            if rng.gen::<f64>() < context.crossover_rate {
                 // Crossover
-                let parent1 = pareto_tournament_selection(
-                    &population_pareto,
-                    self.config.tournament_size,
-                    &mut self.rng,
-                );
-                let parent2 = pareto_tournament_selection(
-                    &population_pareto,
-                    self.config.tournament_size,
-                    &mut self.rng,
-                );
+                let parent1 = select(rng);
+                let parent2 = select(rng);
 
-                let (mut child1, mut child2) = crossover(&parent1, &parent2, &mut self.rng);
+                let (mut child1, mut child2) = match self.config.operators {
+                    VariationOperators::Positional => crossover(&parent1, &parent2, rng),
+                    VariationOperators::Tree => subtree_crossover(&parent1, &parent2, &context.semantic_mapper, rng),
+                };
 
                 // Apply mutation
-                mutate(&mut child1, self.config.mutation_rate, self.config.gene_range.clone(), &mut self.rng);
-                mutate(&mut child2, self.config.mutation_rate, self.config.gene_range.clone(), &mut self.rng);
+                self.mutate_child(&mut child1, context, rng);
+                self.mutate_child(&mut child2, context, rng);
 
                 next_generation.push(child1);
                 if next_generation.len() < self.config.population_size {
@@ -343,13 +667,8 @@ impl EvolutionEngine {
                 }
             } else {
                 // Reproduction (copy)
-                let parent = pareto_tournament_selection(
-                    &population_pareto,
-                    self.config.tournament_size,
-                    &mut self.rng,
-                );
-                let mut child = parent;
-                mutate(&mut child, self.config.mutation_rate, self.config.gene_range.clone(), &mut self.rng);
+                let mut child = select(rng);
+                self.mutate_child(&mut child, context, rng);
                 next_generation.push(child);
             }
         }
@@ -358,7 +677,100 @@ impl EvolutionEngine {
         next_generation.clone()
     }
 
+    fn mutate_child(&self, child: &mut Genome, context: &IslandContext, rng: &mut ChaCha12Rng) {
+        match self.config.operators {
+            VariationOperators::Positional => {
+                mutate(child, context.mutation_rate, self.config.gene_range.clone(), rng);
+            }
+            VariationOperators::Tree => {
+                if rng.gen::<f64>() < context.mutation_rate {
+                    *child = tree_mutation(child, &context.semantic_mapper, self.config.gene_range.clone(), rng);
+                }
+            }
+        }
+    }
+
     pub fn get_hall_of_fame(&self) -> &HallOfFame {
         &self.hall_of_fame
     }
 }
+
+/// Whether the backtest behind `metrics` was stopped by its evaluation budget
+fn is_aborted(metrics: &HashMap<String, f64>) -> bool {
+    metrics.get("aborted").is_some_and(|&aborted| aborted > 0.0)
+}
+
+/// Share of distinct strategies among everything evaluated in a generation
+fn diversity(evaluated: &[Vec<Evaluated>]) -> f64 {
+    let strategies: Vec<String> =
+        evaluated.iter().flatten().map(|(_, _, ast, _, _)| get_canonical_ast_string(ast)).collect();
+    let distinct: std::collections::HashSet<&String> = strategies.iter().collect();
+    distinct.len() as f64 / strategies.len().max(1) as f64
+}
+
+/// Objective values every completed strategy dominates
+fn worst_objectives(objective_configs: &[ObjectiveConfig]) -> Vec<f64> {
+    objective_configs
+        .iter()
+        .map(|config| match config.direction {
+            OptimizationDirection::Maximize => f64::MIN,
+            OptimizationDirection::Minimize => f64::MAX,
+        })
+        .collect()
+}
+
+/// Percentage return of each of `folds` consecutive, equally long slices of the bars
+fn fold_returns(equity_curve: &[f64], folds: usize) -> Vec<f64> {
+    let bars = equity_curve.len().saturating_sub(1);
+    (0..folds)
+        .map(|fold| {
+            let (start, end) = (fold * bars / folds, (fold + 1) * bars / folds);
+            match equity_curve[start] {
+                start_equity if start_equity > 0.0 => (equity_curve[end] / start_equity - 1.0) * 100.0,
+                _ => 0.0,
+            }
+        })
+        .collect()
+}
+
+/// Draws the parents of one generation under a `SelectionMethod`
+struct ParentSelector {
+    method: SelectionMethod,
+    tournament_size: usize,
+    /// Each genome with the score fitness-based methods select on
+    scored: Vec<(Genome, f64)>,
+    /// Each genome with its fold returns, for lexicase selection
+    cases: Vec<(Genome, Vec<f64>)>,
+    /// Picks left from the last stochastic universal sample
+    sampled: Vec<Genome>,
+}
+
+impl ParentSelector {
+    fn new(method: SelectionMethod, tournament_size: usize, scored: Vec<(Genome, f64)>, evaluated: &[Evaluated]) -> Self {
+        let cases = match method {
+            SelectionMethod::Lexicase { .. } => {
+                evaluated.iter().map(|(genome, _, _, _, folds)| (genome.clone(), folds.clone())).collect()
+            }
+            _ => Vec::new(),
+        };
+        Self { method, tournament_size, scored, cases, sampled: Vec::new() }
+    }
+
+    fn select(&mut self, rng: &mut ChaCha12Rng) -> Genome {
+        match self.method {
+            SelectionMethod::Tournament => tournament_selection(&self.scored, self.tournament_size, rng),
+            SelectionMethod::Roulette => roulette_selection(&self.scored, rng),
+            SelectionMethod::Rank => rank_selection(&self.scored, rng),
+            SelectionMethod::StochasticUniversal => {
+                if self.sampled.is_empty() {
+                    // Shuffled so consecutive picks pair up at random
+                    self.sampled = stochastic_universal_sampling(&self.scored, self.scored.len(), rng);
+                    self.sampled.shuffle(rng);
+                }
+                self.sampled.pop().unwrap_or_else(|| self.scored[0].0.clone())
+            }
+            SelectionMethod::Lexicase { .. } => lexicase_selection(&self.cases, rng),
+            SelectionMethod::Boltzmann { temperature } => boltzmann_selection(&self.scored, temperature, rng),
+        }
+    }
+}
@@ -2,19 +2,22 @@ use crate::engines::generation::ast::StrategyAST;
 use crate::engines::generation::pareto::{ObjectiveConfig, MultiObjectiveIndividual};
 use crate::engines::generation::pareto;
 
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EliteStrategy {
     pub ast: StrategyAST,
     pub genome: Vec<u32>,
     pub fitness: f64,                  // Legacy single-objective fitness
     pub metrics: HashMap<String, f64>,
     pub canonical_string: String,       // For deduplication
+    pub aborted: bool,                 // Stopped early by the evaluation budget
     pub pareto_rank: usize,            // Pareto frontier rank (0 = best)
     pub crowding_distance: f64,        // Diversity measure
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct HallOfFame {
     strategies: Vec<EliteStrategy>,
     max_size: usize,
@@ -47,6 +50,11 @@ impl HallOfFame {
 
     /// Attempt to add a strategy to the Hall of Fame
     pub fn try_add(&mut self, mut strategy: EliteStrategy) -> bool {
+        // Strategies stopped early were never fully evaluated
+        if strategy.aborted {
+            return false;
+        }
+
         // Deduplication check
         if self.seen_signatures.contains(&strategy.canonical_string) {
             return false; // Duplicate, reject
@@ -0,0 +1,70 @@
+use crate::engines::generation::genome::Genome;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+/// Several populations evolving side by side, exchanging their best genomes
+/// every few generations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IslandModel {
+    /// One entry per island; each evolves `population_size` genomes
+    pub islands: Vec<IslandSettings>,
+    /// Generations between migrations; 0 keeps the islands isolated
+    pub migration_interval: usize,
+    /// Best genomes each island sends per migration
+    pub migrants: usize,
+    pub topology: MigrationTopology,
+}
+
+/// What sets one island apart; unset fields fall back to the engine's own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IslandSettings {
+    pub max_depth: Option<usize>,
+    pub mutation_rate: Option<f64>,
+    /// Indicator aliases the island builds strategies from
+    pub indicators: Option<Vec<String>>,
+}
+
+/// Which islands send migrants to which
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationTopology {
+    /// Island `i` sends to island `i + 1`, the last to the first
+    Ring,
+    /// Every island sends to every other
+    FullyConnected,
+}
+
+impl MigrationTopology {
+    /// Islands sending to `island`, in the order their migrants arrive
+    pub fn sources(&self, island: usize, islands: usize) -> Vec<usize> {
+        match self {
+            _ if islands < 2 => Vec::new(),
+            MigrationTopology::Ring => vec![(island + islands - 1) % islands],
+            MigrationTopology::FullyConnected => (0..islands).filter(|&source| source != island).collect(),
+        }
+    }
+}
+
+/// Population and random generator of one island
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IslandState {
+    pub population: Vec<Genome>,
+    pub rng: ChaCha12Rng,
+}
+
+/// Move `emigrants[i]`, the best genomes of island `i`, to the islands `topology`
+/// sends them to. Arrivals replace the genomes at the end of each population, past
+/// the elites kept at the front.
+pub fn migrate(islands: &mut [IslandState], emigrants: &[Vec<Genome>], topology: MigrationTopology) {
+    for island in 0..islands.len() {
+        let arrivals: Vec<&Genome> = topology
+            .sources(island, islands.len())
+            .into_iter()
+            .flat_map(|source| &emigrants[source])
+            .collect();
+        let population = &mut islands[island].population;
+        let start = population.len().saturating_sub(arrivals.len());
+        for (slot, genome) in population[start..].iter_mut().zip(arrivals) {
+            slot.clone_from(genome);
+        }
+    }
+}
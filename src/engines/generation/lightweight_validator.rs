@@ -33,6 +33,32 @@ impl LightweightValidator {
                 self.validate_node(action, depth + 1)?;
                 Ok(())
             }
+            AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, entry_order } => {
+                if long_entry.is_none() && short_entry.is_none() {
+                    return Err(TradebiasError::Validation(
+                        "Strategy has no entry condition".to_string(),
+                    ));
+                }
+                if let Some(order) = entry_order {
+                    if order.expiry_bars == 0 {
+                        return Err(TradebiasError::Validation(
+                            "Entry order must live for at least one bar".to_string(),
+                        ));
+                    }
+                    if !(order.atr_multiple >= 0.0 && order.atr_multiple.is_finite())
+                        || (order.atr_multiple > 0.0 && order.atr_period == 0)
+                    {
+                        return Err(TradebiasError::Validation(format!(
+                            "Invalid entry order offset: {} x ATR({})",
+                            order.atr_multiple, order.atr_period
+                        )));
+                    }
+                }
+                for condition in [long_entry, long_exit, short_entry, short_exit].into_iter().flatten() {
+                    self.validate_node(condition, depth + 1)?;
+                }
+                Ok(())
+            }
             AstNode::Call { function, args } => {
                 // Function exists?
                 let func = self.registry.get_function(function).ok_or_else(|| {
@@ -1,6 +1,7 @@
 pub mod ast;
 pub mod semantic_mapper;
 pub mod operators;
+pub mod tree_operators;
 pub mod hall_of_fame;
 pub mod evolution_engine;
 pub mod progress;
@@ -10,11 +11,17 @@ pub mod lightweight_validator;
 pub mod optimisation;
 pub mod genome;
 pub mod pareto;
+pub mod checkpoint;
+pub mod islands;
+pub mod adaptation;
 
 pub use genome::Genome;
 pub use ast::*;
 pub use hall_of_fame::{HallOfFame, EliteStrategy};
 pub use evolution_engine::{EvolutionEngine, EvolutionConfig, ProgressCallback};
+pub use checkpoint::{Checkpoint, CheckpointConfig};
+pub use adaptation::{AdaptationConfig, AdaptationState, AdaptedRates};
+pub use islands::{IslandModel, IslandSettings, IslandState, MigrationTopology};
 pub use progress::{ConsoleProgressCallback, IpcProgressCallback};
 pub use semantic_mapper::SemanticMapper;
 pub use diversity_validator::DiversityValidator;
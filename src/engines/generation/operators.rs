@@ -1,5 +1,6 @@
 use crate::engines::generation::genome::Genome;
 use crate::engines::generation::pareto::crowded_comparison;
+use rand::seq::SliceRandom;
 use rand::Rng;
 
 /// Tournament selection: pick best of K random candidates (single-objective)
@@ -84,6 +85,130 @@ pub fn roulette_selection<R: Rng>(
     population[population.len() - 1].0.clone()
 }
 
+/// Rank selection: probability proportional to rank, 1 for the worst up to N for the best
+pub fn rank_selection<R: Rng>(
+    population: &[(Genome, f64)],
+    rng: &mut R,
+) -> Genome {
+    let mut ascending: Vec<usize> = (0..population.len()).collect();
+    ascending.sort_by(|&a, &b| population[a].1.partial_cmp(&population[b].1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let n = population.len() as f64;
+    let mut spin = rng.gen::<f64>() * n * (n + 1.0) / 2.0;
+
+    for (rank, &idx) in ascending.iter().enumerate() {
+        spin -= (rank + 1) as f64;
+        if spin <= 0.0 {
+            return population[idx].0.clone();
+        }
+    }
+
+    population[ascending[ascending.len() - 1]].0.clone()
+}
+
+/// Stochastic universal sampling: `count` picks from one spin of a roulette
+/// wheel with evenly spaced pointers, so each genome is picked close to its
+/// expected number of times. Picks come back in population order.
+pub fn stochastic_universal_sampling<R: Rng>(
+    population: &[(Genome, f64)],
+    count: usize,
+    rng: &mut R,
+) -> Vec<Genome> {
+    let total_fitness: f64 = population.iter().map(|(_, f)| f.max(0.0)).sum();
+
+    if total_fitness <= 0.0 {
+        // All negative fitness, pick random
+        return (0..count).map(|_| population[rng.gen_range(0..population.len())].0.clone()).collect();
+    }
+
+    let step = total_fitness / count as f64;
+    let mut pointer = rng.gen::<f64>() * step;
+    let mut cumulative = 0.0;
+    let mut picks = Vec::with_capacity(count);
+
+    for (genome, fitness) in population {
+        cumulative += fitness.max(0.0);
+        while picks.len() < count && pointer < cumulative {
+            picks.push(genome.clone());
+            pointer += step;
+        }
+    }
+
+    // Rounding can leave the last pointer just past the end
+    while picks.len() < count {
+        picks.push(population[population.len() - 1].0.clone());
+    }
+
+    picks
+}
+
+/// Boltzmann selection: probability proportional to exp(fitness / temperature).
+/// Low temperatures favour the best strongly, high ones approach a uniform pick.
+pub fn boltzmann_selection<R: Rng>(
+    population: &[(Genome, f64)],
+    temperature: f64,
+    rng: &mut R,
+) -> Genome {
+    let best = population.iter().map(|(_, f)| *f).fold(f64::NEG_INFINITY, f64::max);
+
+    if !best.is_finite() {
+        return population[rng.gen_range(0..population.len())].0.clone();
+    }
+
+    // Shifted by the best fitness so the exponent never overflows
+    let weights: Vec<f64> = population.iter().map(|(_, f)| ((f - best) / temperature).exp()).collect();
+    let mut spin = rng.gen::<f64>() * weights.iter().sum::<f64>();
+
+    for ((genome, _), weight) in population.iter().zip(&weights) {
+        spin -= weight;
+        if spin <= 0.0 {
+            return genome.clone();
+        }
+    }
+
+    population[population.len() - 1].0.clone()
+}
+
+/// Epsilon-lexicase selection over per-case scores, higher being better. Cases
+/// are visited in random order, each keeping the candidates within the case's
+/// median absolute deviation of the best; a random survivor is picked.
+pub fn lexicase_selection<R: Rng>(
+    population: &[(Genome, Vec<f64>)],
+    rng: &mut R,
+) -> Genome {
+    let cases = population.first().map_or(0, |(_, scores)| scores.len());
+    let mut order: Vec<usize> = (0..cases).collect();
+    order.shuffle(rng);
+
+    let mut candidates: Vec<usize> = (0..population.len()).collect();
+    for case in order {
+        if candidates.len() <= 1 {
+            break;
+        }
+        let epsilon = median_absolute_deviation(population.iter().map(|(_, scores)| scores[case]));
+        let best = candidates.iter().map(|&i| population[i].1[case]).fold(f64::NEG_INFINITY, f64::max);
+        candidates.retain(|&i| population[i].1[case] >= best - epsilon);
+    }
+
+    population[candidates[rng.gen_range(0..candidates.len())]].0.clone()
+}
+
+/// Median absolute deviation of the finite values, or 0 without any
+fn median_absolute_deviation(values: impl Iterator<Item = f64>) -> f64 {
+    fn median(mut values: Vec<f64>) -> f64 {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        match values.len() {
+            0 => 0.0,
+            n if n % 2 == 1 => values[n / 2],
+            n => (values[n / 2 - 1] + values[n / 2]) / 2.0,
+        }
+    }
+
+    let values: Vec<f64> = values.filter(|v| v.is_finite()).collect();
+    let center = median(values.clone());
+    median(values.iter().map(|v| (v - center).abs()).collect())
+}
+
 /// Single-point crossover: swap genome segments
 pub fn crossover<R: Rng>(
     parent1: &Genome,
@@ -1,17 +1,18 @@
 /// Pareto optimization utilities for multi-objective evolution
 /// Implements NSGA-II style fast non-dominated sorting and crowding distance
 
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Defines whether a metric should be maximized or minimized
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OptimizationDirection {
     Maximize,
     Minimize,
 }
 
 /// Configuration for a single objective in multi-objective optimization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectiveConfig {
     pub metric_name: String,
     pub direction: OptimizationDirection,
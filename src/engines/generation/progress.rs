@@ -1,3 +1,4 @@
+use super::adaptation::AdaptedRates;
 use super::evolution_engine::ProgressCallback;
 
 pub struct ConsoleProgressCallback;
@@ -19,6 +20,13 @@ impl ProgressCallback for ConsoleProgressCallback {
             println!("  Evaluated {}/{} strategies", strategy_num, total);
         }
     }
+
+    fn on_rates_adapted(&mut self, _generation: usize, rates: &AdaptedRates) {
+        println!(
+            "  Mutation rate: {:.4}, crossover rate: {:.4}, diversity: {:.2}, stagnant for {} generations",
+            rates.mutation_rate, rates.crossover_rate, rates.diversity, rates.stagnant_generations
+        );
+    }
 }
 
 // For IPC communication with UI
@@ -30,6 +38,7 @@ pub enum ProgressMessage {
     GenerationStart(usize),
     GenerationComplete { generation: usize, best_fitness: f64, hof_size: usize },
     StrategyEvaluated { current: usize, total: usize },
+    RatesAdapted { generation: usize, rates: AdaptedRates },
 }
 
 impl IpcProgressCallback {
@@ -57,4 +66,8 @@ impl ProgressCallback for IpcProgressCallback {
             total,
         });
     }
+
+    fn on_rates_adapted(&mut self, generation: usize, rates: &AdaptedRates) {
+        let _ = self.sender.send(ProgressMessage::RatesAdapted { generation, rates: *rates });
+    }
 }
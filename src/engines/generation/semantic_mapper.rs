@@ -1,18 +1,40 @@
 use crate::engines::generation::{
     gene_consumer::GeneConsumer,
+    genome::Genome,
     ast::{StrategyAST, StrategyMetadata},
 };
 use crate::functions::registry::FunctionRegistry;
-use crate::types::{AstNode, DataType, Value as ConstValue};
+use crate::types::{AstNode, DataType, EntryOrder, OrderKind, PriceReference, Value as ConstValue};
 use crate::error::TradebiasError;
 use crate::functions::strategy::StrategyFunction;
+use crate::functions::traits::Indicator;
 use crate::utils::indicator_metadata::MetadataRegistry;
 use std::sync::Arc;
 
+const DATA_ACCESSORS: [&str; 5] = ["Open", "High", "Low", "Close", "Volume"];
+const MATH_OPERATIONS: [&str; 4] = ["Add", "Subtract", "Multiply", "Divide"];
+/// Common indicator periods
+const PERIODS: [i64; 13] = [5, 7, 9, 10, 12, 14, 20, 21, 25, 30, 50, 100, 200];
+const SCALAR_COMPARISONS: [&str; 4] = ["gt_scalar", "lt_scalar", "gte_scalar", "lte_scalar"];
+const ORDER_REFERENCES: [PriceReference; 5] = [
+    PriceReference::Close,
+    PriceReference::Extreme,
+    PriceReference::Open,
+    PriceReference::High,
+    PriceReference::Low,
+];
+const ORDER_ATR_MULTIPLES: [f64; 6] = [0.0, 0.25, 0.5, 1.0, 1.5, 2.0];
+const ORDER_EXPIRIES: [usize; 5] = [1, 2, 3, 5, 10];
+
+#[derive(Clone)]
 pub struct SemanticMapper {
     registry: Arc<FunctionRegistry>,
     metadata: MetadataRegistry,
     max_depth: usize,
+    /// Aliases of the indicators strategies may use; all of them when unset
+    indicators: Option<Vec<String>>,
+    /// ATR period of limit and stop entry orders; strategies enter at market when unset
+    entry_orders: Option<usize>,
 }
 
 impl SemanticMapper {
@@ -21,28 +43,57 @@ impl SemanticMapper {
             registry,
             metadata: MetadataRegistry::new(),
             max_depth,
+            indicators: None,
+            entry_orders: None,
         }
     }
 
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Build strategies only from the indicators with these aliases
+    pub fn with_indicators(mut self, indicators: Vec<String>) -> Self {
+        self.indicators = Some(indicators);
+        self
+    }
+
+    /// Let strategies also enter with limit and stop orders, offset from the
+    /// signal bar by multiples of the ATR over `atr_period` bars
+    pub fn with_entry_orders(mut self, atr_period: usize) -> Self {
+        self.entry_orders = Some(atr_period);
+        self
+    }
+
     /// Main entry point: Create complete strategy AST from genome
     pub fn create_strategy_ast(&self, genome: &[u32]) -> Result<StrategyAST, TradebiasError> {
         let mut consumer = GeneConsumer::new(genome);
 
-        // Build condition (must return BoolSeries)
-        let condition = self.build_expression(DataType::BoolSeries, &mut consumer, 0)?;
+        // Which sides the strategy trades: long only, short only, or both
+        let sides = consumer.choose(3);
+        let trades_long = sides != 1;
+        let trades_short = sides != 0;
 
-        // Build action (simple for now, can be extended)
-        // 1.0 = Long signal, -1.0 = Short signal
-        let action_choice = consumer.choose(2);
-        let action = if action_choice == 0 {
-            AstNode::Const(ConstValue::Float(1.0)) // Long
-        } else {
-            AstNode::Const(ConstValue::Float(-1.0)) // Short
+        // Each traded side gets its own entry and exit condition (BoolSeries)
+        let mut side_rules = |enabled: bool| -> Result<_, TradebiasError> {
+            if !enabled {
+                return Ok((None, None));
+            }
+            let entry = self.build_expression(DataType::BoolSeries, &mut consumer, 0)?;
+            let exit = self.build_expression(DataType::BoolSeries, &mut consumer, 0)?;
+            Ok((Some(Box::new(entry)), Some(Box::new(exit))))
         };
-
-        let root = AstNode::Rule {
-            condition: Box::new(condition),
-            action: Box::new(action),
+        let (long_entry, long_exit) = side_rules(trades_long)?;
+        let (short_entry, short_exit) = side_rules(trades_short)?;
+        let entry_order = self.build_entry_order(&mut consumer);
+
+        let root = AstNode::Strategy {
+            long_entry,
+            long_exit,
+            short_entry,
+            short_exit,
+            entry_order,
         };
 
         Ok(StrategyAST {
@@ -51,6 +102,126 @@ impl SemanticMapper {
         })
     }
 
+    /// A terminal of `data_type` decoded from `genes`, as built at the depth limit
+    pub fn create_terminal(&self, data_type: DataType, genes: &[u32]) -> Result<AstNode, TradebiasError> {
+        self.build_terminal(data_type, &mut GeneConsumer::new(genes))
+    }
+
+    /// Argument types of `function` where it produces `data_type`
+    pub fn argument_types(&self, function: &str, data_type: &DataType) -> Vec<DataType> {
+        match data_type {
+            DataType::NumericSeries if DATA_ACCESSORS.contains(&function) => Vec::new(),
+            DataType::NumericSeries if MATH_OPERATIONS.contains(&function) => {
+                vec![DataType::NumericSeries, DataType::NumericSeries]
+            }
+            DataType::NumericSeries => self
+                .available_indicators()
+                .iter()
+                .find(|indicator| indicator.alias() == function)
+                .map(|indicator| indicator.input_types())
+                .unwrap_or_default(),
+            DataType::BoolSeries => self.registry.get_function(function).map(|f| f.input_types()).unwrap_or_default(),
+            DataType::Integer | DataType::Float => Vec::new(),
+        }
+    }
+
+    /// Functions this mapper could build in place of `function`, producing
+    /// `data_type` from arguments of the same types
+    pub fn alternatives(&self, function: &str, data_type: &DataType) -> Vec<String> {
+        let candidates: Vec<String> = match data_type {
+            DataType::NumericSeries if DATA_ACCESSORS.contains(&function) => {
+                DATA_ACCESSORS.iter().map(|name| name.to_string()).collect()
+            }
+            DataType::NumericSeries if MATH_OPERATIONS.contains(&function) => {
+                MATH_OPERATIONS.iter().map(|name| name.to_string()).collect()
+            }
+            DataType::NumericSeries => {
+                self.available_indicators().iter().map(|indicator| indicator.alias().to_string()).collect()
+            }
+            DataType::BoolSeries => self
+                .registry
+                .get_by_output_type(DataType::BoolSeries)
+                .iter()
+                .map(|f| f.name().to_string())
+                .collect(),
+            DataType::Integer | DataType::Float => Vec::new(),
+        };
+        let arguments = self.argument_types(function, data_type);
+
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate != function && self.argument_types(candidate, data_type) == arguments)
+            .collect()
+    }
+
+    /// Inverse of `create_strategy_ast`: a genome this mapper decodes into `ast`.
+    /// Fails for trees it could not have built, such as ones nested past `max_depth`.
+    pub fn encode_strategy_ast(&self, ast: &StrategyAST) -> Result<Genome, TradebiasError> {
+        Ok(self.encode_with_parameters(ast)?.0)
+    }
+
+    /// Like `encode_strategy_ast`, also returning the positions of genes that only
+    /// pick a constant or an entry order setting. Changing one of those changes
+    /// that value and nothing else.
+    pub fn encode_with_parameters(&self, ast: &StrategyAST) -> Result<(Genome, Vec<usize>), TradebiasError> {
+        let AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, entry_order } = ast.root.as_ref() else {
+            return Err(unencodable("a root other than a strategy"));
+        };
+        let mut encoding = Encoding::default();
+
+        let long = side_rules(long_entry, long_exit)?;
+        let short = side_rules(short_entry, short_exit)?;
+        encoding.choice(match (long, short) {
+            (Some(_), None) => 0,
+            (None, Some(_)) => 1,
+            (Some(_), Some(_)) => 2,
+            (None, None) => return Err(unencodable("a strategy trading neither side")),
+        });
+        for (entry, exit) in [long, short].into_iter().flatten() {
+            self.encode_expression(entry, &DataType::BoolSeries, 0, &mut encoding)?;
+            self.encode_expression(exit, &DataType::BoolSeries, 0, &mut encoding)?;
+        }
+
+        match (entry_order, self.entry_orders) {
+            (None, None) => {}
+            (None, Some(_)) => encoding.choice(0),
+            (Some(_), None) => return Err(unencodable("an entry order without entry orders enabled")),
+            (Some(order), Some(atr_period)) => {
+                encoding.choice(match order.kind {
+                    OrderKind::Limit => 1,
+                    OrderKind::Stop => 2,
+                });
+                if order.atr_period != atr_period {
+                    return Err(unencodable("an entry order with another ATR period"));
+                }
+                encoding.parameter(position(&ORDER_REFERENCES, &order.reference, "entry order reference")?);
+                encoding.parameter(position(&ORDER_ATR_MULTIPLES, &order.atr_multiple, "entry order ATR multiple")?);
+                encoding.parameter(position(&ORDER_EXPIRIES, &order.expiry_bars, "entry order expiry")?);
+            }
+        }
+
+        Ok((encoding.genes, encoding.parameters))
+    }
+
+    /// How entries are filled: at market, or with a resting limit or stop order
+    /// when entry orders are enabled
+    fn build_entry_order(&self, consumer: &mut GeneConsumer) -> Option<EntryOrder> {
+        let atr_period = self.entry_orders?;
+        let kind = match consumer.choose(3) {
+            0 => return None,
+            1 => OrderKind::Limit,
+            _ => OrderKind::Stop,
+        };
+
+        Some(EntryOrder {
+            kind,
+            reference: ORDER_REFERENCES[consumer.choose(ORDER_REFERENCES.len())],
+            atr_multiple: ORDER_ATR_MULTIPLES[consumer.choose(ORDER_ATR_MULTIPLES.len())],
+            atr_period,
+            expiry_bars: ORDER_EXPIRIES[consumer.choose(ORDER_EXPIRIES.len())],
+        })
+    }
+
     /// Recursively build expression of desired type
     fn build_expression(
         &self,
@@ -118,7 +289,7 @@ impl SemanticMapper {
         consumer: &mut GeneConsumer,
         depth: usize,
     ) -> Result<AstNode, TradebiasError> {
-        let indicators = self.registry.get_indicators();
+        let indicators = self.available_indicators();
 
         if indicators.is_empty() {
             return self.build_data_accessor(consumer);
@@ -186,11 +357,10 @@ impl SemanticMapper {
     }
 
     fn build_data_accessor(&self, consumer: &mut GeneConsumer) -> Result<AstNode, TradebiasError> {
-        let accessors = ["Open", "High", "Low", "Close", "Volume"];
-        let choice = consumer.choose(accessors.len());
+        let choice = consumer.choose(DATA_ACCESSORS.len());
 
         Ok(AstNode::Call {
-            function: accessors[choice].to_string(),
+            function: DATA_ACCESSORS[choice].to_string(),
             args: vec![],
         })
     }
@@ -200,23 +370,20 @@ impl SemanticMapper {
         consumer: &mut GeneConsumer,
         depth: usize,
     ) -> Result<AstNode, TradebiasError> {
-        let operations = ["Add", "Subtract", "Multiply", "Divide"];
-        let choice = consumer.choose(operations.len());
+        let choice = consumer.choose(MATH_OPERATIONS.len());
 
         // Must call build_expression to ensure depth checking happens
         let arg1 = self.build_expression(DataType::NumericSeries, consumer, depth + 1)?;
         let arg2 = self.build_expression(DataType::NumericSeries, consumer, depth + 1)?;
 
         Ok(AstNode::Call {
-            function: operations[choice].to_string(),
+            function: MATH_OPERATIONS[choice].to_string(),
             args: vec![Box::new(arg1), Box::new(arg2)],
         })
     }
 
     fn build_integer(&self, consumer: &mut GeneConsumer) -> Result<AstNode, TradebiasError> {
-        // Common indicator periods
-        let periods = [5, 7, 9, 10, 12, 14, 20, 21, 25, 30, 50, 100, 200];
-        let value = periods[consumer.choose(periods.len())];
+        let value = PERIODS[consumer.choose(PERIODS.len())];
 
         Ok(AstNode::Const(ConstValue::Integer(value)))
     }
@@ -238,8 +405,7 @@ impl SemanticMapper {
             DataType::BoolSeries => {
                 // When we hit max depth and need a BoolSeries, create a simple comparison
                 // This prevents the "Cannot build terminal for type BoolSeries" error
-                let comparisons = ["gt_scalar", "lt_scalar", "gte_scalar", "lte_scalar"];
-                let choice = consumer.choose(comparisons.len());
+                let choice = consumer.choose(SCALAR_COMPARISONS.len());
 
                 // Get a numeric series (data accessor)
                 let series = self.build_data_accessor(consumer)?;
@@ -248,10 +414,226 @@ impl SemanticMapper {
                 let threshold = self.build_float(consumer)?;
 
                 Ok(AstNode::Call {
-                    function: comparisons[choice].to_string(),
+                    function: SCALAR_COMPARISONS[choice].to_string(),
                     args: vec![Box::new(series), Box::new(threshold)],
                 })
             }
         }
     }
+
+    /// Indicators strategies may use, in the order genes choose them
+    fn available_indicators(&self) -> Vec<Arc<dyn Indicator>> {
+        let mut indicators = self.registry.get_indicators();
+        if let Some(allowed) = &self.indicators {
+            indicators.retain(|indicator| allowed.iter().any(|alias| alias == indicator.alias()));
+        }
+        indicators
+    }
+
+    /// Mirror of `build_expression`
+    fn encode_expression(
+        &self,
+        node: &AstNode,
+        desired_type: &DataType,
+        depth: usize,
+        encoding: &mut Encoding,
+    ) -> Result<(), TradebiasError> {
+        if depth >= self.max_depth {
+            return self.encode_terminal(node, desired_type, encoding);
+        }
+
+        match desired_type {
+            DataType::BoolSeries => {
+                let (function, args) = call(node)?;
+                let functions = self.registry.get_by_output_type(DataType::BoolSeries);
+                let index = functions
+                    .iter()
+                    .position(|f| f.name() == function)
+                    .ok_or_else(|| unencodable(&format!("the condition {}", function)))?;
+                encoding.choice(index);
+                self.encode_arguments(args, &functions[index].input_types(), depth + 1, encoding)
+            }
+            DataType::NumericSeries => {
+                let (function, args) = call(node)?;
+                if DATA_ACCESSORS.contains(&function) {
+                    encoding.choice(1);
+                    return self.encode_data_accessor(node, encoding);
+                }
+                if let Some(index) = MATH_OPERATIONS.iter().position(|&operation| operation == function) {
+                    encoding.choice(2);
+                    encoding.choice(index);
+                    return self.encode_arguments(args, &[DataType::NumericSeries, DataType::NumericSeries], depth + 1, encoding);
+                }
+
+                let indicators = self.available_indicators();
+                let index = indicators
+                    .iter()
+                    .position(|indicator| indicator.alias() == function)
+                    .ok_or_else(|| unencodable(&format!("the series {}", function)))?;
+                encoding.choice(0);
+                encoding.choice(index);
+                self.encode_indicator_arguments(&indicators[index], args, depth + 1, encoding)
+            }
+            DataType::Integer => self.encode_integer(node, &PERIODS, encoding),
+            DataType::Float => self.encode_float(node, encoding),
+        }
+    }
+
+    /// Mirror of `build_indicator_arguments`
+    fn encode_indicator_arguments(
+        &self,
+        indicator: &Arc<dyn Indicator>,
+        args: &[Box<AstNode>],
+        depth: usize,
+        encoding: &mut Encoding,
+    ) -> Result<(), TradebiasError> {
+        let input_types = indicator.input_types();
+        if args.len() != input_types.len() {
+            return Err(unencodable(&format!("{} with {} arguments", indicator.alias(), args.len())));
+        }
+
+        for (arg, arg_type) in args.iter().zip(&input_types) {
+            match arg_type {
+                DataType::Integer => {
+                    let typical = self.metadata.get(indicator.alias()).and_then(|meta| meta.typical_periods.as_ref());
+                    match typical {
+                        Some(periods) => {
+                            let periods: Vec<i64> = periods.iter().map(|&period| period as i64).collect();
+                            self.encode_integer(arg, &periods, encoding)?;
+                        }
+                        None => self.encode_integer(arg, &PERIODS, encoding)?,
+                    }
+                }
+                _ => self.encode_expression(arg, arg_type, depth, encoding)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_arguments(
+        &self,
+        args: &[Box<AstNode>],
+        input_types: &[DataType],
+        depth: usize,
+        encoding: &mut Encoding,
+    ) -> Result<(), TradebiasError> {
+        if args.len() != input_types.len() {
+            return Err(unencodable(&format!("a call with {} arguments instead of {}", args.len(), input_types.len())));
+        }
+        for (arg, arg_type) in args.iter().zip(input_types) {
+            self.encode_expression(arg, arg_type, depth, encoding)?;
+        }
+        Ok(())
+    }
+
+    fn encode_data_accessor(&self, node: &AstNode, encoding: &mut Encoding) -> Result<(), TradebiasError> {
+        let (function, args) = call(node)?;
+        match DATA_ACCESSORS.iter().position(|&accessor| accessor == function) {
+            Some(index) if args.is_empty() => {
+                encoding.choice(index);
+                Ok(())
+            }
+            _ => Err(unencodable(&format!("{} in place of a price or volume", function))),
+        }
+    }
+
+    fn encode_integer(&self, node: &AstNode, periods: &[i64], encoding: &mut Encoding) -> Result<(), TradebiasError> {
+        match node {
+            AstNode::Const(ConstValue::Integer(value)) => {
+                encoding.parameter(position(periods, value, "period")?);
+                Ok(())
+            }
+            _ => Err(unencodable("a non-integer in place of a period")),
+        }
+    }
+
+    /// Mirror of `build_float`, whose values are `gene / u32::MAX * 100`
+    fn encode_float(&self, node: &AstNode, encoding: &mut Encoding) -> Result<(), TradebiasError> {
+        let AstNode::Const(ConstValue::Float(value)) = node else {
+            return Err(unencodable("a non-float in place of a threshold"));
+        };
+        let gene = (value / 100.0 * u32::MAX as f64).round();
+        if !(0.0..=u32::MAX as f64).contains(&gene) {
+            return Err(unencodable(&format!("the threshold {}", value)));
+        }
+
+        // Rounding may land one gene away from the one decoding exactly to `value`
+        let gene = gene as u32;
+        let decoded = |gene: u32| GeneConsumer::new(&[gene]).float_range(0.0, 100.0);
+        let exact = [gene, gene.saturating_sub(1), gene.saturating_add(1)]
+            .into_iter()
+            .find(|&candidate| decoded(candidate) == *value)
+            .ok_or_else(|| unencodable(&format!("the threshold {}", value)))?;
+        encoding.parameters.push(encoding.genes.len());
+        encoding.genes.push(exact);
+        Ok(())
+    }
+
+    /// Mirror of `build_terminal`
+    fn encode_terminal(&self, node: &AstNode, desired_type: &DataType, encoding: &mut Encoding) -> Result<(), TradebiasError> {
+        match desired_type {
+            DataType::NumericSeries => self.encode_data_accessor(node, encoding),
+            DataType::Integer => self.encode_integer(node, &PERIODS, encoding),
+            DataType::Float => self.encode_float(node, encoding),
+            DataType::BoolSeries => {
+                let (function, args) = call(node)?;
+                let (Some(index), [series, threshold]) =
+                    (SCALAR_COMPARISONS.iter().position(|&comparison| comparison == function), args)
+                else {
+                    return Err(unencodable(&format!("{} past the depth limit", function)));
+                };
+                encoding.choice(index);
+                self.encode_data_accessor(series, encoding)?;
+                self.encode_float(threshold, encoding)
+            }
+        }
+    }
+}
+
+/// Genes written by the encoder, and which of them are parameters
+#[derive(Default)]
+struct Encoding {
+    genes: Genome,
+    parameters: Vec<usize>,
+}
+
+impl Encoding {
+    /// A gene picking option `index`
+    fn choice(&mut self, index: usize) {
+        self.genes.push(index as u32);
+    }
+
+    /// A gene picking value `index` of a constant
+    fn parameter(&mut self, index: usize) {
+        self.parameters.push(self.genes.len());
+        self.choice(index);
+    }
+}
+
+fn unencodable(what: &str) -> TradebiasError {
+    TradebiasError::Generation(format!("Cannot encode {} as a genome", what))
+}
+
+fn call(node: &AstNode) -> Result<(&str, &[Box<AstNode>]), TradebiasError> {
+    match node {
+        AstNode::Call { function, args } => Ok((function, args)),
+        _ => Err(unencodable("a constant in place of a series")),
+    }
+}
+
+fn position<T: PartialEq + std::fmt::Debug>(options: &[T], value: &T, what: &str) -> Result<usize, TradebiasError> {
+    options.iter().position(|option| option == value).ok_or_else(|| unencodable(&format!("the {} {:?}", what, value)))
+}
+
+/// Entry and exit of one side, which the mapper builds both or neither of
+fn side_rules<'a>(
+    entry: &'a Option<Box<AstNode>>,
+    exit: &'a Option<Box<AstNode>>,
+) -> Result<Option<(&'a AstNode, &'a AstNode)>, TradebiasError> {
+    match (entry, exit) {
+        (Some(entry), Some(exit)) => Ok(Some((entry, exit))),
+        (None, None) => Ok(None),
+        _ => Err(unencodable("a side with only one of its entry and exit")),
+    }
 }
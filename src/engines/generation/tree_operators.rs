@@ -0,0 +1,233 @@
+use crate::engines::generation::{ast::StrategyAST, genome::Genome, semantic_mapper::SemanticMapper};
+use crate::types::{AstNode, DataType};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Random picks an operator tries before giving up and returning its parent
+const ATTEMPTS: usize = 10;
+
+/// Genes a shrunk subtree's replacement terminal is decoded from
+const TERMINAL_GENES: usize = 4;
+
+/// A subtree of a strategy: the child indices leading to it, the type it
+/// produces and the depth the mapper built it at
+struct Site {
+    path: Vec<usize>,
+    data_type: DataType,
+    depth: usize,
+}
+
+/// Subtree crossover: swap a random subtree of each parent's strategy for one of
+/// the same type from the other. Everything outside the swapped subtrees is
+/// inherited unchanged.
+pub fn subtree_crossover<R: Rng>(
+    parent1: &Genome,
+    parent2: &Genome,
+    mapper: &SemanticMapper,
+    rng: &mut R,
+) -> (Genome, Genome) {
+    let (Ok(ast1), Ok(ast2)) = (mapper.create_strategy_ast(parent1), mapper.create_strategy_ast(parent2)) else {
+        return (parent1.clone(), parent2.clone());
+    };
+    let (sites1, sites2) = (sites(mapper, &ast1.root), sites(mapper, &ast2.root));
+
+    for _ in 0..ATTEMPTS {
+        let Some(site1) = sites1.choose(rng) else { break };
+        // Subtrees at the same depth decode alike in either parent, so prefer them
+        let matching: Vec<&Site> = sites2.iter().filter(|site| site.data_type == site1.data_type).collect();
+        let level: Vec<&Site> = matching.iter().copied().filter(|site| site.depth == site1.depth).collect();
+        let Some(site2) = (if level.is_empty() { &matching } else { &level }).choose(rng) else { continue };
+
+        let mut child1 = ast1.clone();
+        let mut child2 = ast2.clone();
+        *node_at_mut(&mut child1.root, &site1.path) = node_at(&ast2.root, &site2.path).clone();
+        *node_at_mut(&mut child2.root, &site2.path) = node_at(&ast1.root, &site1.path).clone();
+
+        if let (Some(child1), Some(child2)) = (encode(mapper, &child1, parent1), encode(mapper, &child2, parent2)) {
+            return (child1, child2);
+        }
+    }
+
+    (parent1.clone(), parent2.clone())
+}
+
+/// One of point, hoist, shrink and parameter mutation, picked at random
+pub fn tree_mutation<R: Rng>(
+    genome: &Genome,
+    mapper: &SemanticMapper,
+    gene_range: std::ops::Range<u32>,
+    rng: &mut R,
+) -> Genome {
+    match rng.gen_range(0..4) {
+        0 => point_mutation(genome, mapper, rng),
+        1 => hoist_mutation(genome, mapper, rng),
+        2 => shrink_mutation(genome, mapper, gene_range, rng),
+        _ => parameter_mutation(genome, mapper, gene_range, rng),
+    }
+}
+
+/// Point mutation: replace one function with another taking arguments of the
+/// same types, keeping the arguments
+pub fn point_mutation<R: Rng>(genome: &Genome, mapper: &SemanticMapper, rng: &mut R) -> Genome {
+    mutate_tree(genome, mapper, rng, |ast, sites, rng| {
+        let site = sites.choose(rng)?;
+        let AstNode::Call { function, .. } = node_at_mut(&mut ast.root, &site.path) else { return None };
+        *function = mapper.alternatives(function, &site.data_type).choose(rng)?.clone();
+        Some(())
+    })
+}
+
+/// Hoist mutation: replace a subtree with one of its own descendants of the
+/// same type, shortening the strategy
+pub fn hoist_mutation<R: Rng>(genome: &Genome, mapper: &SemanticMapper, rng: &mut R) -> Genome {
+    mutate_tree(genome, mapper, rng, |ast, sites, rng| {
+        let site = sites.choose(rng)?;
+        let descendants: Vec<&Site> = sites
+            .iter()
+            .filter(|other| {
+                other.path.len() > site.path.len()
+                    && other.path.starts_with(&site.path)
+                    && other.data_type == site.data_type
+            })
+            .collect();
+        let descendant = descendants.choose(rng)?;
+        let hoisted = node_at(&ast.root, &descendant.path).clone();
+        *node_at_mut(&mut ast.root, &site.path) = hoisted;
+        Some(())
+    })
+}
+
+/// Shrink mutation: replace a subtree with a random terminal of the same type
+pub fn shrink_mutation<R: Rng>(
+    genome: &Genome,
+    mapper: &SemanticMapper,
+    gene_range: std::ops::Range<u32>,
+    rng: &mut R,
+) -> Genome {
+    mutate_tree(genome, mapper, rng, |ast, sites, rng| {
+        let calls: Vec<&Site> = sites
+            .iter()
+            .filter(|site| matches!(node_at(&ast.root, &site.path), AstNode::Call { args, .. } if !args.is_empty()))
+            .collect();
+        let site = calls.choose(rng)?;
+        let genes: Vec<u32> = (0..TERMINAL_GENES).map(|_| rng.gen_range(gene_range.clone())).collect();
+        let terminal = mapper.create_terminal(site.data_type.clone(), &genes).ok()?;
+        *node_at_mut(&mut ast.root, &site.path) = terminal;
+        Some(())
+    })
+}
+
+/// Parameter mutation: redraw one constant, such as a period or threshold, or
+/// one entry order setting, leaving the structure of the strategy alone
+pub fn parameter_mutation<R: Rng>(
+    genome: &Genome,
+    mapper: &SemanticMapper,
+    gene_range: std::ops::Range<u32>,
+    rng: &mut R,
+) -> Genome {
+    let Ok(ast) = mapper.create_strategy_ast(genome) else {
+        return genome.clone();
+    };
+    let Ok((mut genes, parameters)) = mapper.encode_with_parameters(&ast) else {
+        return genome.clone();
+    };
+    let Some(&parameter) = parameters.choose(rng) else {
+        return genome.clone();
+    };
+
+    genes[parameter] = rng.gen_range(gene_range);
+    pad(genes, genome)
+}
+
+/// Apply `change` to the strategy `genome` decodes to, retrying with fresh random
+/// picks while the result is not a strategy the mapper could build
+fn mutate_tree<R: Rng>(
+    genome: &Genome,
+    mapper: &SemanticMapper,
+    rng: &mut R,
+    mut change: impl FnMut(&mut StrategyAST, &[Site], &mut R) -> Option<()>,
+) -> Genome {
+    let Ok(ast) = mapper.create_strategy_ast(genome) else {
+        return genome.clone();
+    };
+    let sites = sites(mapper, &ast.root);
+
+    for _ in 0..ATTEMPTS {
+        let mut mutated = ast.clone();
+        if change(&mut mutated, &sites, rng).is_none() {
+            continue;
+        }
+        if let Some(child) = encode(mapper, &mutated, genome) {
+            return child;
+        }
+    }
+
+    genome.clone()
+}
+
+/// Genome of `ast`, if the mapper decodes it back to exactly that strategy
+fn encode(mapper: &SemanticMapper, ast: &StrategyAST, parent: &Genome) -> Option<Genome> {
+    let genes = mapper.encode_strategy_ast(ast).ok()?;
+    let decoded = mapper.create_strategy_ast(&genes).ok()?;
+    (decoded.root == ast.root).then(|| pad(genes, parent))
+}
+
+/// Extend `genes` to the parent's length with the parent's own trailing genes,
+/// which decoding does not reach
+fn pad(mut genes: Genome, parent: &Genome) -> Genome {
+    if genes.len() < parent.len() {
+        genes.extend_from_slice(&parent[genes.len()..]);
+    }
+    genes
+}
+
+/// Every subtree below the strategy root, in depth-first order
+fn sites(mapper: &SemanticMapper, root: &AstNode) -> Vec<Site> {
+    fn visit(mapper: &SemanticMapper, node: &AstNode, site: Site, sites: &mut Vec<Site>) {
+        if let AstNode::Call { function, args } = node {
+            let arg_types = mapper.argument_types(function, &site.data_type);
+            for (i, (arg, data_type)) in args.iter().zip(arg_types).enumerate() {
+                let path = [site.path.as_slice(), &[i]].concat();
+                visit(mapper, arg, Site { path, data_type, depth: site.depth + 1 }, sites);
+            }
+        }
+        sites.push(site);
+    }
+
+    let mut sites = Vec::new();
+    for (i, side) in children(root).into_iter().enumerate() {
+        visit(mapper, side, Site { path: vec![i], data_type: DataType::BoolSeries, depth: 0 }, &mut sites);
+    }
+    sites
+}
+
+/// Child nodes in path order; a strategy's are its present entry and exit rules
+fn children(node: &AstNode) -> Vec<&AstNode> {
+    match node {
+        AstNode::Const(_) => Vec::new(),
+        AstNode::Call { args, .. } => args.iter().map(|arg| arg.as_ref()).collect(),
+        AstNode::Rule { condition, action } => vec![condition.as_ref(), action.as_ref()],
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            [long_entry, long_exit, short_entry, short_exit].into_iter().flatten().map(|side| side.as_ref()).collect()
+        }
+    }
+}
+
+fn children_mut(node: &mut AstNode) -> Vec<&mut AstNode> {
+    match node {
+        AstNode::Const(_) => Vec::new(),
+        AstNode::Call { args, .. } => args.iter_mut().map(|arg| arg.as_mut()).collect(),
+        AstNode::Rule { condition, action } => vec![condition.as_mut(), action.as_mut()],
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            [long_entry, long_exit, short_entry, short_exit].into_iter().flatten().map(|side| side.as_mut()).collect()
+        }
+    }
+}
+
+fn node_at<'a>(root: &'a AstNode, path: &[usize]) -> &'a AstNode {
+    path.iter().fold(root, |node, &i| children(node)[i])
+}
+
+fn node_at_mut<'a>(root: &'a mut AstNode, path: &[usize]) -> &'a mut AstNode {
+    path.iter().fold(root, |node, &i| children_mut(node).swap_remove(i))
+}
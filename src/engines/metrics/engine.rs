@@ -14,10 +14,12 @@ impl MetricsEngine {
 
     pub fn calculate_all(&self, result: &StrategyResult) -> HashMap<String, f64> {
         let mut all_metrics = HashMap::new();
+        // Partial closes of one entry count as a single round trip
+        let round_trips = Trade::round_trips(&result.trades);
 
         // Profitability metrics
         let profit_metrics = ProfitabilityMetrics::calculate(
-            &result.trades,
+            &round_trips,
             self.initial_balance
         );
         all_metrics.extend(profit_metrics);
@@ -27,7 +29,7 @@ impl MetricsEngine {
         all_metrics.extend(risk_metrics);
 
         // Basic metrics
-        all_metrics.insert("num_trades".to_string(), result.trades.len() as f64);
+        all_metrics.insert("num_trades".to_string(), round_trips.len() as f64);
         all_metrics.insert("final_balance".to_string(),
             result.equity_curve.last().copied().unwrap_or(self.initial_balance));
 
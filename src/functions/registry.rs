@@ -20,7 +20,7 @@ use crate::functions::{
         CrossAbove, CrossBelow,
     },
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc};
 
 use super::{
     strategy::StrategyFunction,
@@ -29,13 +29,14 @@ use super::{
 use crate::types::DataType;
 
 pub struct FunctionRegistry {
-    functions: HashMap<String, StrategyFunction>,
+    /// Ordered by name so genomes map to the same functions in every run
+    functions: BTreeMap<String, StrategyFunction>,
 }
 
 impl FunctionRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
-            functions: HashMap::new(),
+            functions: BTreeMap::new(),
         };
         registry.register_indicators();
         registry.register_primitives();
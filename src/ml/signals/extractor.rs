@@ -24,6 +24,8 @@ impl SignalExtractor {
     ) -> Result<SignalDataset> {
         let condition = match ast.root.as_ref() {
             AstNode::Rule { condition, .. } => condition,
+            AstNode::Strategy { long_entry: Some(condition), .. } => condition,
+            AstNode::Strategy { short_entry: Some(condition), .. } => condition,
             _ => return Err(TradebiasError::Validation("StrategyAST root is not a Rule node".to_string())),
         };
 
@@ -86,6 +88,9 @@ impl SignalExtractor {
                     _ => Err(TradebiasError::Validation("Unknown or unsupported action in AST".to_string())),
                 }
             },
+            // Signals follow the same entry condition chosen in `extract`
+            AstNode::Strategy { long_entry: Some(_), .. } => Ok(SignalDirection::Long),
+            AstNode::Strategy { short_entry: Some(_), .. } => Ok(SignalDirection::Short),
             _ => Err(TradebiasError::Validation("StrategyAST root is not a Rule node for signal direction".to_string())),
         }
     }
@@ -1,3 +1,4 @@
+use crate::config::backtesting::{ExecutionTiming, IntrabarAssumption};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -22,7 +23,7 @@ pub enum DataType {
 }
 
 /// Abstract Syntax Tree node
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AstNode {
     Const(Value),
     Call {
@@ -33,9 +34,70 @@ pub enum AstNode {
         condition: Box<AstNode>,
         action: Box<AstNode>,
     },
+    /// Separate boolean conditions for opening and closing each side.
+    /// A missing condition never fires. Entries are market orders unless
+    /// `entry_order` asks for a resting limit or stop order instead.
+    Strategy {
+        long_entry: Option<Box<AstNode>>,
+        long_exit: Option<Box<AstNode>>,
+        short_entry: Option<Box<AstNode>>,
+        short_exit: Option<Box<AstNode>>,
+        #[serde(default)]
+        entry_order: Option<EntryOrder>,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Resting order types for entries that do not fill at market
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderKind {
+    /// Fill at the order price or better: below the market for longs, above for shorts
+    Limit,
+    /// Fill once price trades through the order price: above the market for longs, below for shorts
+    Stop,
+}
+
+/// Bar price an entry order is priced from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceReference {
+    Open,
+    High,
+    Low,
+    Close,
+    /// The high for orders resting above the market and the low for orders below it,
+    /// e.g. a buy stop above the signal bar's high and a sell stop below its low
+    Extreme,
+}
+
+/// How an entry signal is turned into a resting order. The order is priced from the
+/// signal bar at `reference` moved `atr_multiple` ATRs away from the market (lower
+/// for a long limit or short stop, higher for a long stop or short limit), and is
+/// cancelled if it has not filled within `expiry_bars` bars.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EntryOrder {
+    pub kind: OrderKind,
+    pub reference: PriceReference,
+    pub atr_multiple: f64,
+    pub atr_period: usize,
+    pub expiry_bars: usize,
+}
+
+impl EntryOrder {
+    fn to_formula(self) -> String {
+        let kind = match self.kind {
+            OrderKind::Limit => "LIMIT",
+            OrderKind::Stop => "STOP",
+        };
+        let reference = format!("{:?}", self.reference).to_uppercase();
+        let offset = if self.atr_multiple > 0.0 {
+            format!(" +/- {}*ATR({})", self.atr_multiple, self.atr_period)
+        } else {
+            String::new()
+        };
+        format!("VIA {} AT {}{} FOR {} BARS", kind, reference, offset, self.expiry_bars)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     Integer(i64),
     Float(f64),
@@ -44,8 +106,10 @@ pub enum Value {
 }
 
 /// Trade record
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Trade {
+    /// Identifies the entry this trade closes; partial closes of one entry share it
+    pub entry_id: usize,
     pub entry_bar: usize,
     pub exit_bar: usize,
     pub entry_price: f64,
@@ -54,7 +118,55 @@ pub struct Trade {
     pub size: f64,
     pub profit: f64,
     pub exit_reason: ExitReason,
+    /// Commission paid on entry and exit
     pub fees: f64,
+    /// Cost of slippage on entry and exit, already reflected in the fill prices
+    pub slippage: f64,
+    /// Funding or borrow cost accrued while held; negative when funding was received
+    pub carry: f64,
+}
+
+impl Trade {
+    /// Profit before commission, slippage and carry
+    pub fn gross_profit(&self) -> f64 {
+        self.profit + self.fees + self.slippage + self.carry
+    }
+
+    /// Combine partial closes of the same entry into one round trip each, ordered by
+    /// first exit. Size, P&L and costs are summed, the exit price is size-weighted,
+    /// and the exit bar and reason are those of the last close.
+    pub fn round_trips<'a>(trades: impl IntoIterator<Item = &'a Trade>) -> Vec<Trade> {
+        let mut round_trips: Vec<Trade> = Vec::new();
+        let mut index: HashMap<usize, usize> = HashMap::new();
+
+        for trade in trades {
+            match index.get(&trade.entry_id) {
+                Some(&i) => {
+                    let merged = &mut round_trips[i];
+                    let size = merged.size + trade.size;
+                    if size > 0.0 {
+                        merged.exit_price =
+                            (merged.exit_price * merged.size + trade.exit_price * trade.size) / size;
+                    }
+                    merged.size = size;
+                    merged.profit += trade.profit;
+                    merged.fees += trade.fees;
+                    merged.slippage += trade.slippage;
+                    merged.carry += trade.carry;
+                    if trade.exit_bar >= merged.exit_bar {
+                        merged.exit_bar = trade.exit_bar;
+                        merged.exit_reason = trade.exit_reason;
+                    }
+                }
+                None => {
+                    index.insert(trade.entry_id, round_trips.len());
+                    round_trips.push(trade.clone());
+                }
+            }
+        }
+
+        round_trips
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -69,6 +181,81 @@ pub enum ExitReason {
     TakeProfit,
     Signal,
     EndOfData,
+    /// Hit a trailing or chandelier stop
+    TrailingStop,
+    /// A stop moved to the entry price after the breakeven threshold
+    Breakeven,
+    /// Held for the maximum number of bars
+    TimeStop,
+    /// Price reached the lot's liquidation price
+    Liquidation,
+    /// Account equity fell below the maintenance margin
+    MarginCall,
+}
+
+/// Stage in the life of a resting entry order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Placed,
+    Filled,
+    /// Reached its expiry without filling
+    Expired,
+    /// Replaced by a newer order, refused at fill time, or still open at the end of data
+    Cancelled,
+}
+
+/// One step in an entry order's lifecycle, recorded alongside the trades
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub order_id: usize,
+    pub bar: usize,
+    pub direction: Direction,
+    pub kind: OrderKind,
+    /// Order price, or the fill price for `Filled`
+    pub price: f64,
+    pub status: OrderStatus,
+}
+
+/// Mark-to-market account state at the close of one bar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub bar: usize,
+    /// Cash balance, including proceeds from short sales
+    pub cash: f64,
+    /// Signed market value of open lots: longs positive, shorts negative
+    pub position_value: f64,
+    /// Initial margin committed to open lots at current prices
+    pub margin_used: f64,
+    /// Equity required to keep the open lots
+    pub maintenance_margin: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    /// `cash + position_value`, which always equals initial capital plus realised and unrealised P&L
+    pub equity: f64,
+}
+
+/// How bars where a lot's stop and target were both in range were decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct IntrabarResolution {
+    /// Whether a lower-timeframe series was available to walk
+    pub lower_timeframe: bool,
+    /// Assumption applied to ambiguous bars the lower timeframe could not decide
+    pub assumption: IntrabarAssumption,
+    /// Fills where the stop and target were both reachable and the open did not decide
+    pub ambiguous: usize,
+    /// Of those, fills decided by the lower timeframe
+    pub resolved: usize,
+}
+
+/// Why a backtest was stopped before the last bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbortReason {
+    /// Drawdown passed the budget's limit on this bar
+    MaxDrawdown { bar: usize },
+    /// Too few lots had been opened by this checkpoint bar
+    TooFewTrades { bar: usize },
+    /// The signals never changed, so no bar was simulated
+    ConstantSignal,
 }
 
 /// Complete strategy evaluation result
@@ -77,8 +264,20 @@ pub struct StrategyResult {
     pub ast: AstNode,
     pub metrics: HashMap<String, f64>,
     pub trades: Vec<Trade>,
+    /// Initial capital followed by each bar's `LedgerEntry::equity`
     pub equity_curve: Vec<f64>,
+    /// Per-bar account breakdown behind `equity_curve`
+    pub ledger: Vec<LedgerEntry>,
+    /// Lifecycle of every limit and stop entry order
+    pub order_events: Vec<OrderEvent>,
     pub in_sample: bool,
+    /// Fill assumption the result was scored under
+    pub execution_timing: ExecutionTiming,
+    /// Intrabar ordering assumption the result was scored under
+    pub intrabar: IntrabarResolution,
+    /// Set when the evaluation budget stopped the run early; metrics then cover
+    /// only the bars simulated
+    pub aborted: Option<AbortReason>,
 }
 
 // AST Pretty Printer Implementation
@@ -110,6 +309,32 @@ impl AstNode {
             AstNode::Rule { condition, action } => {
                 format!("IF {} THEN {}", condition.to_formula(), action.to_formula())
             }
+            AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, entry_order } => {
+                let side = |name: &str, entry: &Option<Box<AstNode>>, exit: &Option<Box<AstNode>>| {
+                    let mut parts = Vec::new();
+                    if let Some(entry) = entry {
+                        parts.push(format!("ENTER IF {}", entry.to_formula()));
+                    }
+                    if let Some(exit) = exit {
+                        parts.push(format!("EXIT IF {}", exit.to_formula()));
+                    }
+                    (!parts.is_empty()).then(|| format!("{}: {}", name, parts.join(" ")))
+                };
+
+                let sides: Vec<String> = [
+                    side("LONG", long_entry, long_exit),
+                    side("SHORT", short_entry, short_exit),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+
+                match (sides.is_empty(), entry_order) {
+                    (true, _) => "NO RULES".to_string(),
+                    (false, Some(order)) => format!("{}; {}", sides.join("; "), order.to_formula()),
+                    (false, None) => sides.join("; "),
+                }
+            }
         }
     }
 
@@ -190,4 +415,52 @@ mod tests {
         assert!(short.len() <= 30);
         assert!(short.ends_with("..."));
     }
+
+    #[test]
+    fn test_ast_to_formula_strategy() {
+        let rsi = |op: &str, level: i64| {
+            Box::new(AstNode::Call {
+                function: op.to_string(),
+                args: vec![
+                    Box::new(AstNode::Call {
+                        function: "RSI".to_string(),
+                        args: vec![Box::new(AstNode::Const(Value::Integer(14)))],
+                    }),
+                    Box::new(AstNode::Const(Value::Integer(level))),
+                ],
+            })
+        };
+        let ast = AstNode::Strategy {
+            long_entry: Some(rsi("Less", 30)),
+            long_exit: Some(rsi("Greater", 70)),
+            short_entry: None,
+            short_exit: None,
+            entry_order: None,
+        };
+
+        assert_eq!(
+            ast.to_formula(),
+            "LONG: ENTER IF Less(RSI(14), 30) EXIT IF Greater(RSI(14), 70)"
+        );
+
+        let AstNode::Strategy { long_entry, long_exit, .. } = ast else { unreachable!() };
+        let ast = AstNode::Strategy {
+            long_entry,
+            long_exit,
+            short_entry: None,
+            short_exit: None,
+            entry_order: Some(EntryOrder {
+                kind: OrderKind::Limit,
+                reference: PriceReference::Close,
+                atr_multiple: 0.5,
+                atr_period: 14,
+                expiry_bars: 3,
+            }),
+        };
+
+        assert_eq!(
+            ast.to_formula(),
+            "LONG: ENTER IF Less(RSI(14), 30) EXIT IF Greater(RSI(14), 70); VIA LIMIT AT CLOSE +/- 0.5*ATR(14) FOR 3 BARS"
+        );
+    }
 }
@@ -10,6 +10,7 @@ pub struct TradeBiasApp {
     right_panel: RightPanel,
     evolution_runner: Option<EvolutionRunner>,
     run_requested: bool,
+    resume_requested: bool,
     stop_requested: bool,
 }
 
@@ -28,6 +29,7 @@ impl Default for TradeBiasApp {
             right_panel: RightPanel::new(),
             evolution_runner: None,
             run_requested: false,
+            resume_requested: false,
             stop_requested: false,
         }
     }
@@ -99,6 +101,32 @@ impl TradeBiasApp {
                 self.state.current_generation = 0;
                 self.state.progress_percentage = 0.0;
                 self.state.status_message = "Evolution started".to_string();
+                self.state.rate_history.clear();
+            }
+        }
+    }
+
+    fn handle_resume_button(&mut self) {
+        if self.resume_requested && !self.state.is_running {
+            self.resume_requested = false;
+
+            let evolution_config = ConfigBridge::to_evolution_config(&self.state);
+            let backtesting_config = ConfigBridge::to_backtesting_config(&self.state);
+            let trade_management_config = ConfigBridge::to_trade_management_config(&self.state);
+
+            if let Some(data) = self.state.loaded_data.clone() {
+                self.evolution_runner = Some(EvolutionRunner::resume(
+                    data,
+                    evolution_config,
+                    backtesting_config,
+                    trade_management_config,
+                ));
+
+                self.state.is_running = true;
+                self.state.current_generation = 0;
+                self.state.progress_percentage = 0.0;
+                self.state.status_message = "Evolution resumed".to_string();
+                self.state.rate_history.clear();
             }
         }
     }
@@ -124,6 +152,7 @@ impl TradeBiasApp {
                 self.state.progress_percentage =
                     update.generation as f32 / update.total_generations as f32;
                 self.state.status_message = update.status;
+                self.state.rate_history.extend(update.rates);
             }
 
             // Check for completion
@@ -151,6 +180,7 @@ impl eframe::App for TradeBiasApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Handle evolution state
         self.handle_run_button();
+        self.handle_resume_button();
         self.handle_stop_button();
         self.poll_evolution_progress();
 
@@ -185,6 +215,11 @@ impl eframe::App for TradeBiasApp {
                         self.run_requested = true;
                     }
 
+                    // Check if Resume button was clicked
+                    if self.state.status_message == "Resuming evolution..." {
+                        self.resume_requested = true;
+                    }
+
                     // Check if Stop button was clicked
                     if self.state.status_message == "Stopping..." {
                         self.stop_requested = true;
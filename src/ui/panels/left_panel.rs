@@ -1,5 +1,9 @@
-use crate::config::backtesting::ValidationMethod;
-use crate::config::trade_management::{StopLossConfig, TakeProfitConfig, PositionSizing};
+use crate::config::backtesting::{
+    CarryCostModel, CommissionModel, ExecutionTiming, IntrabarAssumption, SlippageModel, ValidationMethod,
+};
+use crate::config::evolution::{SelectionMethod, VariationOperators};
+use crate::config::trade_management::{StopLossConfig, TakeProfitConfig, PositionSizing, ScaleOut};
+use crate::ui::services::ConfigBridge;
 use crate::ui::state::AppState;
 use crate::ui::widgets::{DataSelector, IndicatorSelector, MetricsSelector};
 
@@ -63,24 +67,136 @@ impl LeftPanel {
                 .range(100.0..=1_000_000.0));
         });
 
+        ui.label("Commission:");
+        egui::ComboBox::from_id_salt("commission")
+            .selected_text(format!("{:?}", state.commission))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut state.commission, CommissionModel::None, "None");
+                ui.selectable_value(&mut state.commission, CommissionModel::Percent { percent: 0.1 }, "Percent");
+                ui.selectable_value(&mut state.commission, CommissionModel::PerUnit { amount: 0.01 }, "Per Unit");
+                ui.selectable_value(&mut state.commission, CommissionModel::PerTrade { amount: 1.0 }, "Per Trade");
+            });
+
+        match &mut state.commission {
+            CommissionModel::None => {}
+            CommissionModel::Percent { percent } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Percent:");
+                    ui.add(egui::DragValue::new(percent)
+                        .suffix("%")
+                        .speed(0.001)
+                        .range(0.0..=1.0)
+                        .fixed_decimals(3));
+                });
+            }
+            CommissionModel::PerUnit { amount } | CommissionModel::PerTrade { amount } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Amount:");
+                    ui.add(egui::DragValue::new(amount).prefix("$").speed(0.01).range(0.0..=1000.0));
+                });
+            }
+        }
+
+        ui.label("Slippage:");
+        egui::ComboBox::from_id_salt("slippage")
+            .selected_text(format!("{:?}", state.slippage))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut state.slippage, SlippageModel::None, "None");
+                ui.selectable_value(&mut state.slippage, SlippageModel::Percent { percent: 0.05 }, "Percent");
+                ui.selectable_value(&mut state.slippage, SlippageModel::Ticks { ticks: 1.0, tick_size: 0.01 }, "Ticks");
+                ui.selectable_value(&mut state.slippage, SlippageModel::BarRange { fraction: 0.1 }, "Bar Range");
+            });
+
+        match &mut state.slippage {
+            SlippageModel::None => {}
+            SlippageModel::Percent { percent } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Percent:");
+                    ui.add(egui::DragValue::new(percent)
+                        .suffix("%")
+                        .speed(0.001)
+                        .range(0.0..=1.0)
+                        .fixed_decimals(3));
+                });
+            }
+            SlippageModel::Ticks { ticks, tick_size } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Ticks:");
+                    ui.add(egui::DragValue::new(ticks).range(0.0..=100.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("  Tick Size:");
+                    ui.add(egui::DragValue::new(tick_size).speed(0.01).range(0.0..=1000.0));
+                });
+            }
+            SlippageModel::BarRange { fraction } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Fraction:");
+                    ui.add(egui::DragValue::new(fraction).speed(0.01).range(0.0..=1.0));
+                });
+            }
+        }
+
         ui.horizontal(|ui| {
-            ui.label("Commission:");
-            ui.add(egui::DragValue::new(&mut state.commission)
-                .suffix("%")
-                .speed(0.001)
-                .range(0.0..=1.0)
-                .fixed_decimals(3));
+            ui.label("Execution:");
+            egui::ComboBox::from_id_salt("execution_timing")
+                .selected_text(format!("{:?}", state.execution_timing))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.execution_timing, ExecutionTiming::SameBarClose, "Same Bar Close");
+                    ui.selectable_value(&mut state.execution_timing, ExecutionTiming::NextBarOpen, "Next Bar Open");
+                    ui.selectable_value(&mut state.execution_timing, ExecutionTiming::NextBarClose, "Next Bar Close");
+                });
         });
+        ui.checkbox(&mut state.include_end_of_data_in_stats, "Count End-of-Data Closes in Trade Stats");
 
         ui.horizontal(|ui| {
-            ui.label("Slippage:");
-            ui.add(egui::DragValue::new(&mut state.slippage)
-                .suffix("%")
-                .speed(0.001)
-                .range(0.0..=1.0)
-                .fixed_decimals(3));
+            ui.label("Stop vs Target in One Bar:");
+            egui::ComboBox::from_id_salt("intrabar_assumption")
+                .selected_text(format!("{:?}", state.intrabar_assumption))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.intrabar_assumption, IntrabarAssumption::Pessimistic, "Stop First (Pessimistic)");
+                    ui.selectable_value(&mut state.intrabar_assumption, IntrabarAssumption::Optimistic, "Target First (Optimistic)");
+                });
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Leverage:");
+            ui.add(egui::DragValue::new(&mut state.margin.leverage).speed(0.1).range(1.0..=125.0).suffix("x"));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Maintenance Margin %:");
+            ui.add(egui::DragValue::new(&mut state.margin.maintenance_margin_percent).speed(0.05).range(0.0..=50.0));
+        });
+
+        ui.label("Carry Cost:");
+        egui::ComboBox::from_id_salt("carry")
+            .selected_text(format!("{:?}", state.carry))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut state.carry, CarryCostModel::None, "None");
+                ui.selectable_value(&mut state.carry, CarryCostModel::FundingRate { column: "funding_rate".to_string() }, "Funding Rate");
+                ui.selectable_value(&mut state.carry, CarryCostModel::BorrowRate { annual_percent: 5.0, bars_per_year: 252.0 }, "Borrow Rate");
+            });
+
+        match &mut state.carry {
+            CarryCostModel::None => {}
+            CarryCostModel::FundingRate { column } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Column:");
+                    ui.text_edit_singleline(column);
+                });
+            }
+            CarryCostModel::BorrowRate { annual_percent, bars_per_year } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Annual Rate:");
+                    ui.add(egui::DragValue::new(annual_percent).suffix("%").speed(0.1).range(0.0..=100.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("  Bars per Year:");
+                    ui.add(egui::DragValue::new(bars_per_year).speed(1.0).range(1.0..=1_000_000.0));
+                });
+            }
+        }
+
         ui.horizontal(|ui| {
             ui.label("Position Sizing:");
             egui::ComboBox::from_id_salt("position_sizing")
@@ -88,7 +204,8 @@ impl LeftPanel {
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut state.position_sizing, PositionSizing::Fixed { size: 100.0 }, "Fixed");
                     ui.selectable_value(&mut state.position_sizing, PositionSizing::Percent { percent: 1.0 }, "Percent");
-                    ui.selectable_value(&mut state.position_sizing, PositionSizing::Kelly { fraction: 0.25 }, "Kelly");
+                    ui.selectable_value(&mut state.position_sizing, PositionSizing::Kelly { fraction: 0.25, lookback: 50, warmup_percent: 1.0 }, "Kelly");
+                    ui.selectable_value(&mut state.position_sizing, PositionSizing::VolatilityTarget { risk_percent: 1.0, atr_period: 14, atr_multiplier: 2.0 }, "Volatility Target");
                 });
         });
 
@@ -106,11 +223,33 @@ impl LeftPanel {
                     ui.add(egui::DragValue::new(percent).suffix("%").range(0.1..=100.0));
                 });
             }
-            PositionSizing::Kelly { fraction } => {
+            PositionSizing::Kelly { fraction, lookback, warmup_percent } => {
                 ui.horizontal(|ui| {
                     ui.label("  Fraction:");
                     ui.add(egui::DragValue::new(fraction).range(0.1..=1.0).speed(0.05));
                 });
+                ui.horizontal(|ui| {
+                    ui.label("  Lookback Trades:");
+                    ui.add(egui::DragValue::new(lookback).range(10..=500));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("  Warmup:");
+                    ui.add(egui::DragValue::new(warmup_percent).suffix("%").range(0.1..=100.0));
+                });
+            }
+            PositionSizing::VolatilityTarget { risk_percent, atr_period, atr_multiplier } => {
+                ui.horizontal(|ui| {
+                    ui.label("  Risk:");
+                    ui.add(egui::DragValue::new(risk_percent).suffix("%").range(0.1..=10.0).speed(0.1));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("  ATR Period:");
+                    ui.add(egui::DragValue::new(atr_period).range(5..=50));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("  ATR Multiplier:");
+                    ui.add(egui::DragValue::new(atr_multiplier).range(0.5..=10.0).speed(0.1));
+                });
             }
         }
 
@@ -118,6 +257,7 @@ impl LeftPanel {
             ui.label("Max Positions:");
             ui.add(egui::DragValue::new(&mut state.max_positions).range(1..=20));
         });
+        ui.checkbox(&mut state.allow_hedging, "Allow Hedging (long and short at once)");
 
         ui.label("Stop Loss:");
         egui::ComboBox::from_id_salt("stop_loss")
@@ -126,18 +266,23 @@ impl LeftPanel {
                 ui.selectable_value(&mut state.stop_loss, StopLossConfig::None, "None");
                 ui.selectable_value(&mut state.stop_loss, StopLossConfig::FixedPercent { percent: 2.0 }, "Fixed Percent");
                 ui.selectable_value(&mut state.stop_loss, StopLossConfig::ATR { multiplier: 2.0, period: 14 }, "ATR");
+                ui.selectable_value(&mut state.stop_loss, StopLossConfig::TrailingPercent { percent: 2.0 }, "Trailing Percent");
+                ui.selectable_value(&mut state.stop_loss, StopLossConfig::TrailingATR { multiplier: 2.0, period: 14 }, "Trailing ATR");
+                ui.selectable_value(&mut state.stop_loss, StopLossConfig::Chandelier { multiplier: 3.0, period: 22 }, "Chandelier");
             });
 
         // Show SL parameters based on selected method
         match &mut state.stop_loss {
             StopLossConfig::None => {}
-            StopLossConfig::FixedPercent { percent } => {
+            StopLossConfig::FixedPercent { percent } | StopLossConfig::TrailingPercent { percent } => {
                 ui.horizontal(|ui| {
                     ui.label("  Percent:");
                     ui.add(egui::DragValue::new(percent).suffix("%").range(0.1..=50.0));
                 });
             }
-            StopLossConfig::ATR { multiplier, period } => {
+            StopLossConfig::ATR { multiplier, period }
+            | StopLossConfig::TrailingATR { multiplier, period }
+            | StopLossConfig::Chandelier { multiplier, period } => {
                 ui.horizontal(|ui| {
                     ui.label("  Multiplier:");
                     ui.add(egui::DragValue::new(multiplier).range(0.5..=10.0).speed(0.1));
@@ -149,6 +294,22 @@ impl LeftPanel {
             }
         }
 
+        ui.horizontal(|ui| {
+            let mut enabled = state.breakeven_after_r.is_some();
+            ui.checkbox(&mut enabled, "Breakeven after");
+            let mut r = state.breakeven_after_r.unwrap_or(1.0);
+            ui.add_enabled(enabled, egui::DragValue::new(&mut r).suffix(" R").range(0.1..=10.0).speed(0.1));
+            state.breakeven_after_r = enabled.then_some(r);
+        });
+
+        ui.horizontal(|ui| {
+            let mut enabled = state.max_bars_in_trade.is_some();
+            ui.checkbox(&mut enabled, "Max bars in trade");
+            let mut bars = state.max_bars_in_trade.unwrap_or(50);
+            ui.add_enabled(enabled, egui::DragValue::new(&mut bars).range(1..=1000));
+            state.max_bars_in_trade = enabled.then_some(bars);
+        });
+
         ui.label("Take Profit:");
         egui::ComboBox::from_id_salt("take_profit")
             .selected_text(format!("{:?}", state.take_profit))
@@ -156,6 +317,16 @@ impl LeftPanel {
                 ui.selectable_value(&mut state.take_profit, TakeProfitConfig::None, "None");
                 ui.selectable_value(&mut state.take_profit, TakeProfitConfig::FixedPercent { percent: 5.0 }, "Fixed Percent");
                 ui.selectable_value(&mut state.take_profit, TakeProfitConfig::RiskReward { ratio: 2.0 }, "Risk/Reward");
+                ui.selectable_value(
+                    &mut state.take_profit,
+                    TakeProfitConfig::Ladder {
+                        levels: vec![
+                            ScaleOut { r_multiple: 1.0, percent: 50.0 },
+                            ScaleOut { r_multiple: 2.0, percent: 25.0 },
+                        ],
+                    },
+                    "Scale-Out Ladder",
+                );
             });
 
         // Show TP parameters based on selected method
@@ -173,6 +344,25 @@ impl LeftPanel {
                     ui.add(egui::DragValue::new(ratio).range(0.5..=10.0).speed(0.1));
                 });
             }
+            TakeProfitConfig::Ladder { levels } => {
+                for level in levels.iter_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label("  Close");
+                        ui.add(egui::DragValue::new(&mut level.percent).suffix("%").range(1.0..=100.0));
+                        ui.label("at");
+                        ui.add(egui::DragValue::new(&mut level.r_multiple).suffix(" R").range(0.1..=10.0).speed(0.1));
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("+").clicked() {
+                        let r_multiple = levels.last().map_or(1.0, |l| l.r_multiple + 1.0);
+                        levels.push(ScaleOut { r_multiple, percent: 25.0 });
+                    }
+                    if ui.button("-").clicked() && levels.len() > 1 {
+                        levels.pop();
+                    }
+                });
+            }
         }
     }
 
@@ -209,10 +399,135 @@ impl LeftPanel {
             ui.add(egui::DragValue::new(&mut state.max_tree_depth).range(3..=20));
         });
 
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.entry_orders, "Entry Orders")
+                .on_hover_text("Let strategies enter with limit and stop orders as well as at market");
+            ui.add_enabled(state.entry_orders, egui::DragValue::new(&mut state.entry_order_atr_period).range(1..=200))
+                .on_hover_text("ATR period order offsets are measured in");
+        });
+
         ui.horizontal(|ui| {
             ui.label("Tournament Size:");
             ui.add(egui::DragValue::new(&mut state.tournament_size).range(2..=20));
         });
+
+        ui.horizontal(|ui| {
+            ui.label("Selection:");
+            let method = &mut state.selection_method;
+            egui::ComboBox::from_id_salt("selection_method")
+                .selected_text(match method {
+                    SelectionMethod::Tournament => "Tournament",
+                    SelectionMethod::Roulette => "Roulette",
+                    SelectionMethod::Rank => "Rank",
+                    SelectionMethod::StochasticUniversal => "Stochastic Universal",
+                    SelectionMethod::Lexicase { .. } => "Lexicase",
+                    SelectionMethod::Boltzmann { .. } => "Boltzmann",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(method, SelectionMethod::Tournament, "Tournament");
+                    ui.selectable_value(method, SelectionMethod::Roulette, "Roulette");
+                    ui.selectable_value(method, SelectionMethod::Rank, "Rank");
+                    ui.selectable_value(method, SelectionMethod::StochasticUniversal, "Stochastic Universal");
+                    let selected = matches!(method, SelectionMethod::Lexicase { .. });
+                    if ui.selectable_label(selected, "Lexicase").clicked() && !selected {
+                        *method = SelectionMethod::Lexicase { folds: 5 };
+                    }
+                    let selected = matches!(method, SelectionMethod::Boltzmann { .. });
+                    if ui.selectable_label(selected, "Boltzmann").clicked() && !selected {
+                        *method = SelectionMethod::Boltzmann { temperature: 1.0 };
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Operators:");
+            egui::ComboBox::from_id_salt("variation_operators")
+                .selected_text(format!("{:?}", state.operators))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.operators, VariationOperators::Positional, "Positional")
+                        .on_hover_text("Genome crossover and per-gene mutation");
+                    ui.selectable_value(&mut state.operators, VariationOperators::Tree, "Tree")
+                        .on_hover_text("Subtree crossover and point, hoist, shrink or parameter mutation");
+                });
+        });
+
+        match &mut state.selection_method {
+            SelectionMethod::Lexicase { folds } => {
+                ui.horizontal(|ui| {
+                    ui.label("Lexicase Folds:");
+                    ui.add(egui::DragValue::new(folds).range(2..=20))
+                        .on_hover_text("Consecutive slices of the data whose returns candidates are filtered by");
+                });
+            }
+            SelectionMethod::Boltzmann { temperature } => {
+                ui.horizontal(|ui| {
+                    ui.label("Temperature:");
+                    ui.add(egui::DragValue::new(temperature).range(0.01..=100.0).speed(0.05))
+                        .on_hover_text("Lower values favour the fittest strategies more strongly");
+                });
+            }
+            _ => {}
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Workers:");
+            ui.add(egui::DragValue::new(&mut state.workers).range(0..=256))
+                .on_hover_text("Threads evaluating strategies; 0 uses one per core");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Checkpoint Every:");
+            ui.add(egui::DragValue::new(&mut state.checkpoint_every).range(0..=1000).suffix(" gens"))
+                .on_hover_text("Save progress so the run can be resumed; 0 disables it");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Checkpoint File:");
+            ui.text_edit_singleline(&mut state.checkpoint_path)
+                .on_hover_text("Where checkpoints are saved and resumed from; give concurrent runs their own file");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Islands:");
+            ui.add(egui::DragValue::new(&mut state.num_islands).range(1..=32))
+                .on_hover_text("Populations evolving in parallel, each of the population size");
+        });
+
+        ui.add_enabled_ui(state.num_islands > 1, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Migrate Every:");
+                ui.add(egui::DragValue::new(&mut state.migration_interval).range(0..=1000).suffix(" gens"))
+                    .on_hover_text("Send each island's best strategies to the next island; 0 keeps them isolated");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Migrants:");
+                ui.add(egui::DragValue::new(&mut state.migrants).range(1..=100));
+            });
+        });
+
+        ui.checkbox(&mut state.adaptive_rates, "Adaptive Rates")
+            .on_hover_text("Raise mutation and lower crossover while the search stalls or loses diversity");
+
+        ui.add_enabled_ui(state.adaptive_rates, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Stagnation After:");
+                ui.add(egui::DragValue::new(&mut state.stagnation_generations).range(1..=1000).suffix(" gens"))
+                    .on_hover_text("Generations without a better strategy before the rates adapt");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Restart After:");
+                ui.add(egui::DragValue::new(&mut state.restart_after).range(0..=1000).suffix(" gens"))
+                    .on_hover_text("Replace all but the elites with random strategies; 0 never restarts");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Stop After:");
+                ui.add(egui::DragValue::new(&mut state.stop_after).range(0..=1000).suffix(" gens"))
+                    .on_hover_text("End the run early without improvement; 0 runs every generation");
+            });
+        });
     }
 
     fn show_backtesting_config(ui: &mut egui::Ui, state: &mut AppState) {
@@ -241,6 +556,27 @@ impl LeftPanel {
                 ui.add(egui::DragValue::new(&mut state.num_folds).range(2..=10));
             });
         }
+
+        ui.separator();
+        ui.label("Early Abort:");
+
+        ui.horizontal(|ui| {
+            let mut enabled = state.budget.max_drawdown_percent.is_some();
+            ui.checkbox(&mut enabled, "Max drawdown");
+            let mut percent = state.budget.max_drawdown_percent.unwrap_or(50.0);
+            ui.add_enabled(enabled, egui::DragValue::new(&mut percent).suffix("%").range(1.0..=100.0));
+            state.budget.max_drawdown_percent = enabled.then_some(percent);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Min Trades:");
+            ui.add(egui::DragValue::new(&mut state.budget.min_trades).range(0..=1000));
+            ui.label("by");
+            ui.add(egui::Slider::new(&mut state.budget.min_trades_checkpoint, 0.1..=1.0).step_by(0.05))
+                .on_hover_text("Fraction of the bars after which the trade count is checked");
+        });
+
+        ui.checkbox(&mut state.budget.abort_on_constant_signal, "Skip strategies with a constant signal");
     }
 
     fn show_control_buttons(ui: &mut egui::Ui, state: &mut AppState) {
@@ -255,6 +591,13 @@ impl LeftPanel {
                 // The actual start will be handled in app.rs
             }
 
+            let can_resume = can_run && std::path::Path::new(&state.checkpoint_path).is_file();
+            let resume_button = ui.add_enabled(can_resume, egui::Button::new("⏵ Resume from Checkpoint"));
+            if resume_button.clicked() {
+                state.status_message = "Resuming evolution...".to_string();
+                // The actual resume will be handled in app.rs
+            }
+
             if let Some(error) = validation_error {
                 ui.colored_label(egui::Color32::RED, error);
             }
@@ -280,6 +623,7 @@ impl LeftPanel {
             return Err("Invalid initial capital".to_string());
         }
 
-        Ok(())
+        // E.g. a maintenance margin at or above the initial margin at this leverage
+        ConfigBridge::validate(state).map_err(|e| e.to_string())
     }
 }
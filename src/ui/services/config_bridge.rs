@@ -1,12 +1,22 @@
 use crate::config::backtesting::BacktestingConfig;
 use crate::config::evolution::EvolutionConfig;
 use crate::config::trade_management::TradeManagementConfig;
+use crate::config::traits::ConfigSection;
 use crate::engines::generation::pareto::ObjectiveConfig;
+use crate::error::TradebiasError;
 use crate::ui::state::AppState;
 
 pub struct ConfigBridge;
 
 impl ConfigBridge {
+    /// Validate every config a run is built from, so combinations the engine
+    /// would misbehave on never reach it
+    pub fn validate(state: &AppState) -> Result<(), TradebiasError> {
+        Self::to_evolution_config(state).validate()?;
+        Self::to_backtesting_config(state).validate()?;
+        Self::to_trade_management_config(state).validate()
+    }
+
     /// Convert AppState to BacktestingConfig
     pub fn to_backtesting_config(state: &AppState) -> BacktestingConfig {
         BacktestingConfig {
@@ -14,24 +24,41 @@ impl ConfigBridge {
             train_test_split: state.train_test_split,
             num_folds: state.num_folds,
             initial_capital: state.initial_capital,
-            commission: state.commission,
-            slippage: state.slippage,
+            commission: state.commission.clone(),
+            slippage: state.slippage.clone(),
+            execution_timing: state.execution_timing,
+            include_end_of_data_in_stats: state.include_end_of_data_in_stats,
+            margin: state.margin.clone(),
+            carry: state.carry.clone(),
+            intrabar_assumption: state.intrabar_assumption,
+            budget: state.budget.clone(),
         }
     }
 
     /// Convert AppState to EvolutionConfig
     pub fn to_evolution_config(state: &AppState) -> EvolutionConfig {
-        use crate::config::evolution::SelectionMethod;
-
         EvolutionConfig {
             population_size: state.population_size,
             num_generations: state.num_generations,
             mutation_rate: state.mutation_rate,
             crossover_rate: state.crossover_rate,
-            selection_method: SelectionMethod::Tournament, // Default to tournament
+            selection_method: state.selection_method,
+            operators: state.operators,
             elitism_count: state.elitism_count,
             max_tree_depth: state.max_tree_depth,
+            entry_orders: state.entry_orders,
+            entry_order_atr_period: state.entry_order_atr_period,
             tournament_size: state.tournament_size,
+            workers: state.workers,
+            checkpoint_every: state.checkpoint_every,
+            checkpoint_path: state.checkpoint_path.clone().into(),
+            num_islands: state.num_islands,
+            migration_interval: state.migration_interval,
+            migrants: state.migrants,
+            adaptive_rates: state.adaptive_rates,
+            stagnation_generations: state.stagnation_generations,
+            restart_after: state.restart_after,
+            stop_after: state.stop_after,
         }
     }
 
@@ -42,6 +69,9 @@ impl ConfigBridge {
             take_profit: state.take_profit.clone(),
             position_sizing: state.position_sizing.clone(),
             max_positions: state.max_positions,
+            allow_hedging: state.allow_hedging,
+            breakeven_after_r: state.breakeven_after_r,
+            max_bars_in_trade: state.max_bars_in_trade,
         }
     }
 
@@ -6,11 +6,15 @@ use crate::engines::generation::evolution_engine::{
     EvolutionConfig as EngineEvolutionConfig,
     ProgressCallback,
 };
+use crate::engines::generation::adaptation::{AdaptationConfig, AdaptedRates};
+use crate::engines::generation::checkpoint::CheckpointConfig;
+use crate::engines::generation::islands::{IslandModel, IslandSettings, MigrationTopology};
 use crate::engines::generation::hall_of_fame::EliteStrategy;
 use crate::engines::generation::semantic_mapper::SemanticMapper;
 use crate::engines::generation::pareto::ObjectiveConfig;
 use crate::engines::evaluation::Backtester;
 use crate::data::IndicatorCache;
+use crate::error::TradebiasError;
 use crate::functions::registry::FunctionRegistry;
 use crate::ui::state::StrategyDisplay;
 use polars::prelude::*;
@@ -26,6 +30,8 @@ pub struct ProgressUpdate {
     pub best_fitness: f64,
     pub hall_size: usize,
     pub status: String,
+    /// Rates adapted after the generation, when the run adapts them
+    pub rates: Option<AdaptedRates>,
 }
 
 /// Result from evolution run
@@ -48,6 +54,7 @@ impl ProgressCallback for EvolutionProgressCallback {
             best_fitness: 0.0,
             hall_size: 0,
             status: format!("Generation {}/{} starting...", generation + 1, self.total_generations),
+            rates: None,
         });
 
         // Check for cancellation
@@ -64,6 +71,7 @@ impl ProgressCallback for EvolutionProgressCallback {
             best_fitness,
             hall_size: hall_of_fame_size,
             status: format!("Generation {}/{} - Best: {:.2}", generation + 1, self.total_generations, best_fitness),
+            rates: None,
         });
     }
 
@@ -76,9 +84,28 @@ impl ProgressCallback for EvolutionProgressCallback {
                 best_fitness: 0.0,
                 hall_size: 0,
                 status: format!("Evaluating strategies: {}/{}", strategy_num, total),
+                rates: None,
             });
         }
     }
+
+    fn on_rates_adapted(&mut self, generation: usize, rates: &AdaptedRates) {
+        let _ = self.progress_tx.send(ProgressUpdate {
+            generation: generation + 1,
+            total_generations: self.total_generations,
+            best_fitness: 0.0,
+            hall_size: 0,
+            status: format!(
+                "Generation {}/{} - Mutation: {:.3}, Crossover: {:.3}, Diversity: {:.0}%",
+                generation + 1,
+                self.total_generations,
+                rates.mutation_rate,
+                rates.crossover_rate,
+                rates.diversity * 100.0
+            ),
+            rates: Some(*rates),
+        });
+    }
 }
 
 pub struct EvolutionRunner {
@@ -97,6 +124,45 @@ impl EvolutionRunner {
         selected_indicators: Vec<String>,
         objective_configs: Vec<ObjectiveConfig>,
     ) -> Self {
+        Self::spawn(move |progress_tx, cancel_flag| {
+            Self::run_evolution(
+                data,
+                evolution_config,
+                backtesting_config,
+                trade_management_config,
+                selected_indicators,
+                objective_configs,
+                progress_tx,
+                cancel_flag,
+            )
+        })
+    }
+
+    /// Continue the run checkpointed at `evolution_config.checkpoint_path` in a
+    /// background thread; its settings come from the checkpoint, while the
+    /// backtest and strategy mapping are rebuilt from the configs given
+    pub fn resume(
+        data: DataFrame,
+        evolution_config: EvolutionConfig,
+        backtesting_config: BacktestingConfig,
+        trade_management_config: TradeManagementConfig,
+    ) -> Self {
+        Self::spawn(move |progress_tx, cancel_flag| {
+            Self::resume_evolution(
+                data,
+                evolution_config,
+                backtesting_config,
+                trade_management_config,
+                progress_tx,
+                cancel_flag,
+            )
+        })
+    }
+
+    fn spawn<F>(run: F) -> Self
+    where
+        F: FnOnce(Sender<ProgressUpdate>, Arc<Mutex<bool>>) -> EvolutionResult + Send + 'static,
+    {
         let (progress_tx, progress_rx) = channel();
         let cancel_flag = Arc::new(Mutex::new(false));
         let cancel_flag_clone = Arc::clone(&cancel_flag);
@@ -106,18 +172,7 @@ impl EvolutionRunner {
         // and expression building with max_tree_depth up to 12
         let handle = thread::Builder::new()
             .stack_size(16 * 1024 * 1024) // 16MB stack
-            .spawn(move || {
-                Self::run_evolution(
-                    data,
-                    evolution_config,
-                    backtesting_config,
-                    trade_management_config,
-                    selected_indicators,
-                    objective_configs,
-                    progress_tx,
-                    cancel_flag_clone,
-                )
-            })
+            .spawn(move || run(progress_tx, cancel_flag_clone))
             .expect("Failed to spawn evolution thread");
 
         Self {
@@ -171,7 +226,7 @@ impl EvolutionRunner {
         data: DataFrame,
         evolution_config: EvolutionConfig,
         backtesting_config: BacktestingConfig,
-        _trade_management_config: TradeManagementConfig,
+        trade_management_config: TradeManagementConfig,
         _selected_indicators: Vec<String>,
         objective_configs: Vec<ObjectiveConfig>,
         progress_tx: Sender<ProgressUpdate>,
@@ -182,45 +237,43 @@ impl EvolutionRunner {
         println!("  Generations: {}", evolution_config.num_generations);
         println!("  Data rows: {}", data.height());
 
-        // Create components needed for evolution
-        let registry = Arc::new(FunctionRegistry::new());
-        let cache = Arc::new(IndicatorCache::new(1000));
-
-        // Create backtester
-        let backtester = Backtester::new(
-            Arc::clone(&registry),
-            Arc::clone(&cache),
-            backtesting_config.initial_capital,
-        );
-
-        // Create semantic mapper
-        let semantic_mapper = SemanticMapper::new(
-            Arc::clone(&registry),
-            evolution_config.max_tree_depth,
-        );
+        let (cache, backtester, semantic_mapper) =
+            Self::components(&evolution_config, backtesting_config, trade_management_config);
 
         // Convert UI config to engine config
         let engine_config = EngineEvolutionConfig {
             population_size: evolution_config.population_size,
             generations: evolution_config.num_generations,
-            genome_length: 100, // Default genome length
-            gene_range: 0..1000, // Default gene range
             mutation_rate: evolution_config.mutation_rate,
             crossover_rate: evolution_config.crossover_rate,
             elitism_rate: evolution_config.elitism_count as f64 / evolution_config.population_size as f64,
             tournament_size: evolution_config.tournament_size,
-            hall_of_fame_size: 10, // Keep top 10 strategies
+            selection: evolution_config.selection_method,
+            operators: evolution_config.operators,
 
             // Pareto multi-objective optimization (enabled by default)
             objective_configs: objective_configs.clone(),
             use_pareto: true,
 
-            // Legacy single-objective fields (for backward compatibility)
-            fitness_objectives: vec!["return_pct".to_string()],
-            fitness_weights: vec![1.0],
-
-            min_fitness_threshold: 0.0,
-            seed: None, // Random seed
+            workers: evolution_config.workers,
+            checkpoint: (evolution_config.checkpoint_every > 0).then(|| CheckpointConfig {
+                path: evolution_config.checkpoint_path.clone(),
+                every: evolution_config.checkpoint_every,
+            }),
+            islands: (evolution_config.num_islands > 1).then(|| IslandModel {
+                islands: vec![IslandSettings::default(); evolution_config.num_islands],
+                migration_interval: evolution_config.migration_interval,
+                migrants: evolution_config.migrants,
+                topology: MigrationTopology::Ring,
+            }),
+            adaptation: evolution_config.adaptive_rates.then(|| AdaptationConfig {
+                patience: evolution_config.stagnation_generations,
+                restart_after: (evolution_config.restart_after > 0).then_some(evolution_config.restart_after),
+                stop_after: (evolution_config.stop_after > 0).then_some(evolution_config.stop_after),
+                ..AdaptationConfig::default()
+            }),
+            // Genome shape, a random seed and a hall of fame of 10
+            ..EngineEvolutionConfig::default()
         };
 
         // Create evolution engine
@@ -235,7 +288,94 @@ impl EvolutionRunner {
         };
 
         // Run evolution
-        match engine.run(&data, callback) {
+        let outcome = engine.run(&data, callback);
+        Self::report(outcome, &cache, &progress_tx, total_generations)
+    }
+
+    /// Resume a checkpointed evolution (called in background thread)
+    fn resume_evolution(
+        data: DataFrame,
+        evolution_config: EvolutionConfig,
+        backtesting_config: BacktestingConfig,
+        trade_management_config: TradeManagementConfig,
+        progress_tx: Sender<ProgressUpdate>,
+        cancel_flag: Arc<Mutex<bool>>,
+    ) -> EvolutionResult {
+        println!("🔁 Resuming evolution from {}", evolution_config.checkpoint_path.display());
+
+        let (cache, backtester, semantic_mapper) =
+            Self::components(&evolution_config, backtesting_config, trade_management_config);
+
+        let mut engine = match EvolutionEngine::resume(&evolution_config.checkpoint_path, backtester, semantic_mapper) {
+            Ok(engine) => engine,
+            Err(e) => return Self::report(Err(e), &cache, &progress_tx, evolution_config.num_generations),
+        };
+
+        // The run continues with the checkpoint's settings, not the UI's
+        let total_generations = engine.config().generations;
+        let callback = EvolutionProgressCallback {
+            progress_tx: progress_tx.clone(),
+            cancel_flag,
+            total_generations,
+        };
+
+        let outcome = engine.run(&data, callback);
+        Self::report(outcome, &cache, &progress_tx, total_generations)
+    }
+
+    /// Build the backtester and semantic mapper a run evaluates strategies with
+    fn components(
+        evolution_config: &EvolutionConfig,
+        backtesting_config: BacktestingConfig,
+        trade_management_config: TradeManagementConfig,
+    ) -> (Arc<IndicatorCache>, Backtester, SemanticMapper) {
+        // Create components needed for evolution
+        let registry = Arc::new(FunctionRegistry::new());
+        let cache = Arc::new(IndicatorCache::new(1000));
+
+        // Create backtester
+        let backtester = Backtester::new(
+            Arc::clone(&registry),
+            Arc::clone(&cache),
+            backtesting_config.initial_capital,
+        )
+        .with_trade_management(trade_management_config)
+        .with_costs(backtesting_config.commission.clone(), backtesting_config.slippage.clone())
+        .with_execution_timing(backtesting_config.execution_timing)
+        .with_margin(backtesting_config.margin.clone())
+        .with_carry(backtesting_config.carry.clone())
+        .with_intrabar_assumption(backtesting_config.intrabar_assumption)
+        .with_end_of_data_in_stats(backtesting_config.include_end_of_data_in_stats)
+        .with_budget(backtesting_config.budget.clone());
+
+        // Create semantic mapper
+        let mut semantic_mapper = SemanticMapper::new(
+            Arc::clone(&registry),
+            evolution_config.max_tree_depth,
+        );
+        if evolution_config.entry_orders {
+            semantic_mapper = semantic_mapper.with_entry_orders(evolution_config.entry_order_atr_period);
+        }
+
+        (cache, backtester, semantic_mapper)
+    }
+
+    /// Send the final progress update and convert the outcome for the UI
+    fn report(
+        outcome: Result<Vec<EliteStrategy>, TradebiasError>,
+        cache: &IndicatorCache,
+        progress_tx: &Sender<ProgressUpdate>,
+        total_generations: usize,
+    ) -> EvolutionResult {
+        let stats = cache.stats();
+        println!(
+            "  Indicator cache: {} hits, {} misses ({:.0}% hit rate)",
+            stats.hits,
+            stats.misses,
+            stats.hit_rate() * 100.0
+        );
+
+        match outcome {
             Ok(elite_strategies) => {
                 // Convert EliteStrategy to StrategyDisplay
                 let displays: Vec<StrategyDisplay> = elite_strategies
@@ -250,6 +390,7 @@ impl EvolutionRunner {
                     best_fitness: displays.first().map(|d| d.fitness).unwrap_or(0.0),
                     hall_size: displays.len(),
                     status: format!("Complete! Found {} strategies", displays.len()),
+                    rates: None,
                 });
 
                 Ok(displays)
@@ -261,6 +402,7 @@ impl EvolutionRunner {
                     best_fitness: 0.0,
                     hall_size: 0,
                     status: format!("Error: {}", e),
+                    rates: None,
                 });
                 Err(format!("Evolution failed: {}", e))
             }
@@ -1,6 +1,11 @@
-use crate::config::backtesting::ValidationMethod;
+use crate::config::backtesting::{
+    CarryCostModel, CommissionModel, EvaluationBudget, ExecutionTiming, IntrabarAssumption, MarginConfig, SlippageModel,
+    ValidationMethod,
+};
+use crate::config::evolution::{SelectionMethod, VariationOperators};
 use crate::config::trade_management::{StopLossConfig, TakeProfitConfig, PositionSizing};
 use crate::data::DataPreview;
+use crate::engines::generation::adaptation::AdaptedRates;
 use crate::engines::generation::pareto::OptimizationDirection;
 use polars::prelude::*;
 use std::collections::{HashSet, HashMap};
@@ -23,12 +28,20 @@ pub struct AppState {
 
     // Trade Management Configuration
     pub initial_capital: f64,
-    pub commission: f64,
-    pub slippage: f64,
+    pub commission: CommissionModel,
+    pub slippage: SlippageModel,
+    pub execution_timing: ExecutionTiming,
+    pub include_end_of_data_in_stats: bool,
+    pub margin: MarginConfig,
+    pub carry: CarryCostModel,
+    pub intrabar_assumption: IntrabarAssumption,
     pub stop_loss: StopLossConfig,
     pub take_profit: TakeProfitConfig,
     pub position_sizing: PositionSizing,
     pub max_positions: usize,
+    pub allow_hedging: bool,
+    pub breakeven_after_r: Option<f64>,
+    pub max_bars_in_trade: Option<usize>,
 
     // Evolution Configuration
     pub population_size: usize,
@@ -37,18 +50,35 @@ pub struct AppState {
     pub crossover_rate: f64,
     pub elitism_count: usize,
     pub max_tree_depth: usize,
+    pub entry_orders: bool,
+    pub entry_order_atr_period: usize,
     pub tournament_size: usize,
+    pub selection_method: SelectionMethod,
+    pub operators: VariationOperators,
+    pub workers: usize,
+    pub checkpoint_every: usize,
+    pub checkpoint_path: String,
+    pub num_islands: usize,
+    pub migration_interval: usize,
+    pub migrants: usize,
+    pub adaptive_rates: bool,
+    pub stagnation_generations: usize,
+    pub restart_after: usize,
+    pub stop_after: usize,
 
     // Backtesting Configuration
     pub validation_method: ValidationMethod,
     pub train_test_split: f64,
     pub num_folds: usize,
+    pub budget: EvaluationBudget,
 
     // Execution State
     pub is_running: bool,
     pub current_generation: usize,
     pub progress_percentage: f32,
     pub status_message: String,
+    /// Rates each generation of an adaptive run was bred with, for charting
+    pub rate_history: Vec<AdaptedRates>,
 
     // Results
     pub hall_of_fame: Vec<StrategyDisplay>,
@@ -124,12 +154,20 @@ impl Default for AppState {
 
             // Trade Management Configuration
             initial_capital: 10000.0,
-            commission: 0.001,      // 0.1%
-            slippage: 0.0005,       // 0.05%
+            commission: CommissionModel::Percent { percent: 0.1 },
+            slippage: SlippageModel::Percent { percent: 0.05 },
+            execution_timing: ExecutionTiming::SameBarClose,
+            include_end_of_data_in_stats: true,
+            margin: MarginConfig::default(),
+            carry: CarryCostModel::None,
+            intrabar_assumption: IntrabarAssumption::Pessimistic,
             stop_loss: StopLossConfig::None,
             take_profit: TakeProfitConfig::None,
             position_sizing: PositionSizing::Fixed { size: 100.0 },
             max_positions: 1,
+            allow_hedging: false,
+            breakeven_after_r: None,
+            max_bars_in_trade: None,
 
             // Evolution Configuration
             population_size: 500,
@@ -138,18 +176,34 @@ impl Default for AppState {
             crossover_rate: 0.85,
             elitism_count: 10,
             max_tree_depth: 12,
+            entry_orders: false,
+            entry_order_atr_period: 14,
             tournament_size: 7,
+            selection_method: SelectionMethod::Tournament,
+            operators: VariationOperators::Positional,
+            workers: 0,
+            checkpoint_every: 0,
+            checkpoint_path: "evolution.checkpoint".to_string(),
+            num_islands: 1,
+            migration_interval: 10,
+            migrants: 2,
+            adaptive_rates: false,
+            stagnation_generations: 5,
+            restart_after: 0,
+            stop_after: 0,
 
             // Backtesting Configuration
             validation_method: ValidationMethod::Simple,
             train_test_split: 0.7,
             num_folds: 5,
+            budget: EvaluationBudget::unlimited(),
 
             // Execution State
             is_running: false,
             current_generation: 0,
             progress_percentage: 0.0,
             status_message: "Ready".to_string(),
+            rate_history: Vec::new(),
 
             // Results
             hall_of_fame: Vec::new(),
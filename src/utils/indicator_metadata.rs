@@ -21,6 +21,7 @@ pub enum ScaleType {
     Index,              // Index-based (ADX, CCI)
 }
 
+#[derive(Clone)]
 pub struct MetadataRegistry {
     metadata: HashMap<String, IndicatorMetadata>,
 }
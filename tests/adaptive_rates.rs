@@ -0,0 +1,140 @@
+mod common;
+
+use common::{checkpoint_path, engine, evolve, fingerprint, parts, sample, Recorder};
+use tradebias::config::evolution;
+use tradebias::config::traits::ConfigSection;
+use tradebias::engines::generation::evolution_engine::{EvolutionConfig, EvolutionEngine};
+use tradebias::engines::generation::{AdaptationConfig, AdaptationState, AdaptedRates, CheckpointConfig};
+
+fn adaptation(restart_after: Option<usize>, stop_after: Option<usize>) -> AdaptationConfig {
+    AdaptationConfig { patience: 1, restart_after, stop_after, ..AdaptationConfig::default() }
+}
+
+/// Restarts keep a quarter of each population as elites
+fn config(adaptation: Option<AdaptationConfig>) -> EvolutionConfig {
+    EvolutionConfig {
+        population_size: 8,
+        generations: 8,
+        elitism_rate: 0.25,
+        seed: Some(13),
+        adaptation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_stagnation_raises_mutation_and_lowers_crossover() {
+    let config = AdaptationConfig { patience: 3, min_diversity: 0.0, ..AdaptationConfig::default() };
+    let mut state = AdaptationState::default();
+
+    let rates: Vec<AdaptedRates> = (0..12).map(|_| state.update(&config, 1.0, 1.0, 0.15, 0.85)).collect();
+
+    // The first generation improves on nothing; the rates move once three more pass without improvement
+    assert_eq!(rates[0].stagnant_generations, 0);
+    for stagnant in &rates[..3] {
+        assert_eq!((stagnant.mutation_rate, stagnant.crossover_rate), (0.15, 0.85));
+    }
+    for pair in rates[2..].windows(2) {
+        assert!(pair[1].mutation_rate >= pair[0].mutation_rate);
+        assert!(pair[1].crossover_rate <= pair[0].crossover_rate);
+    }
+    let last = rates[11];
+    assert_eq!((last.mutation_rate, last.crossover_rate), (0.6, 0.5));
+    assert_eq!(last.stagnant_generations, 11);
+
+    // Improving again brings the rates back step by step
+    let recovering: Vec<AdaptedRates> = (0..12).map(|i| state.update(&config, 2.0 + i as f64, 1.0, 0.15, 0.85)).collect();
+    assert!(recovering[0].mutation_rate < 0.6);
+    assert!(recovering[0].mutation_rate > 0.15);
+    assert_eq!((recovering[11].mutation_rate, recovering[11].crossover_rate), (0.15, 0.85));
+}
+
+#[test]
+fn test_low_diversity_adapts_rates_while_improving() {
+    let config = AdaptationConfig { patience: 100, min_diversity: 0.5, ..AdaptationConfig::default() };
+    let mut state = AdaptationState::default();
+
+    let diverse = state.update(&config, 1.0, 0.9, 0.15, 0.85);
+    let converged = state.update(&config, 2.0, 0.2, 0.15, 0.85);
+
+    assert_eq!(diverse.mutation_rate, 0.15);
+    assert!(converged.mutation_rate > 0.15);
+    assert!(converged.crossover_rate < 0.85);
+    assert_eq!(converged.stagnant_generations, 0);
+}
+
+#[test]
+fn test_run_reports_rate_trajectory_and_stops_when_stalled() {
+    let data = sample();
+
+    let (_, fixed) = evolve(&data, config(None));
+    assert!(fixed.rates.is_empty());
+
+    let (elites, stalled) = evolve(&data, config(Some(adaptation(None, Some(1)))));
+
+    assert!(!elites.is_empty());
+    // One report per generation run, ending at the first generation without improvement
+    let generations: Vec<usize> = stalled.rates.iter().map(|(generation, _)| *generation).collect();
+    assert_eq!(generations, (0..stalled.best.len()).collect::<Vec<_>>());
+    assert!(stalled.best.len() < 8);
+    assert_eq!(stalled.rates.last().unwrap().1.stagnant_generations, 1);
+    for (_, rates) in &stalled.rates {
+        assert!((0.0..=1.0).contains(&rates.diversity));
+        assert!(rates.mutation_rate >= 0.15 && rates.crossover_rate <= 0.85);
+    }
+}
+
+#[test]
+fn test_restart_brings_in_immigrants_and_keeps_the_hall_of_fame() {
+    let data = sample();
+
+    let (without, plain) = evolve(&data, config(Some(adaptation(None, None))));
+    let (with, restarted) = evolve(&data, config(Some(adaptation(Some(1), None))));
+
+    assert_eq!(restarted.best.len(), 8);
+    // Nothing changes before the first generation that could restart
+    assert_eq!(plain.best[..2], restarted.best[..2]);
+    assert_ne!(fingerprint(&without), fingerprint(&with));
+    // Restarts replace populations but never lose the best strategy found
+    let best_seen = restarted.best.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    assert!(with.iter().any(|elite| elite.fitness == best_seen));
+}
+
+#[test]
+fn test_adaptive_run_resumes_from_a_checkpoint() {
+    let data = sample();
+    let path = checkpoint_path("adaptive");
+
+    let mut with_checkpoint = config(Some(adaptation(Some(2), None)));
+    with_checkpoint.checkpoint = Some(CheckpointConfig { path: path.clone(), every: 5 });
+    let (uninterrupted, progress) = evolve(&data, with_checkpoint);
+
+    let (backtester, semantic_mapper) = parts();
+    let mut resumed = Recorder::default();
+    let elites = EvolutionEngine::resume(&path, backtester, semantic_mapper).unwrap().run(&data, &mut resumed).unwrap();
+
+    assert_eq!(fingerprint(&elites), fingerprint(&uninterrupted));
+    // The adaptation state is restored, so the rates carry on where they were
+    assert_eq!(resumed.rates, progress.rates[5..]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_invalid_adaptation_is_rejected() {
+    let data = sample();
+
+    for invalid in [
+        AdaptationConfig { step: 0.5, ..AdaptationConfig::default() },
+        AdaptationConfig { max_mutation_rate: 1.5, ..AdaptationConfig::default() },
+        AdaptationConfig { stop_after: Some(0), ..AdaptationConfig::default() },
+    ] {
+        assert!(engine(config(Some(invalid))).run(&data, &mut Recorder::default()).is_err());
+    }
+
+    let ui_config = evolution::EvolutionConfig {
+        adaptive_rates: true,
+        stagnation_generations: 0,
+        ..evolution::EvolutionConfig::default()
+    };
+    assert!(ui_config.validate().is_err());
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use tradebias::config::trade_management::{StopLossConfig, TradeManagementConfig};
+use tradebias::data::{CsvConnector, IndicatorCache};
+use tradebias::engines::evaluation::Backtester;
+use tradebias::engines::generation::ast::{StrategyAST, StrategyMetadata};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, EntryOrder, OrderKind, PriceReference, StrategyResult, Value};
+
+fn call(function: &str, args: Vec<AstNode>) -> AstNode {
+    AstNode::Call { function: function.to_string(), args: args.into_iter().map(Box::new).collect() }
+}
+
+fn indicator(function: &str, period: i64) -> AstNode {
+    call(function, vec![call("Close", vec![]), AstNode::Const(Value::Integer(period))])
+}
+
+fn float(value: f64) -> AstNode {
+    AstNode::Const(Value::Float(value))
+}
+
+fn strategy(root: AstNode) -> StrategyAST {
+    StrategyAST { root: Box::new(root), metadata: StrategyMetadata::default() }
+}
+
+fn rule(action: AstNode) -> StrategyAST {
+    strategy(AstNode::Rule { condition: Box::new(AstNode::Const(Value::Bool(true))), action: Box::new(action) })
+}
+
+fn rsi_rules(entry_order: Option<EntryOrder>) -> StrategyAST {
+    strategy(AstNode::Strategy {
+        long_entry: Some(Box::new(call("lt_scalar", vec![indicator("RSI", 14), float(35.0)]))),
+        long_exit: Some(Box::new(call("gt_scalar", vec![indicator("RSI", 14), float(60.0)]))),
+        short_entry: Some(Box::new(call("gt_scalar", vec![indicator("RSI", 14), float(65.0)]))),
+        short_exit: None,
+        entry_order,
+    })
+}
+
+fn population() -> Vec<StrategyAST> {
+    let limit = EntryOrder {
+        kind: OrderKind::Limit,
+        reference: PriceReference::Close,
+        atr_multiple: 0.5,
+        atr_period: 14,
+        expiry_bars: 3,
+    };
+    vec![
+        rule(call("Subtract", vec![indicator("EMA", 10), indicator("EMA", 50)])),
+        rsi_rules(None),
+        rsi_rules(Some(limit)),
+        rule(call("Subtract", vec![indicator("SMA", 20), indicator("EMA", 50)])),
+        rule(call("Subtract", vec![indicator("RSI", 14), float(50.0)])),
+    ]
+}
+
+fn backtester(trade_management: TradeManagementConfig, capacity: usize) -> Backtester {
+    Backtester::new(Arc::new(FunctionRegistry::new()), Arc::new(IndicatorCache::new(capacity)), 10000.0)
+        .with_trade_management(trade_management)
+}
+
+fn assert_same(batch: &StrategyResult, alone: &StrategyResult, context: &str) {
+    assert_eq!(batch.trades, alone.trades, "trades differ: {}", context);
+    assert_eq!(batch.ledger, alone.ledger, "ledger differs: {}", context);
+    assert_eq!(batch.order_events, alone.order_events, "order events differ: {}", context);
+    for (name, value) in &batch.metrics {
+        let other = alone.metrics[name];
+        assert!(*value == other || (value.is_nan() && other.is_nan()), "{} differs: {}", name, context);
+    }
+}
+
+#[test]
+fn test_batch_matches_running_each_strategy_alone() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let managements = [
+        TradeManagementConfig::signal_only(),
+        TradeManagementConfig {
+            stop_loss: StopLossConfig::ATR { multiplier: 2.0, period: 14 },
+            ..TradeManagementConfig::signal_only()
+        },
+    ];
+
+    for trade_management in managements {
+        let population = population();
+        let results = backtester(trade_management.clone(), 100).run_batch(&population, &data).unwrap();
+
+        assert_eq!(results.len(), population.len());
+        for (i, (ast, result)) in population.iter().zip(&results).enumerate() {
+            let alone = backtester(trade_management.clone(), 0).run(ast, &data).unwrap();
+            let context = format!("strategy {} with {:?}", i, trade_management.stop_loss);
+            assert_same(result, &alone, &context);
+            assert_eq!(format!("{:?}", result.ast), format!("{:?}", ast.root), "{}", context);
+        }
+    }
+}
+
+#[test]
+fn test_batch_computes_shared_indicators_once() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let backtester = backtester(TradeManagementConfig::signal_only(), 100);
+
+    backtester.run_batch(&population(), &data).unwrap();
+
+    // EMA(10), EMA(50), RSI(14) and SMA(20), each evaluated once for the batch
+    let stats = backtester.cache().stats();
+    assert_eq!((stats.misses, stats.hits), (4, 0));
+}
+
+#[test]
+fn test_empty_batch() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+
+    let results = backtester(TradeManagementConfig::signal_only(), 100).run_batch(&[], &data).unwrap();
+
+    assert!(results.is_empty());
+}
+
+#[test]
+fn test_unknown_function_fails_the_batch() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let mut population = population();
+    population.push(rule(call("NoSuchIndicator", vec![])));
+
+    assert!(backtester(TradeManagementConfig::signal_only(), 100).run_batch(&population, &data).is_err());
+}
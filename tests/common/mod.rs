@@ -0,0 +1,108 @@
+//! Helpers shared by the integration tests; each test crate uses only some of them
+#![allow(dead_code)]
+
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::ThreadId;
+use tradebias::data::{CsvConnector, IndicatorCache};
+use tradebias::engines::evaluation::Backtester;
+use tradebias::engines::generation::evolution_engine::{EvolutionConfig, EvolutionEngine, ProgressCallback};
+use tradebias::engines::generation::hall_of_fame::EliteStrategy;
+use tradebias::engines::generation::semantic_mapper::SemanticMapper;
+use tradebias::engines::generation::{AdaptedRates, IslandModel, IslandSettings, MigrationTopology};
+use tradebias::functions::registry::FunctionRegistry;
+
+pub fn sample() -> DataFrame {
+    CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap()
+}
+
+/// A backtester and depth 4 mapper over a fresh registry
+pub fn parts() -> (Backtester, SemanticMapper) {
+    let registry = Arc::new(FunctionRegistry::new());
+    let backtester = Backtester::new(Arc::clone(&registry), Arc::new(IndicatorCache::new(100)), 10000.0);
+    (backtester, SemanticMapper::new(registry, 4))
+}
+
+pub fn engine(config: EvolutionConfig) -> EvolutionEngine {
+    let (backtester, semantic_mapper) = parts();
+    EvolutionEngine::new(config, backtester, semantic_mapper)
+}
+
+/// Three islands differing in depth, mutation rate and indicators
+pub fn island_model(migration_interval: usize, topology: MigrationTopology) -> IslandModel {
+    IslandModel {
+        islands: vec![
+            IslandSettings::default(),
+            IslandSettings { max_depth: Some(2), mutation_rate: Some(0.4), ..IslandSettings::default() },
+            IslandSettings { indicators: Some(vec!["RSI".to_string()]), ..IslandSettings::default() },
+        ],
+        migration_interval,
+        migrants: 2,
+        topology,
+    }
+}
+
+/// Run `config` to completion, with the progress it reported
+pub fn evolve(data: &DataFrame, config: EvolutionConfig) -> (Vec<EliteStrategy>, Recorder) {
+    let mut recorder = Recorder::default();
+    let elites = engine(config).run(data, &mut recorder).unwrap();
+    (elites, recorder)
+}
+
+/// Genome, strategy, fitness and metrics of an elite, with floats compared bit for bit
+pub type Fingerprint = (Vec<u32>, String, u64, BTreeMap<String, u64>);
+
+pub fn fingerprint(elites: &[EliteStrategy]) -> Vec<Fingerprint> {
+    elites
+        .iter()
+        .map(|elite| {
+            let metrics = elite.metrics.iter().map(|(k, v)| (k.clone(), v.to_bits())).collect();
+            (elite.genome.clone(), elite.canonical_string.clone(), elite.fitness.to_bits(), metrics)
+        })
+        .collect()
+}
+
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+}
+
+/// A checkpoint path unique to `name`, removed before use
+pub fn checkpoint_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("tradebias_{}_{}.checkpoint", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Every progress call, and the thread it came from
+#[derive(Default)]
+pub struct Recorder {
+    pub generations: Vec<usize>,
+    pub best: Vec<f64>,
+    pub evaluated: Vec<(usize, usize)>,
+    pub rates: Vec<(usize, AdaptedRates)>,
+    pub threads: Vec<ThreadId>,
+}
+
+impl ProgressCallback for &mut Recorder {
+    fn on_generation_start(&mut self, generation: usize) {
+        self.generations.push(generation);
+        self.threads.push(std::thread::current().id());
+    }
+
+    fn on_generation_complete(&mut self, _generation: usize, best_fitness: f64, _hall_of_fame_size: usize) {
+        self.best.push(best_fitness);
+        self.threads.push(std::thread::current().id());
+    }
+
+    fn on_strategy_evaluated(&mut self, strategy_num: usize, total: usize) {
+        self.evaluated.push((strategy_num, total));
+        self.threads.push(std::thread::current().id());
+    }
+
+    fn on_rates_adapted(&mut self, generation: usize, rates: &AdaptedRates) {
+        self.rates.push((generation, *rates));
+        self.threads.push(std::thread::current().id());
+    }
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use common::{sample, Recorder};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tradebias::config::backtesting::{BacktestingConfig, EvaluationBudget, ExecutionTiming};
+use tradebias::config::traits::ConfigSection;
+use tradebias::data::IndicatorCache;
+use tradebias::engines::evaluation::Backtester;
+use tradebias::engines::generation::ast::{StrategyAST, StrategyMetadata};
+use tradebias::engines::generation::evolution_engine::{EvolutionConfig, EvolutionEngine};
+use tradebias::engines::generation::hall_of_fame::{get_canonical_ast_string, EliteStrategy, HallOfFame};
+use tradebias::engines::generation::semantic_mapper::SemanticMapper;
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AbortReason, AstNode, StrategyResult, Value};
+
+fn call(function: &str, args: Vec<AstNode>) -> AstNode {
+    AstNode::Call { function: function.to_string(), args: args.into_iter().map(Box::new).collect() }
+}
+
+fn ema(period: i64) -> AstNode {
+    call("EMA", vec![call("Close", vec![]), AstNode::Const(Value::Integer(period))])
+}
+
+fn rule(action: AstNode) -> StrategyAST {
+    StrategyAST {
+        root: Box::new(AstNode::Rule { condition: Box::new(AstNode::Const(Value::Bool(true))), action: Box::new(action) }),
+        metadata: StrategyMetadata::default(),
+    }
+}
+
+/// Long while the close is above EMA(50), short below
+fn crossover() -> StrategyAST {
+    rule(call("Subtract", vec![call("Close", vec![]), ema(50)]))
+}
+
+fn backtester(budget: EvaluationBudget) -> Backtester {
+    Backtester::new(Arc::new(FunctionRegistry::new()), Arc::new(IndicatorCache::new(100)), 10000.0)
+        .with_budget(budget)
+}
+
+/// Run with and without the fast path and require identical results
+fn run_both(budget: EvaluationBudget, ast: &StrategyAST, data: &DataFrame) -> StrategyResult {
+    let fast = backtester(budget.clone()).run(ast, data).unwrap();
+    let slow = backtester(budget).with_fast_path(false).run(ast, data).unwrap();
+    assert_eq!(fast.aborted, slow.aborted);
+    assert_eq!(fast.trades, slow.trades);
+    assert_eq!(fast.ledger, slow.ledger);
+    assert_eq!(fast.equity_curve, slow.equity_curve);
+    fast
+}
+
+#[test]
+fn test_unlimited_budget_runs_every_bar() {
+    let data = sample();
+
+    let result = run_both(EvaluationBudget::unlimited(), &crossover(), &data);
+
+    assert_eq!(result.aborted, None);
+    assert_eq!(result.metrics["aborted"], 0.0);
+    assert_eq!(result.ledger.len(), data.height());
+}
+
+#[test]
+fn test_drawdown_limit_stops_the_run() {
+    let data = sample();
+    let full = backtester(EvaluationBudget::unlimited()).run(&crossover(), &data).unwrap();
+    let limit = full.metrics["max_drawdown"] / 2.0;
+    let budget = EvaluationBudget { max_drawdown_percent: Some(limit), ..EvaluationBudget::unlimited() };
+
+    for timing in [ExecutionTiming::SameBarClose, ExecutionTiming::NextBarOpen] {
+        let fast = backtester(budget.clone()).with_execution_timing(timing).run(&crossover(), &data).unwrap();
+        let slow = backtester(budget.clone())
+            .with_execution_timing(timing)
+            .with_fast_path(false)
+            .run(&crossover(), &data)
+            .unwrap();
+
+        let Some(AbortReason::MaxDrawdown { bar }) = fast.aborted else {
+            panic!("expected a drawdown abort, got {:?}", fast.aborted);
+        };
+        assert_eq!(fast.aborted, slow.aborted);
+        assert_eq!(fast.trades, slow.trades);
+        assert_eq!(fast.ledger, slow.ledger);
+        assert_eq!(fast.ledger.len(), bar + 1);
+        assert_eq!(fast.metrics["aborted"], 1.0);
+        // Partial metrics cover the bars up to the breach
+        assert!(fast.metrics["max_drawdown"] > limit);
+        assert!(fast.trades.iter().all(|t| t.exit_bar <= bar));
+    }
+}
+
+#[test]
+fn test_too_few_trades_by_the_checkpoint() {
+    let data = sample();
+    let budget = EvaluationBudget { min_trades: 1000, min_trades_checkpoint: 0.25, ..EvaluationBudget::unlimited() };
+
+    let result = run_both(budget, &crossover(), &data);
+
+    let checkpoint = data.height() / 4;
+    assert_eq!(result.aborted, Some(AbortReason::TooFewTrades { bar: checkpoint - 1 }));
+    assert_eq!(result.ledger.len(), checkpoint);
+}
+
+#[test]
+fn test_enough_trades_by_the_checkpoint() {
+    let data = sample();
+    let budget = EvaluationBudget { min_trades: 1, min_trades_checkpoint: 0.9, ..EvaluationBudget::unlimited() };
+
+    let result = run_both(budget, &crossover(), &data);
+
+    assert_eq!(result.aborted, None);
+}
+
+#[test]
+fn test_constant_signal_is_skipped() {
+    let data = sample();
+    let always_long = rule(AstNode::Const(Value::Float(1.0)));
+    let budget = EvaluationBudget { abort_on_constant_signal: true, ..EvaluationBudget::unlimited() };
+
+    let skipped = run_both(budget.clone(), &always_long, &data);
+    let traded = run_both(budget, &crossover(), &data);
+    let held = run_both(EvaluationBudget::unlimited(), &always_long, &data);
+
+    assert_eq!(skipped.aborted, Some(AbortReason::ConstantSignal));
+    assert!(skipped.trades.is_empty());
+    assert_eq!(skipped.metrics["return_pct"], 0.0);
+    assert_eq!(traded.aborted, None);
+    assert_eq!(held.aborted, None);
+    assert_eq!(held.trades.len(), 1);
+}
+
+#[test]
+fn test_budget_validation() {
+    let with_budget = |budget: EvaluationBudget| BacktestingConfig { budget, ..BacktestingConfig::default() };
+
+    assert!(with_budget(EvaluationBudget { max_drawdown_percent: Some(0.0), ..EvaluationBudget::unlimited() })
+        .validate()
+        .is_err());
+    assert!(with_budget(EvaluationBudget { min_trades_checkpoint: 1.5, ..EvaluationBudget::unlimited() })
+        .validate()
+        .is_err());
+    assert!(with_budget(EvaluationBudget { max_drawdown_percent: Some(30.0), ..EvaluationBudget::unlimited() })
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn test_hall_of_fame_rejects_aborted_strategies() {
+    let ast = crossover();
+    let elite = |aborted: bool| EliteStrategy {
+        canonical_string: get_canonical_ast_string(&ast),
+        ast: ast.clone(),
+        genome: vec![1, 2, 3],
+        fitness: 1.0,
+        metrics: HashMap::new(),
+        aborted,
+        pareto_rank: 0,
+        crowding_distance: 0.0,
+    };
+    let mut hall_of_fame = HallOfFame::new(5);
+
+    assert!(!hall_of_fame.try_add(elite(true)));
+    assert!(hall_of_fame.is_empty());
+    assert!(hall_of_fame.try_add(elite(false)));
+}
+
+#[test]
+fn test_evolution_keeps_aborted_strategies_out_of_the_hall_of_fame() {
+    let data = sample();
+    let registry = Arc::new(FunctionRegistry::new());
+    let budget = EvaluationBudget { min_trades: 1_000_000, ..EvaluationBudget::unlimited() };
+
+    for use_pareto in [false, true] {
+        let config = EvolutionConfig {
+            population_size: 8,
+            generations: 2,
+            use_pareto,
+            seed: Some(7),
+            workers: 1,
+            ..Default::default()
+        };
+        let mut progress = Recorder::default();
+        let backtester = Backtester::new(Arc::clone(&registry), Arc::new(IndicatorCache::new(100)), 10000.0)
+            .with_budget(budget.clone());
+        let semantic_mapper = SemanticMapper::new(Arc::clone(&registry), 4);
+
+        let elites = EvolutionEngine::new(config, backtester, semantic_mapper).run(&data, &mut progress).unwrap();
+
+        // Every strategy is aborted, so none is kept and the best fitness is the floor
+        assert!(elites.is_empty());
+        assert!(progress.best.iter().all(|&fitness| fitness == f64::NEG_INFINITY));
+    }
+}
+
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{checkpoint_path, engine, evolve, parts, sample, Recorder};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tradebias::config::evolution;
+use tradebias::config::traits::ConfigSection;
+use tradebias::engines::generation::evolution_engine::{EvolutionConfig, EvolutionEngine, ProgressCallback};
+use tradebias::engines::generation::hall_of_fame::EliteStrategy;
+use tradebias::engines::generation::{Checkpoint, CheckpointConfig};
+
+/// Panics when generation `at` starts
+struct Crash {
+    at: usize,
+}
+
+impl ProgressCallback for Crash {
+    fn on_generation_start(&mut self, generation: usize) {
+        assert!(generation < self.at, "crashed");
+    }
+
+    fn on_generation_complete(&mut self, _generation: usize, _best_fitness: f64, _hall_of_fame_size: usize) {}
+
+    fn on_strategy_evaluated(&mut self, _strategy_num: usize, _total: usize) {}
+}
+
+fn config(use_pareto: bool, checkpoint: Option<CheckpointConfig>) -> EvolutionConfig {
+    EvolutionConfig {
+        population_size: 12,
+        generations: 5,
+        use_pareto,
+        seed: Some(7),
+        workers: 2,
+        checkpoint,
+        ..Default::default()
+    }
+}
+
+/// Every field of each elite, with floats compared bit for bit
+fn assert_identical(resumed: &[EliteStrategy], uninterrupted: &[EliteStrategy]) {
+    let bits = |elite: &EliteStrategy| {
+        let metrics: BTreeMap<String, u64> = elite.metrics.iter().map(|(k, v)| (k.clone(), v.to_bits())).collect();
+        (
+            elite.genome.clone(),
+            elite.canonical_string.clone(),
+            elite.fitness.to_bits(),
+            elite.pareto_rank,
+            elite.crowding_distance.to_bits(),
+            metrics,
+        )
+    };
+    assert_eq!(resumed.len(), uninterrupted.len());
+    for (a, b) in resumed.iter().zip(uninterrupted) {
+        assert_eq!(bits(a), bits(b));
+    }
+}
+
+#[test]
+fn test_resumed_run_matches_uninterrupted_run() {
+    let data = sample();
+
+    for use_pareto in [false, true] {
+        let path = checkpoint_path(&format!("resume_{}", use_pareto));
+        let every = Some(CheckpointConfig { path: path.clone(), every: 2 });
+        let (uninterrupted, progress) = evolve(&data, config(use_pareto, every));
+
+        // The last checkpoint is taken after generation 3, before generation 4 is evaluated
+        let (backtester, semantic_mapper) = parts();
+        let mut resumed_progress = Recorder::default();
+        let resumed = EvolutionEngine::resume(&path, backtester, semantic_mapper)
+            .unwrap()
+            .run(&data, &mut resumed_progress)
+            .unwrap();
+
+        assert_eq!(progress.generations, vec![0, 1, 2, 3, 4]);
+        assert_eq!(resumed_progress.generations, vec![4]);
+        assert_identical(&resumed, &uninterrupted);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn test_resume_after_a_crash() {
+    let data = sample();
+    let path = checkpoint_path("crash");
+    let (uninterrupted, _) = evolve(&data, config(false, None));
+
+    // A run that dies as generation 3 starts
+    let every = Some(CheckpointConfig { path: path.clone(), every: 1 });
+    let mut engine = engine(config(false, every));
+    let crashed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        engine.run(&data, Crash { at: 3 })
+    }));
+    assert!(crashed.is_err());
+
+    let checkpoint = Checkpoint::load(&path).unwrap();
+    assert_eq!(checkpoint.generation, 3);
+    assert_eq!(checkpoint.islands[0].population.len(), 12);
+    assert_eq!(checkpoint.config.seed, Some(7));
+
+    let (backtester, semantic_mapper) = parts();
+    let mut resumed = EvolutionEngine::resume(&path, backtester, semantic_mapper).unwrap();
+    // Settings come from the checkpoint
+    assert_eq!(resumed.config().generations, 5);
+
+    assert_identical(&resumed.run(&data, &mut Recorder::default()).unwrap(), &uninterrupted);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_resume_rejects_a_corrupt_checkpoint() {
+    let path = checkpoint_path("corrupt");
+    std::fs::write(&path, b"not a checkpoint").unwrap();
+    let (backtester, semantic_mapper) = parts();
+
+    assert!(EvolutionEngine::resume(&path, backtester, semantic_mapper).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_resume_reports_a_missing_checkpoint() {
+    let (backtester, semantic_mapper) = parts();
+
+    assert!(EvolutionEngine::resume(&checkpoint_path("missing"), backtester, semantic_mapper).is_err());
+}
+
+#[test]
+fn test_checkpoints_need_a_file() {
+    let ui_config = evolution::EvolutionConfig {
+        checkpoint_every: 5,
+        checkpoint_path: PathBuf::new(),
+        ..evolution::EvolutionConfig::default()
+    };
+    assert!(ui_config.validate().is_err());
+
+    let disabled = evolution::EvolutionConfig { checkpoint_every: 0, ..ui_config };
+    assert!(disabled.validate().is_ok());
+}
@@ -1,4 +1,6 @@
-use tradebias::config::backtesting::{BacktestingConfig, ValidationMethod};
+use tradebias::config::backtesting::{
+    BacktestingConfig, CarryCostModel, CommissionModel, EvaluationBudget, ExecutionTiming, IntrabarAssumption, MarginConfig, SlippageModel, ValidationMethod,
+};
 use tradebias::config::evolution::EvolutionConfig;
 use tradebias::data::IndicatorCache;
 use tradebias::engines::evaluation::Backtester;
@@ -48,9 +50,22 @@ fn create_test_evolution_config() -> EvolutionConfig {
         mutation_rate: 0.15,
         crossover_rate: 0.85,
         selection_method: tradebias::config::evolution::SelectionMethod::Tournament,
+        operators: tradebias::config::evolution::VariationOperators::Positional,
         elitism_count: 2,
         max_tree_depth: 5,
+        entry_orders: false,
+        entry_order_atr_period: 14,
         tournament_size: 3,
+        workers: 0,
+        checkpoint_every: 0,
+        checkpoint_path: "evolution.checkpoint".into(),
+        num_islands: 1,
+        migration_interval: 10,
+        migrants: 2,
+        adaptive_rates: false,
+        stagnation_generations: 5,
+        restart_after: 0,
+        stop_after: 0,
     }
 }
 
@@ -61,8 +76,14 @@ fn create_test_backtesting_config() -> BacktestingConfig {
         train_test_split: 0.7,
         num_folds: 5,
         initial_capital: 10000.0,
-        commission: 0.001,
-        slippage: 0.001,
+        commission: CommissionModel::Percent { percent: 0.1 },
+        slippage: SlippageModel::Percent { percent: 0.1 },
+        execution_timing: ExecutionTiming::SameBarClose,
+        include_end_of_data_in_stats: true,
+        margin: MarginConfig::default(),
+        carry: CarryCostModel::None,
+        intrabar_assumption: IntrabarAssumption::Pessimistic,
+        budget: EvaluationBudget::unlimited(),
     }
 }
 
@@ -102,17 +123,14 @@ fn test_evolution_basic() {
     let engine_config = EngineEvolutionConfig {
         population_size: evolution_config.population_size,
         generations: evolution_config.num_generations,
-        genome_length: 100,
-        gene_range: 0..1000,
         mutation_rate: evolution_config.mutation_rate,
         crossover_rate: evolution_config.crossover_rate,
         elitism_rate: evolution_config.elitism_count as f64 / evolution_config.population_size as f64,
         tournament_size: evolution_config.tournament_size,
         hall_of_fame_size: 5,
-        fitness_objectives: vec!["return_pct".to_string()],
-        fitness_weights: vec![1.0],
-        min_fitness_threshold: 0.0,
         seed: Some(42), // Fixed seed for reproducibility
+        workers: evolution_config.workers,
+        ..Default::default()
     };
 
     let mut engine = EvolutionEngine::new(engine_config, backtester, semantic_mapper);
@@ -191,17 +209,12 @@ fn test_evolution_with_different_depths() {
         let engine_config = EngineEvolutionConfig {
             population_size: 10,
             generations: 3,
-            genome_length: 100,
-            gene_range: 0..1000,
-            mutation_rate: 0.15,
-            crossover_rate: 0.85,
             elitism_rate: 0.2,
             tournament_size: 3,
             hall_of_fame_size: 3,
-            fitness_objectives: vec!["return_pct".to_string()],
-            fitness_weights: vec![1.0],
-            min_fitness_threshold: 0.0,
             seed: Some(42 + max_depth as u64), // Different seed for each depth
+            workers: 0,
+            ..Default::default()
         };
 
         let mut engine = EvolutionEngine::new(engine_config, backtester, semantic_mapper);
@@ -261,17 +274,12 @@ fn test_evolution_with_different_population_sizes() {
         let engine_config = EngineEvolutionConfig {
             population_size: pop_size,
             generations: 3,
-            genome_length: 100,
-            gene_range: 0..1000,
-            mutation_rate: 0.15,
-            crossover_rate: 0.85,
             elitism_rate: 2.0 / pop_size as f64,
             tournament_size: 3,
             hall_of_fame_size: 3,
-            fitness_objectives: vec!["return_pct".to_string()],
-            fitness_weights: vec![1.0],
-            min_fitness_threshold: 0.0,
             seed: Some(42 + pop_size as u64),
+            workers: 0,
+            ..Default::default()
         };
 
         let mut engine = EvolutionEngine::new(engine_config, backtester, semantic_mapper);
@@ -0,0 +1,149 @@
+use polars::prelude::*;
+use std::sync::Arc;
+use tradebias::data::{CsvConnector, IndicatorCache};
+use tradebias::engines::evaluation::{CompiledExprs, ExpressionBuilder, IndicatorColumns};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, Value};
+
+fn call(function: &str, args: Vec<AstNode>) -> AstNode {
+    AstNode::Call { function: function.to_string(), args: args.into_iter().map(Box::new).collect() }
+}
+
+fn column(function: &str) -> AstNode {
+    call(function, vec![])
+}
+
+fn float(value: f64) -> AstNode {
+    AstNode::Const(Value::Float(value))
+}
+
+fn rsi() -> AstNode {
+    call("RSI", vec![column("Close"), AstNode::Const(Value::Integer(14))])
+}
+
+fn body() -> AstNode {
+    call("Subtract", vec![column("Close"), column("Open")])
+}
+
+fn builder() -> ExpressionBuilder {
+    ExpressionBuilder::new(Arc::new(FunctionRegistry::new()), Arc::new(IndicatorCache::new(100)))
+}
+
+fn sample() -> DataFrame {
+    CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap()
+}
+
+/// Evaluate compiled roots on `frame`
+fn evaluate(compiled: &CompiledExprs, frame: DataFrame) -> Vec<Series> {
+    let roots: Vec<Expr> =
+        compiled.roots.iter().enumerate().map(|(i, e)| e.clone().unwrap().alias(format!("root_{}", i))).collect();
+    let out = compiled.hoist(frame.lazy()).select(roots).collect().unwrap();
+    out.get_columns().iter().map(|c| c.as_materialized_series().clone()).collect()
+}
+
+/// Evaluate each root built on its own, as before compilation
+fn evaluate_inline(builder: &ExpressionBuilder, roots: &[&AstNode], frame: &DataFrame) -> Vec<Series> {
+    let exprs: Vec<Expr> = roots
+        .iter()
+        .enumerate()
+        .map(|(i, root)| builder.build(root, frame).unwrap().alias(format!("root_{}", i)))
+        .collect();
+    let out = frame.clone().lazy().select(exprs).collect().unwrap();
+    out.get_columns().iter().map(|c| c.as_materialized_series().clone()).collect()
+}
+
+#[test]
+fn test_repeated_subtree_is_hoisted_once() {
+    let data = sample();
+    let builder = builder();
+    let long = call("And", vec![
+        call("gt", vec![rsi(), float(50.0)]),
+        call("gt_scalar", vec![body(), float(0.0)]),
+    ]);
+    let short = call("lt", vec![rsi(), call("Abs", vec![body()])]);
+
+    let compiled = builder.compile(&[Some(&long), None, Some(&short)], &data, None).unwrap();
+
+    assert_eq!(compiled.stages.len(), 1);
+    assert_eq!(compiled.stages[0].len(), 2, "RSI and the candle body");
+    assert!(compiled.roots[1].is_none());
+    let compiled = CompiledExprs { roots: vec![compiled.roots[0].clone(), compiled.roots[2].clone()], ..compiled };
+    let inline = evaluate_inline(&builder, &[&long, &short], &data);
+    for (hoisted, inline) in evaluate(&compiled, data).iter().zip(&inline) {
+        assert!(hoisted.equals_missing(inline));
+    }
+}
+
+#[test]
+fn test_nested_repeats_are_staged_innermost_first() {
+    let data = sample();
+    let builder = builder();
+    let range = || call("Abs", vec![body()]);
+    let smoothed = || call("EMA", vec![range(), AstNode::Const(Value::Integer(10))]);
+    let root = call("Or", vec![
+        call("gt", vec![range(), smoothed()]),
+        call("And", vec![call("lt", vec![smoothed(), body()]), call("gt_scalar", vec![body(), float(0.0)])]),
+    ]);
+
+    let compiled = builder.compile(&[Some(&root)], &data, None).unwrap();
+
+    // The body, then the range built on it, then the EMA of the range
+    assert_eq!(compiled.stages.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 1, 1]);
+    let inline = evaluate_inline(&builder, &[&root], &data);
+    assert!(evaluate(&compiled, data)[0].equals_missing(&inline[0]));
+}
+
+#[test]
+fn test_nothing_is_hoisted_without_repeats() {
+    let data = sample();
+    let root = call("gt", vec![rsi(), body()]);
+
+    let compiled = builder().compile(&[Some(&root)], &data, None).unwrap();
+
+    assert!(compiled.stages.is_empty());
+}
+
+#[test]
+fn test_cached_indicators_are_not_hoisted_again() {
+    let data = sample();
+    let builder = builder();
+    let inner = call("Abs", vec![body()]);
+    let root = call("And", vec![
+        call("gt", vec![rsi(), call("EMA", vec![inner.clone(), AstNode::Const(Value::Integer(5))])]),
+        call("lt", vec![rsi(), inner.clone()]),
+    ]);
+    let mut columns = IndicatorColumns::new(&data);
+
+    let compiled = builder.compile(&[Some(&root)], &data, Some(&mut columns)).unwrap();
+
+    // RSI is materialised once, and the range inside the EMA is evaluated with the
+    // EMA, leaving a single use of it to build inline
+    assert!(compiled.stages.is_empty());
+    let inline = evaluate_inline(&builder, &[&root], &data);
+    let frame = columns.attach(&data).unwrap();
+    assert!(evaluate(&compiled, frame)[0].equals_missing(&inline[0]));
+}
+
+#[test]
+fn test_doubling_tree_compiles_to_one_column_per_level() {
+    let data = sample();
+    // Each level adds the previous level to itself: 2^levels leaves, `levels` distinct subtrees
+    let levels = 12;
+    let mut node = body();
+    for _ in 0..levels {
+        node = call("Add", vec![node.clone(), node]);
+    }
+    let root = call("gt_scalar", vec![node, float(0.0)]);
+
+    let compiled = builder().compile(&[Some(&root)], &data, None).unwrap();
+
+    assert_eq!(compiled.stages.len(), levels);
+    let expected = data
+        .clone()
+        .lazy()
+        .select([((col("close") - col("open")) * lit(2f64.powi(levels as i32))).gt(lit(0.0))])
+        .collect()
+        .unwrap();
+    let signal = &evaluate(&compiled, data)[0];
+    assert!(signal.equals_missing(expected.get_columns()[0].as_materialized_series()));
+}
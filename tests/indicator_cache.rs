@@ -0,0 +1,188 @@
+use polars::prelude::*;
+use rayon::prelude::*;
+use std::sync::Arc;
+use tradebias::data::{CsvConnector, IndicatorCache};
+use tradebias::engines::evaluation::Backtester;
+use tradebias::engines::generation::ast::{StrategyAST, StrategyMetadata};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, StrategyResult, Value};
+
+fn series(name: &str, values: &[f64]) -> Series {
+    Series::new(name.into(), values)
+}
+
+fn call(function: &str, args: Vec<AstNode>) -> AstNode {
+    AstNode::Call { function: function.to_string(), args: args.into_iter().map(Box::new).collect() }
+}
+
+fn indicator(function: &str, input: AstNode, period: i64) -> AstNode {
+    call(function, vec![input, AstNode::Const(Value::Integer(period))])
+}
+
+fn close() -> AstNode {
+    call("Close", vec![])
+}
+
+/// Long while `fast` is above `slow`, short below
+fn crossover(fast: AstNode, slow: AstNode) -> StrategyAST {
+    StrategyAST {
+        root: Box::new(AstNode::Rule {
+            condition: Box::new(AstNode::Const(Value::Bool(true))),
+            action: Box::new(call("Subtract", vec![fast, slow])),
+        }),
+        metadata: StrategyMetadata::default(),
+    }
+}
+
+fn strategies() -> Vec<StrategyAST> {
+    let rsi = || indicator("RSI", close(), 14);
+    vec![
+        crossover(indicator("EMA", close(), 10), indicator("EMA", close(), 50)),
+        crossover(indicator("SMA", close(), 10), indicator("EMA", close(), 50)),
+        crossover(rsi(), AstNode::Const(Value::Float(50.0))),
+        crossover(indicator("EMA", rsi(), 5), rsi()),
+        crossover(indicator("SMA", rsi(), 5), indicator("EMA", rsi(), 5)),
+    ]
+}
+
+fn backtester(cache: Arc<IndicatorCache>) -> Backtester {
+    Backtester::new(Arc::new(FunctionRegistry::new()), cache, 10000.0)
+}
+
+fn uncached(ast: &StrategyAST, data: &DataFrame) -> StrategyResult {
+    backtester(Arc::new(IndicatorCache::new(0))).run(ast, data).unwrap()
+}
+
+fn assert_same(cached: &StrategyResult, fresh: &StrategyResult) {
+    assert_eq!(cached.trades, fresh.trades);
+    assert_eq!(cached.equity_curve, fresh.equity_curve);
+}
+
+#[test]
+fn test_least_recently_used_entry_is_evicted() {
+    let cache = IndicatorCache::new(2);
+    cache.set("a".to_string(), series("a", &[1.0]));
+    cache.set("b".to_string(), series("b", &[2.0]));
+
+    // Reading "a" makes "b" the oldest
+    assert!(cache.get("a").is_some());
+    cache.set("c".to_string(), series("c", &[3.0]));
+
+    assert!(cache.get("a").is_some());
+    assert!(cache.get("b").is_none());
+    assert!(cache.get("c").is_some());
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_stats_count_hits_misses_and_evictions() {
+    let cache = IndicatorCache::new(1);
+    let mut computed = 0;
+
+    for key in ["x", "x", "y", "x"] {
+        let _ = cache
+            .get_or_compute(key, || {
+                computed += 1;
+                Ok(series(key, &[0.0]))
+            })
+            .unwrap();
+    }
+
+    let stats = cache.stats();
+    assert_eq!(computed, 3);
+    assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 3, 2));
+    assert_eq!((stats.entries, stats.capacity), (1, 1));
+    assert_eq!(stats.hit_rate(), 0.25);
+}
+
+#[test]
+fn test_zero_capacity_stores_nothing() {
+    let cache = IndicatorCache::new(0);
+    cache.set("a".to_string(), series("a", &[1.0]));
+
+    assert!(cache.is_empty());
+    assert!(cache.get("a").is_none());
+}
+
+#[test]
+fn test_fingerprint_follows_names_and_values() {
+    let data = df! { "close" => &[1.0, 2.0, 3.0] }.unwrap();
+    let same = df! { "close" => &[1.0, 2.0, 3.0] }.unwrap();
+    let changed = df! { "close" => &[1.0, 2.0, 3.5] }.unwrap();
+    let renamed = df! { "open" => &[1.0, 2.0, 3.0] }.unwrap();
+
+    assert_eq!(IndicatorCache::fingerprint(&data), IndicatorCache::fingerprint(&same));
+    assert_ne!(IndicatorCache::fingerprint(&data), IndicatorCache::fingerprint(&changed));
+    assert_ne!(IndicatorCache::fingerprint(&data), IndicatorCache::fingerprint(&renamed));
+}
+
+#[test]
+fn test_backtests_share_indicator_results() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let cache = Arc::new(IndicatorCache::new(100));
+    let backtester = backtester(Arc::clone(&cache));
+
+    for ast in strategies() {
+        assert_same(&backtester.run(&ast, &data).unwrap(), &uncached(&ast, &data));
+    }
+
+    // EMA(50) and RSI(14) are computed once; EMA(RSI, 5) is found again in the last strategy
+    let stats = backtester.cache().stats();
+    assert!(stats.hits >= 4, "{:?}", stats);
+    assert_eq!(stats.misses as usize, stats.entries);
+}
+
+#[test]
+fn test_results_are_not_reused_across_datasets() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let shifted = data
+        .clone()
+        .lazy()
+        .with_column((col("close") * lit(1.01)).alias("close"))
+        .collect()
+        .unwrap();
+    let backtester = backtester(Arc::new(IndicatorCache::new(100)));
+    let ast = &strategies()[3];
+
+    backtester.run(ast, &data).unwrap();
+    let result = backtester.run(ast, &shifted).unwrap();
+
+    assert_same(&result, &uncached(ast, &shifted));
+    assert_eq!(backtester.cache().stats().hits, 0);
+}
+
+#[test]
+fn test_switching_datasets_finds_each_ones_results() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let shifted = data
+        .clone()
+        .lazy()
+        .with_column((col("close") * lit(1.01)).alias("close"))
+        .collect()
+        .unwrap();
+    let backtester = backtester(Arc::new(IndicatorCache::new(100)));
+    let ast = &strategies()[3];
+
+    for frame in [&data, &shifted, &data, &shifted] {
+        assert_same(&backtester.run(ast, frame).unwrap(), &uncached(ast, frame));
+    }
+
+    // Coming back to a dataset reuses everything computed on it
+    let stats = backtester.cache().stats();
+    assert_eq!(stats.hits, stats.misses);
+}
+
+#[test]
+fn test_parallel_backtests_match_sequential() {
+    let data = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let backtester = backtester(Arc::new(IndicatorCache::new(100)));
+    let population: Vec<StrategyAST> = (0..8).flat_map(|_| strategies()).collect();
+
+    let results: Vec<StrategyResult> =
+        population.par_iter().map(|ast| backtester.run(ast, &data).unwrap()).collect();
+
+    for (ast, result) in population.iter().zip(&results) {
+        assert_same(result, &uncached(ast, &data));
+    }
+    assert!(backtester.cache().stats().hit_rate() > 0.5);
+}
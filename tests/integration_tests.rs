@@ -1,11 +1,13 @@
 use std::collections::HashMap;
+use tradebias::config::backtesting::ExecutionTiming;
 use tradebias::engines::metrics::MetricsEngine;
-use tradebias::types::{AstNode, Direction, ExitReason, StrategyResult, Trade, Value};
+use tradebias::types::{AstNode, Direction, ExitReason, IntrabarResolution, StrategyResult, Trade, Value};
 
 #[test]
 fn test_metrics_engine() {
     let trades = vec![
         Trade {
+            entry_id: 0,
             entry_bar: 0,
             exit_bar: 5,
             entry_price: 100.0,
@@ -15,8 +17,11 @@ fn test_metrics_engine() {
             profit: 10.0,
             exit_reason: ExitReason::TakeProfit,
             fees: 0.0,
+            slippage: 0.0,
+            carry: 0.0,
         },
         Trade {
+            entry_id: 1,
             entry_bar: 6,
             exit_bar: 10,
             entry_price: 110.0,
@@ -26,6 +31,8 @@ fn test_metrics_engine() {
             profit: 5.0,
             exit_reason: ExitReason::Signal,
             fees: 0.0,
+            slippage: 0.0,
+            carry: 0.0,
         },
     ];
 
@@ -36,7 +43,12 @@ fn test_metrics_engine() {
         metrics: HashMap::new(),
         trades,
         equity_curve,
+        ledger: Vec::new(),
+        order_events: Vec::new(),
         in_sample: true,
+        execution_timing: ExecutionTiming::SameBarClose,
+        intrabar: IntrabarResolution::default(),
+        aborted: None,
     };
 
     let engine = MetricsEngine::new(10000.0);
@@ -0,0 +1,216 @@
+use polars::prelude::*;
+use std::sync::Arc;
+use tradebias::config::backtesting::IntrabarAssumption;
+use tradebias::config::trade_management::{
+    ScaleOut, StopLossConfig, TakeProfitConfig, TradeManagementConfig,
+};
+use tradebias::data::CsvConnector;
+use tradebias::data::IndicatorCache;
+use tradebias::engines::evaluation::backtester::Backtester;
+use tradebias::engines::evaluation::portfolio::{BarSignals, PriceBar};
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::engines::generation::ast::{StrategyAST, StrategyMetadata};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, ExitReason, Value};
+
+/// Long at 100 with a stop at 98 and a target at 102
+fn bracketed(take_profit: TakeProfitConfig, assumption: IntrabarAssumption) -> Portfolio {
+    let config = TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+        take_profit,
+        ..TradeManagementConfig::signal_only()
+    };
+    let mut portfolio = Portfolio::new_with_trade_management(10000.0, config)
+        .with_intrabar_assumption(assumption);
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio
+}
+
+fn target() -> TakeProfitConfig {
+    TakeProfitConfig::FixedPercent { percent: 2.0 }
+}
+
+// Reaches both 98 and 102
+fn wide_bar() -> PriceBar {
+    PriceBar::new(100.0, 103.0, 97.0, 100.0)
+}
+
+#[test]
+fn test_pessimistic_fallback_stops_out() {
+    let mut portfolio = bracketed(target(), IntrabarAssumption::Pessimistic);
+
+    portfolio.process_price_bar(1, 0.0, &wide_bar()).unwrap();
+
+    assert_eq!(portfolio.get_trades()[0].exit_reason, ExitReason::StopLoss);
+    assert_eq!(portfolio.intrabar_ambiguous, 1);
+    assert_eq!(portfolio.intrabar_resolved, 0);
+}
+
+#[test]
+fn test_optimistic_fallback_takes_profit() {
+    let mut portfolio = bracketed(target(), IntrabarAssumption::Optimistic);
+
+    portfolio.process_price_bar(1, 0.0, &wide_bar()).unwrap();
+
+    assert_eq!(portfolio.get_trades()[0].exit_reason, ExitReason::TakeProfit);
+    assert_eq!(portfolio.get_trades()[0].exit_price, 102.0);
+}
+
+#[test]
+fn test_path_decides_target_first() {
+    let mut portfolio = bracketed(target(), IntrabarAssumption::Pessimistic);
+    let path = [
+        PriceBar::new(100.0, 101.0, 99.0, 100.5),
+        PriceBar::new(100.5, 103.0, 100.0, 102.5),
+        PriceBar::new(102.5, 102.5, 97.0, 100.0),
+    ];
+
+    portfolio.process_bar_with_path(1, BarSignals::default(), &wide_bar(), &path).unwrap();
+
+    assert_eq!(portfolio.get_trades()[0].exit_reason, ExitReason::TakeProfit);
+    assert_eq!(portfolio.intrabar_ambiguous, 1);
+    assert_eq!(portfolio.intrabar_resolved, 1);
+}
+
+#[test]
+fn test_path_decides_stop_first() {
+    let mut portfolio = bracketed(target(), IntrabarAssumption::Optimistic);
+    let path = [PriceBar::new(100.0, 100.0, 97.0, 98.5), PriceBar::new(98.5, 103.0, 98.5, 100.0)];
+
+    portfolio.process_bar_with_path(1, BarSignals::default(), &wide_bar(), &path).unwrap();
+
+    assert_eq!(portfolio.get_trades()[0].exit_reason, ExitReason::StopLoss);
+    assert_eq!(portfolio.intrabar_resolved, 1);
+}
+
+#[test]
+fn test_path_that_cannot_tell_falls_back() {
+    let mut portfolio = bracketed(target(), IntrabarAssumption::Optimistic);
+    let path = [wide_bar()];
+
+    portfolio.process_bar_with_path(1, BarSignals::default(), &wide_bar(), &path).unwrap();
+
+    assert_eq!(portfolio.get_trades()[0].exit_reason, ExitReason::TakeProfit);
+    assert_eq!(portfolio.intrabar_ambiguous, 1);
+    assert_eq!(portfolio.intrabar_resolved, 0);
+}
+
+#[test]
+fn test_open_through_target_is_not_ambiguous() {
+    let mut portfolio = bracketed(target(), IntrabarAssumption::Pessimistic);
+
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(103.0, 104.0, 97.0, 100.0)).unwrap();
+
+    let trade = &portfolio.get_trades()[0];
+    assert_eq!(trade.exit_reason, ExitReason::TakeProfit);
+    assert_eq!(trade.exit_price, 103.0);
+    assert_eq!(portfolio.intrabar_ambiguous, 0);
+}
+
+#[test]
+fn test_scale_out_before_stop_keeps_partial_profit() {
+    let ladder = TakeProfitConfig::Ladder { levels: vec![ScaleOut { r_multiple: 1.0, percent: 50.0 }] };
+    let mut portfolio = bracketed(ladder, IntrabarAssumption::Optimistic);
+
+    portfolio.process_price_bar(1, 0.0, &wide_bar()).unwrap();
+
+    // Half off at 1R, then the rest stopped later in the bar
+    let reasons: Vec<_> = portfolio.get_trades().iter().map(|t| t.exit_reason).collect();
+    assert_eq!(reasons, vec![ExitReason::TakeProfit, ExitReason::StopLoss]);
+    assert!(portfolio.positions.is_empty());
+}
+
+fn always_long() -> StrategyAST {
+    StrategyAST {
+        root: Box::new(AstNode::Rule {
+            condition: Box::new(AstNode::Const(Value::Bool(true))),
+            action: Box::new(AstNode::Const(Value::Float(1.0))),
+        }),
+        metadata: StrategyMetadata::default(),
+    }
+}
+
+fn bracketed_backtester() -> Backtester {
+    let trade_management = TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+        take_profit: TakeProfitConfig::FixedPercent { percent: 2.0 },
+        ..TradeManagementConfig::signal_only()
+    };
+    let registry = Arc::new(FunctionRegistry::new());
+    let cache = Arc::new(IndicatorCache::new(100));
+    Backtester::new(registry, cache, 10000.0).with_trade_management(trade_management)
+}
+
+#[test]
+fn test_backtester_walks_lower_timeframe() {
+    let hourly = df! {
+        "timestamp" => &["2025-01-01 00:00:00", "2025-01-01 01:00:00", "2025-01-01 02:00:00"],
+        "open" => &[100.0, 100.0, 100.0],
+        "high" => &[100.0, 103.0, 100.5],
+        "low" => &[100.0, 97.0, 99.5],
+        "close" => &[100.0, 100.0, 100.0],
+    }
+    .unwrap();
+    // The second hour rallies through the target before it sells off through the stop
+    let half_hourly = df! {
+        "timestamp" => &[
+            "2025-01-01 00:00:00", "2025-01-01 00:30:00",
+            "2025-01-01 01:00:00", "2025-01-01 01:30:00",
+            "2025-01-01 02:00:00", "2025-01-01 02:30:00",
+        ],
+        "open" => &[100.0, 100.0, 100.0, 102.5, 100.0, 100.0],
+        "high" => &[100.0, 100.0, 103.0, 102.5, 100.5, 100.0],
+        "low" => &[100.0, 100.0, 99.5, 97.0, 99.5, 100.0],
+        "close" => &[100.0, 100.0, 102.5, 100.0, 100.0, 100.0],
+    }
+    .unwrap();
+
+    let pessimistic = bracketed_backtester().run(&always_long(), &hourly).unwrap();
+    assert_eq!(pessimistic.trades[0].exit_reason, ExitReason::StopLoss);
+    assert!(!pessimistic.intrabar.lower_timeframe);
+    assert_eq!(pessimistic.intrabar.assumption, IntrabarAssumption::Pessimistic);
+    assert_eq!(pessimistic.intrabar.ambiguous, 1);
+
+    let resolved = bracketed_backtester()
+        .with_lower_timeframe(half_hourly)
+        .run(&always_long(), &hourly)
+        .unwrap();
+    assert_eq!(resolved.trades[0].exit_reason, ExitReason::TakeProfit);
+    assert!(resolved.intrabar.lower_timeframe);
+    assert_eq!(resolved.intrabar.resolved, 1);
+}
+
+#[test]
+fn test_backtester_lower_timeframe_needs_timestamps() {
+    let df = df! { "close" => &[100.0, 101.0] }.unwrap();
+
+    let result = bracketed_backtester()
+        .with_lower_timeframe(df.clone())
+        .run(&always_long(), &df);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_backtester_with_sample_minute_data() {
+    let hourly = CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+    let minutes = CsvConnector::load("tests/data/BTC_1min_sample.csv").unwrap();
+
+    let trade_management = TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 0.3 },
+        take_profit: TakeProfitConfig::FixedPercent { percent: 0.3 },
+        ..TradeManagementConfig::signal_only()
+    };
+    let registry = Arc::new(FunctionRegistry::new());
+    let cache = Arc::new(IndicatorCache::new(100));
+    let result = Backtester::new(registry, cache, 10000.0)
+        .with_trade_management(trade_management)
+        .with_lower_timeframe(minutes)
+        .run(&always_long(), &hourly)
+        .unwrap();
+
+    // Tight brackets on hourly bars leave many fills to resolve, and minute bars settle most
+    assert!(result.intrabar.ambiguous > 0);
+    assert!(result.intrabar.resolved > 0);
+    assert!(result.intrabar.resolved <= result.intrabar.ambiguous);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::{checkpoint_path, engine, evolve, fingerprint, island_model, parts, sample, Recorder};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tradebias::engines::generation::evolution_engine::{EvolutionConfig, EvolutionEngine};
+use tradebias::engines::generation::islands::migrate;
+use tradebias::engines::generation::semantic_mapper::SemanticMapper;
+use tradebias::engines::generation::{CheckpointConfig, IslandModel, IslandState, MigrationTopology};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::AstNode;
+
+fn config(workers: usize, use_pareto: bool, islands: Option<IslandModel>) -> EvolutionConfig {
+    EvolutionConfig {
+        population_size: 8,
+        generations: 4,
+        use_pareto,
+        seed: Some(11),
+        workers,
+        islands,
+        ..Default::default()
+    }
+}
+
+/// Indicator calls anywhere in `node`
+fn indicators_used(node: &AstNode, indicators: &HashSet<String>, used: &mut HashSet<String>) {
+    match node {
+        AstNode::Const(_) => {}
+        AstNode::Call { function, args } => {
+            if indicators.contains(function) {
+                used.insert(function.clone());
+            }
+            for arg in args {
+                indicators_used(arg, indicators, used);
+            }
+        }
+        AstNode::Rule { condition, action } => {
+            indicators_used(condition, indicators, used);
+            indicators_used(action, indicators, used);
+        }
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            for side in [long_entry, long_exit, short_entry, short_exit].into_iter().flatten() {
+                indicators_used(side, indicators, used);
+            }
+        }
+    }
+}
+
+fn depth(node: &AstNode) -> usize {
+    match node {
+        AstNode::Const(_) => 1,
+        AstNode::Call { args, .. } => 1 + args.iter().map(|arg| depth(arg)).max().unwrap_or(0),
+        AstNode::Rule { condition, action } => 1 + depth(condition).max(depth(action)),
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            1 + [long_entry, long_exit, short_entry, short_exit].into_iter().flatten().map(|side| depth(side)).max().unwrap_or(0)
+        }
+    }
+}
+
+#[test]
+fn test_each_generation_evaluates_every_island() {
+    let data = sample();
+
+    for topology in [MigrationTopology::Ring, MigrationTopology::FullyConnected] {
+        let (elites, progress) = evolve(&data, config(1, false, Some(island_model(2, topology))));
+
+        assert!(!elites.is_empty());
+        // 3 × 8 strategies per generation
+        let per_generation: Vec<_> = (1..=24).map(|n| (n, 24)).collect();
+        assert_eq!(progress.evaluated, per_generation.repeat(4));
+    }
+}
+
+#[test]
+fn test_ring_migration_replaces_the_tail_of_the_next_island() {
+    let island = |id: u32| IslandState {
+        population: (0..4).map(|i| vec![id, i]).collect(),
+        rng: ChaCha12Rng::seed_from_u64(0),
+    };
+    let mut islands = vec![island(0), island(1), island(2)];
+    let emigrants = vec![vec![vec![0, 9]], vec![vec![1, 9]], vec![vec![2, 9]]];
+
+    migrate(&mut islands, &emigrants, MigrationTopology::Ring);
+
+    assert_eq!(islands[0].population, vec![vec![0, 0], vec![0, 1], vec![0, 2], vec![2, 9]]);
+    assert_eq!(islands[1].population, vec![vec![1, 0], vec![1, 1], vec![1, 2], vec![0, 9]]);
+    assert_eq!(islands[2].population, vec![vec![2, 0], vec![2, 1], vec![2, 2], vec![1, 9]]);
+}
+
+#[test]
+fn test_fully_connected_migration_receives_from_every_other_island() {
+    let island = |id: u32| IslandState {
+        population: (0..4).map(|i| vec![id, i]).collect(),
+        rng: ChaCha12Rng::seed_from_u64(0),
+    };
+    let mut islands = vec![island(0), island(1), island(2)];
+    let emigrants = vec![vec![vec![0, 9]], vec![vec![1, 9]], vec![vec![2, 9]]];
+
+    migrate(&mut islands, &emigrants, MigrationTopology::FullyConnected);
+
+    assert_eq!(islands[0].population, vec![vec![0, 0], vec![0, 1], vec![1, 9], vec![2, 9]]);
+    assert_eq!(islands[2].population, vec![vec![2, 0], vec![2, 1], vec![0, 9], vec![1, 9]]);
+    assert_eq!(MigrationTopology::Ring.sources(0, 1), Vec::<usize>::new());
+}
+
+#[test]
+fn test_mapper_respects_island_settings() {
+    let registry = Arc::new(FunctionRegistry::new());
+    let all: HashSet<String> = registry.get_indicators().iter().map(|i| i.alias().to_string()).collect();
+    let mapper = SemanticMapper::new(Arc::clone(&registry), 6);
+    let rsi_only = mapper.clone().with_indicators(vec!["RSI".to_string()]);
+    let shallow = mapper.clone().with_max_depth(1);
+
+    let mut used = HashSet::new();
+    let mut rsi_used = HashSet::new();
+    let (mut deepest, mut deepest_shallow) = (0, 0);
+    for seed in 0..50u32 {
+        let genome: Vec<u32> = (0..200).map(|i| (seed * 7919 + i * 104729) % 1000).collect();
+        let ast = mapper.create_strategy_ast(&genome).unwrap();
+        indicators_used(&ast.root, &all, &mut used);
+        deepest = deepest.max(depth(&ast.root));
+        indicators_used(&rsi_only.create_strategy_ast(&genome).unwrap().root, &all, &mut rsi_used);
+        deepest_shallow = deepest_shallow.max(depth(&shallow.create_strategy_ast(&genome).unwrap().root));
+    }
+
+    assert!(used.len() > 1);
+    assert_eq!(rsi_used, HashSet::from(["RSI".to_string()]));
+    assert!(deepest_shallow < deepest);
+}
+
+#[test]
+fn test_island_run_resumes_from_a_checkpoint() {
+    let data = sample();
+    let path = checkpoint_path("islands");
+    let mut with_checkpoint = config(2, false, Some(island_model(1, MigrationTopology::Ring)));
+    with_checkpoint.checkpoint = Some(CheckpointConfig { path: path.clone(), every: 3 });
+
+    let (uninterrupted, _) = evolve(&data, with_checkpoint);
+
+    let (backtester, semantic_mapper) = parts();
+    let mut progress = Recorder::default();
+    let resumed = EvolutionEngine::resume(&path, backtester, semantic_mapper).unwrap().run(&data, &mut progress).unwrap();
+
+    assert_eq!(progress.evaluated.len(), 24);
+    assert_eq!(fingerprint(&resumed), fingerprint(&uninterrupted));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_empty_island_model_is_rejected() {
+    let empty = IslandModel {
+        islands: Vec::new(),
+        migration_interval: 1,
+        migrants: 1,
+        topology: MigrationTopology::Ring,
+    };
+
+    assert!(engine(config(1, false, Some(empty))).run(&sample(), &mut Recorder::default()).is_err());
+}
@@ -93,5 +93,19 @@ fn print_ast_debug(node: &AstNode, indent: usize) {
             println!("{}  Action:", prefix);
             print_ast_debug(action, indent + 2);
         }
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            println!("{}Strategy", prefix);
+            for (label, condition) in [
+                ("Long entry", long_entry),
+                ("Long exit", long_exit),
+                ("Short entry", short_entry),
+                ("Short exit", short_exit),
+            ] {
+                if let Some(condition) = condition {
+                    println!("{}  {}:", prefix, label);
+                    print_ast_debug(condition, indent + 2);
+                }
+            }
+        }
     }
 }
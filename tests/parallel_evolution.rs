@@ -0,0 +1,93 @@
+mod common;
+
+use common::{evolve, fingerprint, island_model, sample};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tradebias::config::evolution::{SelectionMethod, VariationOperators};
+use tradebias::data::IndicatorCache;
+use tradebias::engines::evaluation::Backtester;
+use tradebias::engines::generation::evolution_engine::EvolutionConfig;
+use tradebias::engines::generation::{AdaptationConfig, MigrationTopology};
+use tradebias::functions::registry::FunctionRegistry;
+
+fn config(workers: usize, use_pareto: bool) -> EvolutionConfig {
+    EvolutionConfig { population_size: 16, generations: 3, use_pareto, seed: Some(7), workers, ..Default::default() }
+}
+
+/// A config for each way of breeding a generation: islands, every selection
+/// method, tree operators and adapting rates with restarts
+fn variants() -> Vec<(String, EvolutionConfig)> {
+    let base = || EvolutionConfig { population_size: 12, generations: 4, seed: Some(7), ..Default::default() };
+    let mut variants = vec![("default".to_string(), base())];
+    for topology in [MigrationTopology::Ring, MigrationTopology::FullyConnected] {
+        variants.push((
+            format!("{:?} islands", topology),
+            EvolutionConfig { islands: Some(island_model(2, topology)), ..base() },
+        ));
+    }
+    for selection in [
+        SelectionMethod::Roulette,
+        SelectionMethod::Rank,
+        SelectionMethod::StochasticUniversal,
+        SelectionMethod::Lexicase { folds: 4 },
+        SelectionMethod::Boltzmann { temperature: 5.0 },
+    ] {
+        variants.push((format!("{:?}", selection), EvolutionConfig { selection, ..base() }));
+    }
+    variants.push((
+        "tree operators".to_string(),
+        EvolutionConfig { operators: VariationOperators::Tree, mutation_rate: 0.5, ..base() },
+    ));
+    let adaptation = AdaptationConfig { patience: 1, restart_after: Some(2), ..AdaptationConfig::default() };
+    variants.push(("adaptive rates".to_string(), EvolutionConfig { adaptation: Some(adaptation), ..base() }));
+    variants
+}
+
+#[test]
+fn test_results_do_not_depend_on_worker_count() {
+    let data = sample();
+
+    for (name, variant) in variants() {
+        for use_pareto in [false, true] {
+            let run = |workers| evolve(&data, EvolutionConfig { use_pareto, workers, ..variant.clone() });
+            let (single, single_progress) = run(1);
+            assert!(!single.is_empty(), "{} kept no strategies", name);
+
+            for workers in [4, 0] {
+                let (parallel, progress) = run(workers);
+                assert_eq!(fingerprint(&single), fingerprint(&parallel), "{}, pareto {}", name, use_pareto);
+                assert_eq!(single_progress.best, progress.best, "{}, pareto {}", name, use_pareto);
+                assert_eq!(single_progress.rates, progress.rates, "{}, pareto {}", name, use_pareto);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_progress_is_reported_in_order_on_the_calling_thread() {
+    let (_, progress) = evolve(&sample(), config(4, false));
+
+    assert_eq!(progress.generations, vec![0, 1, 2]);
+    let expected: Vec<(usize, usize)> = (0..3).flat_map(|_| (1..=16).map(|n| (n, 16))).collect();
+    assert_eq!(progress.evaluated, expected);
+    let caller = std::thread::current().id();
+    assert!(progress.threads.iter().all(|id| *id == caller));
+}
+
+fn metric_bits(metrics: &HashMap<String, f64>) -> BTreeMap<String, u64> {
+    metrics.iter().map(|(k, v)| (k.clone(), v.to_bits())).collect()
+}
+
+#[test]
+fn test_batched_evaluation_scores_like_single_runs() {
+    let data = sample();
+    let backtester = Backtester::new(Arc::new(FunctionRegistry::new()), Arc::new(IndicatorCache::new(0)), 10000.0);
+
+    for workers in [1, 3] {
+        let (elites, _) = evolve(&data, config(workers, false));
+        for elite in &elites {
+            let alone = backtester.run(&elite.ast, &data).unwrap();
+            assert_eq!(metric_bits(&elite.metrics), metric_bits(&alone.metrics), "{}", elite.canonical_string);
+        }
+    }
+}
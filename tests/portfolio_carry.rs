@@ -0,0 +1,101 @@
+mod common;
+
+use common::assert_close;
+use tradebias::config::backtesting::{CarryCostModel, ExecutionTiming};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+
+fn funded(close: f64, funding_rate: f64) -> PriceBar {
+    PriceBar { funding_rate: Some(funding_rate), ..PriceBar::from_close(close) }
+}
+
+fn funding() -> CarryCostModel {
+    CarryCostModel::FundingRate { column: "funding_rate".to_string() }
+}
+
+#[test]
+fn test_long_pays_positive_funding() {
+    let mut portfolio = Portfolio::new(10000.0).with_carry(funding());
+
+    // 10 units filled at the close of bar 0, which pays nothing for that bar
+    portfolio.process_price_bar(0, 1.0, &funded(100.0, 0.001)).unwrap();
+    assert_close(portfolio.cash, 9000.0);
+
+    // Held through bar 1: 0.1% of 1100 notional
+    portfolio.process_price_bar(1, 1.0, &funded(110.0, 0.001)).unwrap();
+    assert_close(portfolio.positions[0].carry, 1.1);
+    assert_close(portfolio.cash, 9000.0 - 1.1);
+
+    // Closed on bar 2's signal before it accrues again
+    portfolio.process_price_bar(2, -1.0, &funded(100.0, 0.001)).unwrap();
+
+    let trade = &portfolio.get_trades()[0];
+    assert_close(trade.carry, 1.1);
+    assert_close(trade.profit, -1.1);
+    assert_close(trade.gross_profit(), 0.0);
+    assert_close(portfolio.realized_pnl, -1.1);
+}
+
+#[test]
+fn test_short_receives_positive_funding() {
+    let mut portfolio = Portfolio::new(10000.0).with_carry(funding());
+
+    portfolio.process_price_bar(0, -1.0, &funded(100.0, 0.001)).unwrap();
+    portfolio.process_price_bar(1, -1.0, &funded(100.0, 0.001)).unwrap();
+    portfolio.process_price_bar(2, -1.0, &funded(100.0, -0.002)).unwrap();
+    portfolio.process_price_bar(3, 1.0, &funded(100.0, 0.0)).unwrap();
+
+    // Received 1.0, then paid 2.0
+    let trade = &portfolio.get_trades()[0];
+    assert_close(trade.carry, 1.0);
+    assert_close(trade.profit, -1.0);
+}
+
+#[test]
+fn test_borrow_rate_charged_on_shorts_only() {
+    let borrow = CarryCostModel::BorrowRate { annual_percent: 25.0, bars_per_year: 250.0 };
+
+    let mut short = Portfolio::new(10000.0).with_carry(borrow.clone());
+    short.process_bar(0, -1.0, 100.0).unwrap();
+    short.process_bar(1, -1.0, 100.0).unwrap();
+    short.process_bar(2, -1.0, 100.0).unwrap();
+    short.process_bar(3, 1.0, 100.0).unwrap();
+
+    // 0.1% of 1000 notional per bar, for two bars
+    let trade = &short.get_trades()[0];
+    assert_close(trade.carry, 2.0);
+    assert_close(trade.profit, -2.0);
+
+    let mut long = Portfolio::new(10000.0).with_carry(borrow);
+    long.process_bar(0, 1.0, 100.0).unwrap();
+    long.process_bar(1, 1.0, 100.0).unwrap();
+    long.process_bar(2, -1.0, 100.0).unwrap();
+
+    assert_close(long.get_trades()[0].carry, 0.0);
+}
+
+#[test]
+fn test_next_bar_open_fill_pays_for_its_entry_bar() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_carry(funding())
+        .with_execution_timing(ExecutionTiming::NextBarOpen);
+
+    portfolio.process_price_bar(0, 1.0, &funded(100.0, 0.001)).unwrap();
+    assert!(portfolio.positions.is_empty());
+
+    // Filled at bar 1's open and held through its close
+    portfolio.process_price_bar(1, 1.0, &funded(100.0, 0.001)).unwrap();
+    assert_close(portfolio.positions[0].carry, 1.0);
+}
+
+#[test]
+fn test_no_carry_by_default() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_price_bar(0, 1.0, &funded(100.0, 0.01)).unwrap();
+    portfolio.process_price_bar(1, 1.0, &funded(100.0, 0.01)).unwrap();
+    portfolio.process_price_bar(2, -1.0, &funded(100.0, 0.01)).unwrap();
+
+    assert_close(portfolio.get_trades()[0].carry, 0.0);
+    assert_close(portfolio.cash, 10000.0);
+}
@@ -177,8 +177,10 @@ fn test_portfolio_constant_signal_no_exit() {
     // Equity = 10000 (initial) + 90 (total P&L) = 10090.
     assert!(portfolio.equity() > 10000.0,
         "Equity should be greater than initial capital due to unrealized gains");
-    assert_eq!(portfolio.final_balance(), 9000.0,
+    assert_eq!(portfolio.cash, 9000.0,
         "Cash balance should be reduced by the cost of the position");
+    assert_eq!(portfolio.final_balance(), 10090.0,
+        "Final balance should mark the open position to market");
 }
 
 #[test]
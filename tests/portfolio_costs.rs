@@ -0,0 +1,93 @@
+mod common;
+
+use common::assert_close;
+use tradebias::config::backtesting::{CommissionModel, SlippageModel};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+
+#[test]
+fn test_percent_commission_charged_per_side() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::Percent { percent: 0.1 }, SlippageModel::None);
+
+    // 10 units at 100: entry notional 1000, fee 1.0
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    assert_close(portfolio.cash, 10000.0 - 1000.0 - 1.0);
+
+    // Exit notional 1100, fee 1.1
+    portfolio.process_bar(1, -1.0, 110.0).unwrap();
+
+    let trade = &portfolio.get_trades()[0];
+    assert_close(trade.fees, 2.1);
+    assert_close(trade.profit, 100.0 - 2.1);
+    assert_close(trade.gross_profit(), 100.0);
+    assert_close(portfolio.cash, 10000.0 + 100.0 - 2.1);
+    assert_close(portfolio.realized_pnl, 100.0 - 2.1);
+}
+
+#[test]
+fn test_entry_commission_reduces_equity_immediately() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::PerTrade { amount: 5.0 }, SlippageModel::None);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+
+    assert_close(portfolio.equity(), 9995.0);
+    assert_close(portfolio.total_value(), portfolio.equity());
+}
+
+#[test]
+fn test_per_unit_commission() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::PerUnit { amount: 0.5 }, SlippageModel::None);
+
+    portfolio.process_bar(0, -1.0, 100.0).unwrap();
+    portfolio.process_bar(1, 1.0, 90.0).unwrap();
+
+    // 10 units short, 0.5 per unit each side
+    let trade = &portfolio.get_trades()[0];
+    assert_close(trade.fees, 10.0);
+    assert_close(trade.profit, 100.0 - 10.0);
+}
+
+#[test]
+fn test_percent_slippage_moves_fills_against_trade() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::None, SlippageModel::Percent { percent: 1.0 });
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    portfolio.process_bar(1, -1.0, 110.0).unwrap();
+
+    let trade = &portfolio.get_trades()[0];
+    assert_close(trade.entry_price, 101.0);
+    assert_close(trade.exit_price, 108.9);
+    assert_close(trade.profit, (108.9 - 101.0) * trade.size);
+    assert_close(trade.gross_profit(), (110.0 - 100.0) * trade.size);
+}
+
+#[test]
+fn test_short_slippage_fills_lower_on_entry() {
+    let mut portfolio = Portfolio::new(10000.0).with_costs(
+        CommissionModel::None,
+        SlippageModel::Ticks { ticks: 2.0, tick_size: 0.5 },
+    );
+
+    portfolio.process_bar(0, -1.0, 100.0).unwrap();
+    portfolio.process_bar(1, 1.0, 90.0).unwrap();
+
+    let trade = &portfolio.get_trades()[0];
+    assert_close(trade.entry_price, 99.0);
+    assert_close(trade.exit_price, 91.0);
+}
+
+#[test]
+fn test_bar_range_slippage() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::None, SlippageModel::BarRange { fraction: 0.25 });
+
+    let entry = PriceBar { open: 99.0, high: 102.0, low: 98.0, close: 100.0, atr: None, funding_rate: None };
+    portfolio.process_price_bar(0, 1.0, &entry).unwrap();
+
+    // A quarter of the 4-point range
+    assert_close(portfolio.positions[0].entry_price, 101.0);
+}
@@ -0,0 +1,76 @@
+use tradebias::config::backtesting::ExecutionTiming;
+use tradebias::config::trade_management::{StopLossConfig, TradeManagementConfig};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::ExitReason;
+
+#[test]
+fn test_same_bar_close_fills_on_signal_bar() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(99.0, 101.0, 98.0, 100.0)).unwrap();
+
+    let position = &portfolio.positions[0];
+    assert_eq!(position.entry_bar, 0);
+    assert_eq!(position.entry_price, 100.0);
+}
+
+#[test]
+fn test_next_bar_open_fills_at_following_open() {
+    let mut portfolio =
+        Portfolio::new(10000.0).with_execution_timing(ExecutionTiming::NextBarOpen);
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(99.0, 101.0, 98.0, 100.0)).unwrap();
+    assert!(portfolio.positions.is_empty());
+
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(102.0, 104.0, 101.0, 103.0)).unwrap();
+    let position = &portfolio.positions[0];
+    assert_eq!(position.entry_bar, 1);
+    assert_eq!(position.entry_price, 102.0);
+
+    // Exit signal on bar 2 fills at bar 3's open
+    portfolio.process_price_bar(2, -1.0, &PriceBar::new(103.0, 106.0, 102.0, 105.0)).unwrap();
+    assert!(!portfolio.positions.is_empty());
+    portfolio.process_price_bar(3, 0.0, &PriceBar::new(107.0, 108.0, 106.0, 107.5)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_bar, 3);
+    assert_eq!(trades[0].exit_price, 107.0);
+    assert_eq!(trades[0].exit_reason, ExitReason::Signal);
+}
+
+#[test]
+fn test_next_bar_close_fills_at_following_close() {
+    let mut portfolio =
+        Portfolio::new(10000.0).with_execution_timing(ExecutionTiming::NextBarClose);
+
+    portfolio.process_price_bar(0, -1.0, &PriceBar::new(99.0, 101.0, 98.0, 100.0)).unwrap();
+    assert!(portfolio.positions.is_empty());
+
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(102.0, 104.0, 97.0, 98.0)).unwrap();
+    let position = &portfolio.positions[0];
+    assert_eq!(position.entry_bar, 1);
+    assert_eq!(position.entry_price, 98.0);
+}
+
+#[test]
+fn test_next_bar_open_checks_stops_after_entry() {
+    let config = TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+        ..TradeManagementConfig::signal_only()
+    };
+    let mut portfolio = Portfolio::new_with_trade_management(10000.0, config)
+        .with_execution_timing(ExecutionTiming::NextBarOpen);
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(99.0, 101.0, 98.0, 100.0)).unwrap();
+    // Fills at 110, stop at 107.8, and the same bar trades down to 107
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(110.0, 111.0, 107.0, 108.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].entry_bar, 1);
+    assert_eq!(trades[0].exit_bar, 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::StopLoss);
+    assert!((trades[0].exit_price - 107.8).abs() < 1e-9);
+}
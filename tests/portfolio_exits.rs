@@ -0,0 +1,148 @@
+mod common;
+
+use common::assert_close;
+use tradebias::config::trade_management::{StopLossConfig, TradeManagementConfig};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::ExitReason;
+
+fn with_atr(prices: PriceBar, atr: f64) -> PriceBar {
+    PriceBar { atr: Some(atr), ..prices }
+}
+
+fn portfolio_with(config: TradeManagementConfig) -> Portfolio {
+    Portfolio::new_with_trade_management(10000.0, config)
+}
+
+#[test]
+fn test_trailing_percent_follows_highest_high() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        stop_loss: StopLossConfig::TrailingPercent { percent: 2.0 },
+        ..TradeManagementConfig::signal_only()
+    });
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    assert_eq!(portfolio.positions[0].stop_loss, Some(98.0));
+
+    // New high of 110 lifts the stop to 107.8; it never moves back down
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(101.0, 110.0, 108.0, 109.0)).unwrap();
+    portfolio.process_price_bar(2, 0.0, &PriceBar::new(109.0, 109.5, 108.5, 109.0)).unwrap();
+    assert_close(portfolio.positions[0].stop_loss.unwrap(), 107.8);
+
+    portfolio.process_price_bar(3, 0.0, &PriceBar::new(108.5, 109.0, 107.0, 107.5)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::TrailingStop);
+    assert_close(trades[0].exit_price, 107.8);
+}
+
+#[test]
+fn test_trailing_stop_ignores_entry_bar_range() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        stop_loss: StopLossConfig::TrailingPercent { percent: 2.0 },
+        ..TradeManagementConfig::signal_only()
+    });
+
+    // The entry bar's high happened before the fill at its close
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 120.0, 99.0, 100.0)).unwrap();
+
+    assert_eq!(portfolio.positions[0].stop_loss, Some(98.0));
+}
+
+#[test]
+fn test_chandelier_trails_best_price_by_atr() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        stop_loss: StopLossConfig::Chandelier { multiplier: 3.0, period: 22 },
+        ..TradeManagementConfig::signal_only()
+    });
+
+    portfolio.process_price_bar(0, 1.0, &with_atr(PriceBar::new(100.0, 100.0, 100.0, 100.0), 2.0)).unwrap();
+    assert_eq!(portfolio.positions[0].stop_loss, Some(94.0));
+
+    // 110 high - 3 x 2 ATR
+    portfolio.process_price_bar(1, 0.0, &with_atr(PriceBar::new(101.0, 110.0, 105.0, 106.0), 2.0)).unwrap();
+    assert_close(portfolio.positions[0].stop_loss.unwrap(), 104.0);
+
+    portfolio.process_price_bar(2, 0.0, &with_atr(PriceBar::new(105.0, 105.5, 103.0, 103.5), 2.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::TrailingStop);
+    assert_close(trades[0].exit_price, 104.0);
+}
+
+#[test]
+fn test_trailing_atr_follows_close_for_shorts() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        stop_loss: StopLossConfig::TrailingATR { multiplier: 2.0, period: 14 },
+        ..TradeManagementConfig::signal_only()
+    });
+
+    portfolio.process_price_bar(0, -1.0, &with_atr(PriceBar::new(100.0, 100.0, 100.0, 100.0), 1.0)).unwrap();
+    assert_eq!(portfolio.positions[0].stop_loss, Some(102.0));
+
+    // Close of 95 + 2 x 1 ATR
+    portfolio.process_price_bar(1, 0.0, &with_atr(PriceBar::new(99.0, 99.5, 94.0, 95.0), 1.0)).unwrap();
+    assert_close(portfolio.positions[0].stop_loss.unwrap(), 97.0);
+
+    portfolio.process_price_bar(2, 0.0, &with_atr(PriceBar::new(95.5, 98.0, 95.0, 97.5), 1.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::TrailingStop);
+    assert_close(trades[0].exit_price, 97.0);
+}
+
+#[test]
+fn test_breakeven_after_one_r() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+        breakeven_after_r: Some(1.0),
+        ..TradeManagementConfig::signal_only()
+    });
+
+    // 1R = 2 points below the 100 entry
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(100.5, 101.5, 100.0, 101.0)).unwrap();
+    assert_eq!(portfolio.positions[0].stop_loss, Some(98.0));
+
+    portfolio.process_price_bar(2, 0.0, &PriceBar::new(101.0, 102.5, 100.5, 102.0)).unwrap();
+    assert_eq!(portfolio.positions[0].stop_loss, Some(100.0));
+
+    portfolio.process_price_bar(3, 0.0, &PriceBar::new(101.0, 101.0, 99.0, 99.5)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::Breakeven);
+    assert_eq!(trades[0].exit_price, 100.0);
+}
+
+#[test]
+fn test_plain_stop_keeps_stop_loss_reason() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+        breakeven_after_r: Some(3.0),
+        ..TradeManagementConfig::signal_only()
+    });
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(99.0, 99.5, 97.0, 97.5)).unwrap();
+
+    assert_eq!(portfolio.get_trades()[0].exit_reason, ExitReason::StopLoss);
+}
+
+#[test]
+fn test_time_stop_after_max_bars() {
+    let mut portfolio = portfolio_with(TradeManagementConfig {
+        max_bars_in_trade: Some(3),
+        ..TradeManagementConfig::signal_only()
+    });
+
+    for (i, price) in [100.0, 101.0, 102.0, 103.0].iter().enumerate() {
+        portfolio.process_bar(i, 1.0, *price).unwrap();
+    }
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::TimeStop);
+    assert_eq!((trades[0].entry_bar, trades[0].exit_bar), (0, 3));
+    assert_eq!(trades[0].exit_price, 103.0);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::assert_close;
+use tradebias::config::backtesting::{CommissionModel, SlippageModel};
+use tradebias::config::trade_management::TradeManagementConfig;
+use tradebias::engines::evaluation::Portfolio;
+
+#[test]
+fn test_ledger_identity_holds_every_bar() {
+    let config = TradeManagementConfig {
+        max_positions: 2,
+        allow_hedging: true,
+        ..TradeManagementConfig::signal_only()
+    };
+    let mut portfolio = Portfolio::new_with_trade_management(10000.0, config)
+        .with_costs(CommissionModel::Percent { percent: 0.1 }, SlippageModel::Percent { percent: 0.05 });
+
+    let bars = [(1.0, 100.0), (-1.0, 104.0), (0.0, 97.0), (0.0, 101.0)];
+    for (i, (signal, price)) in bars.iter().enumerate() {
+        portfolio.process_bar(i, *signal, *price).unwrap();
+    }
+
+    let ledger = portfolio.get_ledger();
+    assert_eq!(ledger.len(), bars.len());
+    for (i, entry) in ledger.iter().enumerate() {
+        assert_eq!(entry.bar, i);
+        assert_close(entry.equity, entry.cash + entry.position_value);
+        assert_close(entry.equity, 10000.0 + entry.realized_pnl + entry.unrealized_pnl);
+        assert_close(portfolio.get_equity_curve()[i + 1], entry.equity);
+    }
+}
+
+#[test]
+fn test_short_proceeds_are_offset_by_position_value() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar(0, -1.0, 100.0).unwrap();
+    portfolio.process_bar(1, 0.0, 90.0).unwrap();
+
+    // 10 units short: cash holds the 1000 proceeds, the position owes 900
+    let entry = portfolio.get_ledger()[1];
+    assert_close(entry.cash, 11000.0);
+    assert_close(entry.position_value, -900.0);
+    assert_close(entry.margin_used, 900.0);
+    assert_close(entry.equity, 10100.0);
+    assert_close(portfolio.final_balance(), 10100.0);
+}
+
+#[test]
+fn test_final_balance_marks_open_position_to_market() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    portfolio.process_bar(1, 0.0, 110.0).unwrap();
+
+    assert_close(portfolio.cash, 9000.0);
+    assert_close(portfolio.final_balance(), 10100.0);
+    assert_close(portfolio.final_balance(), portfolio.equity());
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::assert_close;
+use tradebias::config::backtesting::{
+    BacktestingConfig, CommissionModel, MarginConfig, SlippageModel,
+};
+use tradebias::config::traits::ConfigSection;
+use tradebias::config::trade_management::{
+    PositionSizing, StopLossConfig, TakeProfitConfig, TradeManagementConfig,
+};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::ExitReason;
+
+fn levered(percent: f64, leverage: f64, stop_loss: StopLossConfig) -> Portfolio {
+    let config = TradeManagementConfig {
+        stop_loss,
+        take_profit: TakeProfitConfig::None,
+        position_sizing: PositionSizing::Percent { percent },
+        ..TradeManagementConfig::signal_only()
+    };
+    Portfolio::new_with_trade_management(10000.0, config)
+        .with_margin(MarginConfig { leverage, maintenance_margin_percent: 0.5 })
+}
+
+#[test]
+fn test_leverage_scales_percent_sizing() {
+    let mut portfolio = levered(20.0, 5.0, StopLossConfig::None);
+
+    // 20% of equity as margin at 5x = 10000 notional
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+
+    assert_close(portfolio.positions[0].size, 100.0);
+    assert_close(portfolio.margin_used, 2000.0);
+    assert_close(portfolio.maintenance_margin, 50.0);
+
+    let entry = portfolio.get_ledger().last().unwrap();
+    assert_close(entry.margin_used, 2000.0);
+    assert_close(entry.maintenance_margin, 50.0);
+}
+
+#[test]
+fn test_leverage_caps_notional_at_equity_times_leverage() {
+    let mut portfolio = levered(500.0, 2.0, StopLossConfig::None);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+
+    assert_close(portfolio.positions[0].size, 200.0);
+}
+
+#[test]
+fn test_long_liquidated_at_liquidation_price() {
+    let mut portfolio = levered(100.0, 5.0, StopLossConfig::None);
+
+    // 50000 notional; 20% initial less 0.5% maintenance puts liquidation at 80.5
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    assert_close(portfolio.positions[0].liquidation_price.unwrap(), 80.5);
+
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(90.0, 91.0, 80.0, 85.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::Liquidation);
+    assert_close(trades[0].exit_price, 80.5);
+    assert_close(portfolio.equity(), 250.0);
+    assert!(portfolio.positions.is_empty());
+}
+
+#[test]
+fn test_short_liquidated_above_entry() {
+    let mut portfolio = levered(100.0, 5.0, StopLossConfig::None);
+
+    portfolio.process_price_bar(0, -1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    assert_close(portfolio.positions[0].liquidation_price.unwrap(), 119.5);
+
+    // Gaps through the liquidation price, so the fill is the open
+    portfolio.process_price_bar(1, -1.0, &PriceBar::new(121.0, 122.0, 120.0, 121.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::Liquidation);
+    assert_close(trades[0].exit_price, 121.0);
+}
+
+#[test]
+fn test_nearer_stop_beats_liquidation() {
+    let mut portfolio = levered(100.0, 5.0, StopLossConfig::FixedPercent { percent: 2.0 });
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(99.0, 99.0, 70.0, 75.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::StopLoss);
+    assert_close(trades[0].exit_price, 98.0);
+}
+
+#[test]
+fn test_margin_call_when_equity_below_maintenance() {
+    let mut portfolio = levered(100.0, 5.0, StopLossConfig::None)
+        .with_costs(CommissionModel::PerTrade { amount: 100.0 }, SlippageModel::None);
+
+    // Entry fee leaves 9900 of equity behind 50000 of notional
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+
+    // Liquidation (80.5) is not touched, but at 80.58 equity is 190 against 201.45 maintenance
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(81.0, 81.0, 80.55, 80.58)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::MarginCall);
+    assert_close(trades[0].exit_price, 80.58);
+    assert_close(portfolio.equity(), 90.0);
+
+    // The bar is recorded after the margin call
+    let entry = portfolio.get_ledger().last().unwrap();
+    assert_close(entry.margin_used, 0.0);
+    assert_close(entry.equity, 90.0);
+}
+
+#[test]
+fn test_spot_default_has_no_liquidation() {
+    let config = TradeManagementConfig {
+        position_sizing: PositionSizing::Percent { percent: 100.0 },
+        ..TradeManagementConfig::signal_only()
+    };
+    let mut portfolio = Portfolio::new_with_trade_management(10000.0, config);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    assert!(portfolio.positions[0].liquidation_price.is_none());
+    assert_close(portfolio.margin_used, 10000.0);
+
+    portfolio.process_bar(1, 1.0, 10.0).unwrap();
+    assert!(portfolio.get_trades().is_empty());
+}
+
+#[test]
+fn test_margin_config_validation() {
+    let with_margin = |leverage: f64, maintenance_margin_percent: f64| BacktestingConfig {
+        margin: MarginConfig { leverage, maintenance_margin_percent },
+        ..BacktestingConfig::default()
+    };
+
+    assert!(with_margin(0.5, 0.5).validate().is_err());
+    // 10x needs 10% initial margin; maintenance must be below it
+    assert!(with_margin(10.0, 10.0).validate().is_err());
+    assert!(with_margin(10.0, 0.5).validate().is_ok());
+    assert!(BacktestingConfig::default().validate().is_ok());
+}
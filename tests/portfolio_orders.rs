@@ -0,0 +1,153 @@
+use tradebias::config::backtesting::{CommissionModel, ExecutionTiming, SlippageModel};
+use tradebias::engines::evaluation::portfolio::{BarSignals, OrderRequest, PriceBar};
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::{Direction, OrderKind, OrderStatus};
+
+fn long_order(kind: OrderKind, price: f64, expiry_bars: usize) -> BarSignals {
+    BarSignals {
+        long_entry: true,
+        entry_order: Some(OrderRequest { kind, price, expiry_bars }),
+        ..Default::default()
+    }
+}
+
+fn short_order(kind: OrderKind, price: f64, expiry_bars: usize) -> BarSignals {
+    BarSignals {
+        short_entry: true,
+        entry_order: Some(OrderRequest { kind, price, expiry_bars }),
+        ..Default::default()
+    }
+}
+
+fn statuses(portfolio: &Portfolio) -> Vec<OrderStatus> {
+    portfolio.get_order_events().iter().map(|e| e.status).collect()
+}
+
+#[test]
+fn test_long_limit_fills_when_low_reaches_price() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar_signals(0, long_order(OrderKind::Limit, 98.0, 3), &PriceBar::from_close(100.0)).unwrap();
+    assert!(portfolio.positions.is_empty());
+    assert_eq!(portfolio.pending_orders.len(), 1);
+
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::new(100.0, 101.0, 99.0, 100.0)).unwrap();
+    assert!(portfolio.positions.is_empty());
+
+    portfolio.process_bar_signals(2, BarSignals::default(), &PriceBar::new(99.0, 100.0, 97.5, 99.0)).unwrap();
+
+    let position = &portfolio.positions[0];
+    assert_eq!(position.direction, Direction::Long);
+    assert_eq!(position.entry_bar, 2);
+    assert_eq!(position.entry_price, 98.0);
+    assert!(portfolio.pending_orders.is_empty());
+
+    assert_eq!(statuses(&portfolio), vec![OrderStatus::Placed, OrderStatus::Filled]);
+    let filled = &portfolio.get_order_events()[1];
+    assert_eq!(filled.bar, 2);
+    assert_eq!(filled.price, 98.0);
+}
+
+#[test]
+fn test_unfilled_order_expires() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar_signals(0, long_order(OrderKind::Limit, 95.0, 2), &PriceBar::from_close(100.0)).unwrap();
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::new(100.0, 101.0, 97.0, 99.0)).unwrap();
+    portfolio.process_bar_signals(2, BarSignals::default(), &PriceBar::new(99.0, 100.0, 96.0, 97.0)).unwrap();
+
+    assert_eq!(statuses(&portfolio), vec![OrderStatus::Placed, OrderStatus::Expired]);
+    assert_eq!(portfolio.get_order_events()[1].bar, 2);
+
+    // Too late: the order is gone
+    portfolio.process_bar_signals(3, BarSignals::default(), &PriceBar::new(96.0, 96.0, 90.0, 92.0)).unwrap();
+    assert!(portfolio.positions.is_empty());
+}
+
+#[test]
+fn test_buy_stop_gapped_through_fills_at_open_with_slippage() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::None, SlippageModel::Ticks { ticks: 1.0, tick_size: 0.5 });
+
+    // Buy stop above the signal bar's high
+    portfolio.process_bar_signals(0, long_order(OrderKind::Stop, 102.0, 5), &PriceBar::new(99.0, 102.0, 98.0, 100.0)).unwrap();
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::new(103.0, 105.0, 102.5, 104.0)).unwrap();
+
+    assert_eq!(portfolio.positions[0].entry_price, 103.5);
+}
+
+#[test]
+fn test_limit_fill_pays_no_slippage() {
+    let mut portfolio = Portfolio::new(10000.0)
+        .with_costs(CommissionModel::None, SlippageModel::Ticks { ticks: 1.0, tick_size: 0.5 });
+
+    portfolio.process_bar_signals(0, long_order(OrderKind::Limit, 98.0, 5), &PriceBar::from_close(100.0)).unwrap();
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::new(99.0, 99.0, 97.0, 98.5)).unwrap();
+
+    assert_eq!(portfolio.positions[0].entry_price, 98.0);
+    assert_eq!(portfolio.positions[0].entry_slippage, 0.0);
+}
+
+#[test]
+fn test_short_stop_fills_below_market() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar_signals(0, short_order(OrderKind::Stop, 98.0, 2), &PriceBar::from_close(100.0)).unwrap();
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::new(99.0, 99.5, 97.0, 97.5)).unwrap();
+
+    let position = &portfolio.positions[0];
+    assert_eq!(position.direction, Direction::Short);
+    assert_eq!(position.entry_price, 98.0);
+}
+
+#[test]
+fn test_new_signal_replaces_pending_order() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar_signals(0, long_order(OrderKind::Limit, 95.0, 5), &PriceBar::from_close(100.0)).unwrap();
+    portfolio.process_bar_signals(1, long_order(OrderKind::Limit, 97.0, 5), &PriceBar::from_close(100.0)).unwrap();
+
+    assert_eq!(portfolio.pending_orders.len(), 1);
+    assert_eq!(portfolio.pending_orders[0].price, 97.0);
+    assert_eq!(
+        statuses(&portfolio),
+        vec![OrderStatus::Placed, OrderStatus::Cancelled, OrderStatus::Placed]
+    );
+}
+
+#[test]
+fn test_next_bar_open_order_can_fill_on_placement_bar() {
+    let mut portfolio = Portfolio::new(10000.0).with_execution_timing(ExecutionTiming::NextBarOpen);
+
+    portfolio.process_bar_signals(0, long_order(OrderKind::Limit, 98.0, 1), &PriceBar::from_close(100.0)).unwrap();
+    assert!(portfolio.pending_orders.is_empty());
+
+    // Placed at bar 1's open and filled in the same bar's range
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::new(100.0, 100.0, 97.0, 99.0)).unwrap();
+
+    assert_eq!(portfolio.positions[0].entry_bar, 1);
+    assert_eq!(portfolio.positions[0].entry_price, 98.0);
+}
+
+#[test]
+fn test_end_of_data_cancels_resting_orders() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar_signals(0, long_order(OrderKind::Limit, 90.0, 10), &PriceBar::from_close(100.0)).unwrap();
+    portfolio.process_bar_signals(1, BarSignals::default(), &PriceBar::from_close(100.0)).unwrap();
+    portfolio.close_at_end_of_data(1, &PriceBar::from_close(100.0)).unwrap();
+
+    assert!(portfolio.pending_orders.is_empty());
+    assert_eq!(statuses(&portfolio), vec![OrderStatus::Placed, OrderStatus::Cancelled]);
+}
+
+#[test]
+fn test_no_order_placed_while_book_is_full() {
+    let mut portfolio = Portfolio::new(10000.0);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    portfolio.process_bar_signals(1, long_order(OrderKind::Limit, 98.0, 3), &PriceBar::from_close(100.0)).unwrap();
+
+    assert!(portfolio.pending_orders.is_empty());
+    assert!(portfolio.get_order_events().is_empty());
+}
@@ -0,0 +1,108 @@
+use tradebias::config::trade_management::{PositionSizing, StopLossConfig, TradeManagementConfig};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::{Direction, ExitReason};
+
+fn book(max_positions: usize, allow_hedging: bool, stop_loss: StopLossConfig) -> Portfolio {
+    let config = TradeManagementConfig {
+        stop_loss,
+        max_positions,
+        allow_hedging,
+        ..TradeManagementConfig::signal_only()
+    };
+    Portfolio::new_with_trade_management(10000.0, config)
+}
+
+#[test]
+fn test_single_position_ignores_repeated_signal() {
+    let mut portfolio = book(1, false, StopLossConfig::None);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    portfolio.process_bar(1, 1.0, 101.0).unwrap();
+
+    assert_eq!(portfolio.positions.len(), 1);
+    assert_eq!(portfolio.positions[0].entry_bar, 0);
+}
+
+#[test]
+fn test_pyramiding_up_to_max_positions() {
+    let mut portfolio = book(3, false, StopLossConfig::None);
+
+    for (i, price) in [100.0, 102.0, 104.0, 106.0].iter().enumerate() {
+        portfolio.process_bar(i, 1.0, *price).unwrap();
+    }
+
+    let entries: Vec<usize> = portfolio.positions.iter().map(|p| p.entry_bar).collect();
+    assert_eq!(entries, vec![0, 1, 2]);
+
+    // One opposite signal closes all three lots, each as its own trade
+    portfolio.process_bar(4, -1.0, 110.0).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 3);
+    assert!(portfolio.positions.is_empty());
+    assert!(trades.iter().all(|t| t.exit_bar == 4 && t.exit_reason == ExitReason::Signal));
+
+    let total: f64 = trades.iter().map(|t| t.profit).sum();
+    assert!((portfolio.realized_pnl - total).abs() < 1e-9);
+    assert!((portfolio.equity() - (10000.0 + total)).abs() < 1e-9);
+    assert!((portfolio.final_balance() - portfolio.equity()).abs() < 1e-9);
+}
+
+#[test]
+fn test_each_lot_has_its_own_stop() {
+    let mut portfolio = book(2, false, StopLossConfig::FixedPercent { percent: 5.0 });
+
+    // Lots at 100 (stop 95) and 110 (stop 104.5)
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(110.0, 110.0, 110.0, 110.0)).unwrap();
+    // Dips to 104: only the second lot's stop is touched
+    portfolio.process_price_bar(2, 0.0, &PriceBar::new(108.0, 108.0, 104.0, 106.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].entry_bar, 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::StopLoss);
+    assert_eq!(trades[0].exit_price, 104.5);
+
+    assert_eq!(portfolio.positions.len(), 1);
+    assert_eq!(portfolio.positions[0].entry_bar, 0);
+}
+
+#[test]
+fn test_hedged_long_and_short_lots() {
+    let mut portfolio = book(2, true, StopLossConfig::None);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    portfolio.process_bar(1, -1.0, 100.0).unwrap();
+
+    let directions: Vec<Direction> = portfolio.positions.iter().map(|p| p.direction).collect();
+    assert_eq!(directions, vec![Direction::Long, Direction::Short]);
+    assert!(portfolio.get_trades().is_empty());
+
+    // Equal lots offset each other, so price moves leave equity flat
+    let long_size = portfolio.positions[0].size;
+    let short_size = portfolio.positions[1].size;
+    portfolio.process_bar(2, 0.0, 120.0).unwrap();
+    let expected = 10000.0 + (120.0 - 100.0) * (long_size - short_size);
+    assert!((portfolio.equity() - expected).abs() < 1e-9);
+    assert!((portfolio.total_value() - portfolio.equity()).abs() < 1e-9);
+}
+
+#[test]
+fn test_pyramided_notional_capped_at_equity() {
+    let config = TradeManagementConfig {
+        position_sizing: PositionSizing::Percent { percent: 60.0 },
+        max_positions: 3,
+        ..TradeManagementConfig::signal_only()
+    };
+    let mut portfolio = Portfolio::new_with_trade_management(10000.0, config);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    portfolio.process_bar(1, 1.0, 100.0).unwrap();
+    portfolio.process_bar(2, 1.0, 100.0).unwrap();
+
+    // 6000 then only the remaining 4000; the book is then fully invested
+    let sizes: Vec<f64> = portfolio.positions.iter().map(|p| p.size).collect();
+    assert_eq!(sizes, vec![60.0, 40.0]);
+}
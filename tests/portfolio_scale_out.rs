@@ -0,0 +1,92 @@
+mod common;
+
+use common::assert_close;
+use tradebias::config::trade_management::{
+    ScaleOut, StopLossConfig, TakeProfitConfig, TradeManagementConfig,
+};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::{ExitReason, Trade};
+
+/// 50% at 1R, 25% at 2R, the rest on a 2% trailing stop
+fn ladder_portfolio() -> Portfolio {
+    let config = TradeManagementConfig {
+        stop_loss: StopLossConfig::TrailingPercent { percent: 2.0 },
+        take_profit: TakeProfitConfig::Ladder {
+            levels: vec![
+                ScaleOut { r_multiple: 1.0, percent: 50.0 },
+                ScaleOut { r_multiple: 2.0, percent: 25.0 },
+            ],
+        },
+        ..TradeManagementConfig::signal_only()
+    };
+    Portfolio::new_with_trade_management(10000.0, config)
+}
+
+#[test]
+fn test_ladder_scales_out_then_trails_remainder() {
+    let mut portfolio = ladder_portfolio();
+
+    // 10 units at 100, R = 2: levels at 102 and 104
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    assert_eq!(portfolio.positions[0].scale_outs, vec![(102.0, 5.0), (104.0, 2.5)]);
+
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(100.5, 102.5, 100.5, 102.0)).unwrap();
+    assert_eq!(portfolio.get_trades().len(), 1);
+    assert_close(portfolio.positions[0].size, 5.0);
+
+    portfolio.process_price_bar(2, 0.0, &PriceBar::new(102.0, 106.0, 102.0, 105.0)).unwrap();
+    assert_close(portfolio.positions[0].size, 2.5);
+
+    // Trailing stop at 106 x 0.98 = 103.88 takes the rest
+    portfolio.process_price_bar(3, 0.0, &PriceBar::new(104.5, 104.5, 103.0, 103.5)).unwrap();
+    assert!(portfolio.positions.is_empty());
+
+    let trades = portfolio.get_trades();
+    let sizes: Vec<f64> = trades.iter().map(|t| t.size).collect();
+    let reasons: Vec<ExitReason> = trades.iter().map(|t| t.exit_reason).collect();
+    assert_eq!(sizes, vec![5.0, 2.5, 2.5]);
+    assert_eq!(
+        reasons,
+        vec![ExitReason::TakeProfit, ExitReason::TakeProfit, ExitReason::TrailingStop]
+    );
+    assert!(trades.iter().all(|t| t.entry_id == trades[0].entry_id && t.entry_bar == 0));
+    assert_close(trades[2].exit_price, 103.88);
+
+    let total: f64 = trades.iter().map(|t| t.profit).sum();
+    assert_close(total, 5.0 * 2.0 + 2.5 * 4.0 + 2.5 * 3.88);
+    assert_close(portfolio.equity(), 10000.0 + total);
+}
+
+#[test]
+fn test_gap_fills_several_levels_at_open() {
+    let mut portfolio = ladder_portfolio();
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(105.0, 106.0, 104.5, 105.5)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 2);
+    assert!(trades.iter().all(|t| t.exit_price == 105.0));
+    assert_close(portfolio.positions[0].size, 2.5);
+}
+
+#[test]
+fn test_round_trips_merge_partial_closes() {
+    let mut portfolio = ladder_portfolio();
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 0.0, &PriceBar::new(100.5, 102.5, 100.5, 102.0)).unwrap();
+    portfolio.process_price_bar(2, -1.0, &PriceBar::new(101.0, 101.5, 100.5, 101.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 2);
+
+    let round_trips = Trade::round_trips(trades);
+    assert_eq!(round_trips.len(), 1);
+    assert_close(round_trips[0].size, 10.0);
+    assert_close(round_trips[0].exit_price, 101.5);
+    assert_close(round_trips[0].profit, 5.0 * 2.0 + 5.0 * 1.0);
+    assert_eq!(round_trips[0].exit_bar, 2);
+    assert_eq!(round_trips[0].exit_reason, ExitReason::Signal);
+}
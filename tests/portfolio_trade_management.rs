@@ -0,0 +1,243 @@
+use tradebias::config::trade_management::{
+    PositionSizing, StopLossConfig, TakeProfitConfig, TradeManagementConfig,
+};
+use tradebias::engines::evaluation::portfolio::PriceBar;
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::types::{Direction, ExitReason};
+
+fn portfolio_with(stop_loss: StopLossConfig, take_profit: TakeProfitConfig) -> Portfolio {
+    let config = TradeManagementConfig {
+        stop_loss,
+        take_profit,
+        ..TradeManagementConfig::signal_only()
+    };
+    Portfolio::new_with_trade_management(10000.0, config)
+}
+
+#[test]
+fn test_long_stop_loss_hit_intrabar() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::FixedPercent { percent: 2.0 },
+        TakeProfitConfig::None,
+    );
+
+    // Enter long at 100, stop at 98
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    // Low touches 97 but the bar closes back above the stop
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(99.5, 100.5, 97.0, 99.8)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::StopLoss);
+    assert_eq!(trades[0].exit_price, 98.0);
+    assert_eq!(trades[0].exit_bar, 1);
+    assert!(portfolio.positions.is_empty());
+}
+
+#[test]
+fn test_long_take_profit_hit_intrabar() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::FixedPercent { percent: 2.0 },
+        TakeProfitConfig::RiskReward { ratio: 2.0 },
+    );
+
+    // Enter long at 100: stop 98, target 104
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(101.0, 105.0, 100.5, 102.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::TakeProfit);
+    assert_eq!(trades[0].exit_price, 104.0);
+    assert!(trades[0].profit > 0.0);
+}
+
+#[test]
+fn test_short_stop_and_target_levels() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::FixedPercent { percent: 5.0 },
+        TakeProfitConfig::FixedPercent { percent: 10.0 },
+    );
+
+    portfolio.process_price_bar(0, -1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+
+    let position = &portfolio.positions[0];
+    assert_eq!(position.direction, Direction::Short);
+    assert_eq!(position.stop_loss, Some(105.0));
+    assert_eq!(position.take_profit, Some(90.0));
+
+    // Price falls through the target
+    portfolio.process_price_bar(1, -1.0, &PriceBar::new(95.0, 96.0, 89.0, 91.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].exit_reason, ExitReason::TakeProfit);
+    assert_eq!(trades[0].exit_price, 90.0);
+}
+
+#[test]
+fn test_stop_checked_before_target_in_same_bar() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::FixedPercent { percent: 2.0 },
+        TakeProfitConfig::FixedPercent { percent: 2.0 },
+    );
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    // Wide bar spans both 98 and 102
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(100.0, 103.0, 97.0, 101.0)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::StopLoss);
+}
+
+#[test]
+fn test_gap_through_stop_fills_at_open() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::FixedPercent { percent: 2.0 },
+        TakeProfitConfig::None,
+    );
+
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+    // Opens well below the 98 stop
+    portfolio.process_price_bar(1, 1.0, &PriceBar::new(95.0, 96.0, 94.0, 95.5)).unwrap();
+
+    let trades = portfolio.get_trades();
+    assert_eq!(trades[0].exit_reason, ExitReason::StopLoss);
+    assert_eq!(trades[0].exit_price, 95.0);
+}
+
+#[test]
+fn test_atr_stop_uses_entry_bar_atr() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::ATR { multiplier: 2.0, period: 14 },
+        TakeProfitConfig::RiskReward { ratio: 1.5 },
+    );
+
+    let entry = PriceBar { atr: Some(3.0), ..PriceBar::from_close(100.0) };
+    portfolio.process_price_bar(0, 1.0, &entry).unwrap();
+
+    let position = &portfolio.positions[0];
+    assert_eq!(position.stop_loss, Some(94.0));
+    assert_eq!(position.take_profit, Some(109.0));
+}
+
+#[test]
+fn test_atr_stop_without_atr_has_no_levels() {
+    let mut portfolio = portfolio_with(
+        StopLossConfig::ATR { multiplier: 2.0, period: 14 },
+        TakeProfitConfig::RiskReward { ratio: 2.0 },
+    );
+
+    // ATR is still warming up on the entry bar
+    portfolio.process_price_bar(0, 1.0, &PriceBar::new(100.0, 100.0, 100.0, 100.0)).unwrap();
+
+    let position = &portfolio.positions[0];
+    assert_eq!(position.stop_loss, None);
+    assert_eq!(position.take_profit, None);
+}
+
+fn portfolio_sized(position_sizing: PositionSizing, stop_loss: StopLossConfig) -> Portfolio {
+    let config = TradeManagementConfig {
+        stop_loss,
+        position_sizing,
+        ..TradeManagementConfig::signal_only()
+    };
+    Portfolio::new_with_trade_management(10000.0, config)
+}
+
+#[test]
+fn test_fixed_sizing_buys_fixed_notional() {
+    let mut portfolio = portfolio_sized(PositionSizing::Fixed { size: 500.0 }, StopLossConfig::None);
+
+    portfolio.process_bar(0, 1.0, 50.0).unwrap();
+
+    assert_eq!(portfolio.positions[0].size, 10.0);
+}
+
+#[test]
+fn test_percent_sizing_uses_equity() {
+    let mut portfolio = portfolio_sized(PositionSizing::Percent { percent: 25.0 }, StopLossConfig::None);
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+
+    assert_eq!(portfolio.positions[0].size, 25.0);
+}
+
+#[test]
+fn test_volatility_target_risks_fixed_fraction_to_stop() {
+    let mut portfolio = portfolio_sized(
+        PositionSizing::VolatilityTarget { risk_percent: 1.0, atr_period: 14, atr_multiplier: 2.0 },
+        StopLossConfig::FixedPercent { percent: 5.0 },
+    );
+
+    // 1% of 10000 = 100 at risk over a 5-point stop = 20 units
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+    assert_eq!(portfolio.positions[0].size, 20.0);
+
+    // Stopped out at 95 loses exactly the risk budget
+    portfolio.process_bar(1, 1.0, 95.0).unwrap();
+    assert!((portfolio.get_trades()[0].profit + 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_volatility_target_falls_back_to_atr() {
+    let mut portfolio = portfolio_sized(
+        PositionSizing::VolatilityTarget { risk_percent: 2.0, atr_period: 14, atr_multiplier: 2.0 },
+        StopLossConfig::None,
+    );
+
+    // 2% of 10000 = 200 at risk over 2 x ATR(5) = 10 points = 20 units
+    let entry = PriceBar { atr: Some(5.0), ..PriceBar::from_close(100.0) };
+    portfolio.process_price_bar(0, 1.0, &entry).unwrap();
+
+    assert_eq!(portfolio.positions[0].size, 20.0);
+}
+
+#[test]
+fn test_sizing_capped_at_equity() {
+    let mut portfolio = portfolio_sized(
+        PositionSizing::VolatilityTarget { risk_percent: 5.0, atr_period: 14, atr_multiplier: 1.0 },
+        StopLossConfig::FixedPercent { percent: 0.5 },
+    );
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+
+    // Uncapped this would be 1000 units; notional may not exceed equity
+    assert_eq!(portfolio.positions[0].size, 100.0);
+}
+
+#[test]
+fn test_kelly_uses_warmup_until_enough_trades() {
+    let mut portfolio = portfolio_sized(
+        PositionSizing::Kelly { fraction: 0.5, lookback: 20, warmup_percent: 5.0 },
+        StopLossConfig::None,
+    );
+
+    portfolio.process_bar(0, 1.0, 100.0).unwrap();
+
+    assert_eq!(portfolio.positions[0].size, 5.0);
+}
+
+#[test]
+fn test_kelly_estimate_from_completed_trades() {
+    let mut portfolio = portfolio_sized(
+        PositionSizing::Kelly { fraction: 0.5, lookback: 20, warmup_percent: 5.0 },
+        StopLossConfig::None,
+    );
+
+    // Ten round trips: 6 winners of +10%, 4 losers of -5%
+    let mut bar_idx = 0;
+    for i in 0..10 {
+        let exit = if i < 6 { 110.0 } else { 95.0 };
+        portfolio.process_bar(bar_idx, 1.0, 100.0).unwrap();
+        portfolio.process_bar(bar_idx + 1, -1.0, exit).unwrap();
+        bar_idx += 2;
+    }
+
+    // W = 0.6, R = 2.0 -> Kelly = 0.6 - 0.4 / 2 = 0.4; half Kelly = 20% of equity
+    let equity = portfolio.equity();
+    portfolio.process_bar(bar_idx, 1.0, 100.0).unwrap();
+
+    let size = portfolio.positions[0].size;
+    assert!((size - equity * 0.2 / 100.0).abs() < 1e-9);
+}
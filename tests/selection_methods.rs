@@ -0,0 +1,135 @@
+mod common;
+
+use common::{engine, evolve, fingerprint, sample, Recorder};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::collections::HashMap;
+use tradebias::config::evolution::{self, SelectionMethod};
+use tradebias::config::traits::ConfigSection;
+use tradebias::engines::generation::evolution_engine::EvolutionConfig;
+use tradebias::engines::generation::operators::{
+    boltzmann_selection, lexicase_selection, rank_selection, stochastic_universal_sampling,
+};
+use tradebias::engines::generation::Genome;
+
+const METHODS: [SelectionMethod; 6] = [
+    SelectionMethod::Tournament,
+    SelectionMethod::Roulette,
+    SelectionMethod::Rank,
+    SelectionMethod::StochasticUniversal,
+    SelectionMethod::Lexicase { folds: 4 },
+    SelectionMethod::Boltzmann { temperature: 5.0 },
+];
+
+/// How often each genome is picked in `draws` calls
+fn pick_counts(draws: usize, mut pick: impl FnMut(&mut ChaCha12Rng) -> Genome) -> HashMap<Genome, usize> {
+    let mut rng = ChaCha12Rng::seed_from_u64(3);
+    let mut counts = HashMap::new();
+    for _ in 0..draws {
+        *counts.entry(pick(&mut rng)).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_rank_selection_favours_higher_ranks() {
+    // Fitness magnitudes do not matter, only their order
+    let population = vec![(vec![0], -1000.0), (vec![1], 0.5), (vec![2], 0.6)];
+
+    let counts = pick_counts(6000, |rng| rank_selection(&population, rng));
+
+    // Expected shares 1/6, 2/6 and 3/6
+    assert!((800..1200).contains(&counts[&vec![0]]));
+    assert!((1800..2200).contains(&counts[&vec![1]]));
+    assert!((2800..3200).contains(&counts[&vec![2]]));
+}
+
+#[test]
+fn test_stochastic_universal_sampling_picks_expected_counts() {
+    let population = vec![(vec![0], 1.0), (vec![1], 0.0), (vec![2], 3.0)];
+    let mut rng = ChaCha12Rng::seed_from_u64(3);
+
+    for _ in 0..20 {
+        let picks = stochastic_universal_sampling(&population, 4, &mut rng);
+        assert_eq!(picks, vec![vec![0], vec![2], vec![2], vec![2]]);
+    }
+
+    let hopeless = vec![(vec![0], f64::NEG_INFINITY), (vec![1], -2.0)];
+    assert_eq!(stochastic_universal_sampling(&hopeless, 5, &mut rng).len(), 5);
+}
+
+#[test]
+fn test_boltzmann_temperature_controls_selection_pressure() {
+    let population = vec![(vec![0], 1.0), (vec![1], 2.0), (vec![2], 3.0), (vec![3], f64::NEG_INFINITY)];
+
+    let cold = pick_counts(1000, |rng| boltzmann_selection(&population, 0.01, rng));
+    let hot = pick_counts(3000, |rng| boltzmann_selection(&population, 1000.0, rng));
+
+    assert_eq!(cold.get(&vec![2]), Some(&1000));
+    for genome in 0..3 {
+        assert!((900..1100).contains(&hot[&vec![genome]]));
+    }
+    // An aborted strategy is never picked
+    assert!(!hot.contains_key(&vec![3]));
+}
+
+#[test]
+fn test_lexicase_selects_specialists_and_never_dominated_genomes() {
+    let population = vec![
+        (vec![0], vec![10.0, 0.0, 0.0]),
+        (vec![1], vec![0.0, 10.0, 0.0]),
+        (vec![2], vec![0.0, 0.0, 10.0]),
+        // Worse than every specialist on every case it could survive
+        (vec![3], vec![-5.0, -5.0, -5.0]),
+    ];
+
+    let counts = pick_counts(3000, |rng| lexicase_selection(&population, rng));
+
+    assert!(!counts.contains_key(&vec![3]));
+    for genome in 0..3 {
+        assert!((800..1200).contains(&counts[&vec![genome]]));
+    }
+}
+
+fn config(selection: SelectionMethod, use_pareto: bool) -> EvolutionConfig {
+    EvolutionConfig {
+        population_size: 12,
+        generations: 3,
+        selection,
+        use_pareto,
+        seed: Some(5),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_engine_dispatches_every_method() {
+    let data = sample();
+
+    for use_pareto in [false, true] {
+        let mut distinct = Vec::new();
+        for method in METHODS {
+            let (elites, progress) = evolve(&data, config(method, use_pareto));
+
+            assert!(!elites.is_empty(), "{:?} kept no strategies", method);
+            assert_eq!(progress.best.len(), 3);
+            if !distinct.contains(&fingerprint(&elites)) {
+                distinct.push(fingerprint(&elites));
+            }
+        }
+        // The configured method changes which parents breed
+        assert!(distinct.len() > 1);
+    }
+}
+
+#[test]
+fn test_invalid_selection_parameters_are_rejected() {
+    let data = sample();
+
+    for method in [SelectionMethod::Lexicase { folds: 0 }, SelectionMethod::Boltzmann { temperature: 0.0 }] {
+        assert!(engine(config(method, false)).run(&data, &mut Recorder::default()).is_err());
+
+        let ui_config = evolution::EvolutionConfig { selection_method: method, ..evolution::EvolutionConfig::default() };
+        assert!(ui_config.validate().is_err());
+    }
+}
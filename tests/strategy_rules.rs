@@ -0,0 +1,177 @@
+use polars::prelude::*;
+use std::sync::Arc;
+use tradebias::config::trade_management::TradeManagementConfig;
+use tradebias::data::IndicatorCache;
+use tradebias::engines::evaluation::backtester::Backtester;
+use tradebias::engines::evaluation::portfolio::{BarSignals, PriceBar};
+use tradebias::engines::evaluation::Portfolio;
+use tradebias::engines::generation::ast::{StrategyAST, StrategyMetadata};
+use tradebias::engines::generation::semantic_mapper::SemanticMapper;
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{
+    AstNode, Direction, EntryOrder, ExitReason, OrderKind, OrderStatus, PriceReference, Value,
+};
+
+fn close_vs(function: &str, level: f64) -> Option<Box<AstNode>> {
+    Some(Box::new(AstNode::Call {
+        function: function.to_string(),
+        args: vec![
+            Box::new(AstNode::Call { function: "Close".to_string(), args: vec![] }),
+            Box::new(AstNode::Const(Value::Float(level))),
+        ],
+    }))
+}
+
+#[test]
+fn test_mapper_generates_entry_and_exit_per_side() {
+    let registry = Arc::new(FunctionRegistry::new());
+    let mapper = SemanticMapper::new(registry, 3);
+
+    for seed in 0..20u32 {
+        let genome: Vec<u32> = (0..100u32).map(|i| seed.wrapping_mul(7919).wrapping_add(i * 31)).collect();
+        let ast = mapper.create_strategy_ast(&genome).unwrap();
+
+        match ast.root.as_ref() {
+            AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+                assert!(long_entry.is_some() || short_entry.is_some());
+                assert_eq!(long_entry.is_some(), long_exit.is_some());
+                assert_eq!(short_entry.is_some(), short_exit.is_some());
+            }
+            other => panic!("expected a Strategy root, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_mapper_builds_entry_orders_only_when_enabled() {
+    let registry = Arc::new(FunctionRegistry::new());
+    let market = SemanticMapper::new(Arc::clone(&registry), 3);
+    let orders = market.clone().with_entry_orders(21);
+    let entry_order = |mapper: &SemanticMapper, genome: &[u32]| match mapper.create_strategy_ast(genome).unwrap().root.as_ref() {
+        AstNode::Strategy { entry_order, .. } => *entry_order,
+        other => panic!("expected a Strategy root, got {:?}", other),
+    };
+
+    let mut built = 0;
+    for seed in 0..50u32 {
+        let genome: Vec<u32> = (0..100u32).map(|i| seed.wrapping_mul(7919).wrapping_add(i * 31)).collect();
+        assert_eq!(entry_order(&market, &genome), None);
+        if let Some(order) = entry_order(&orders, &genome) {
+            assert_eq!(order.atr_period, 21);
+            built += 1;
+        }
+    }
+
+    assert!(built > 0);
+}
+
+#[test]
+fn test_backtester_honours_exit_rule() {
+    let df = df! {
+        "close" => &[100.0, 106.0, 108.0, 103.0, 100.0, 107.0, 102.0, 101.0],
+    }
+    .unwrap();
+
+    // Long above 105, out again below 104
+    let ast = StrategyAST {
+        root: Box::new(AstNode::Strategy {
+            long_entry: close_vs("gt_scalar", 105.0),
+            long_exit: close_vs("lt_scalar", 104.0),
+            short_entry: None,
+            short_exit: None,
+            entry_order: None,
+        }),
+        metadata: StrategyMetadata::default(),
+    };
+
+    let registry = Arc::new(FunctionRegistry::new());
+    let cache = Arc::new(IndicatorCache::new(100));
+    let result = Backtester::new(registry, cache, 10000.0).run(&ast, &df).unwrap();
+
+    assert_eq!(result.trades.len(), 2);
+    assert!(result.trades.iter().all(|t| t.direction == Direction::Long));
+    assert!(result.trades.iter().all(|t| t.exit_reason == ExitReason::Signal));
+    assert_eq!((result.trades[0].entry_bar, result.trades[0].exit_bar), (1, 3));
+    assert_eq!((result.trades[1].entry_bar, result.trades[1].exit_bar), (5, 6));
+}
+
+#[test]
+fn test_backtester_enters_with_limit_orders() {
+    let df = df! {
+        "open" => &[100.0, 105.0, 106.0, 106.0, 104.0],
+        "high" => &[101.0, 107.0, 108.0, 105.0, 109.0],
+        "low" => &[99.0, 104.0, 105.0, 103.0, 106.0],
+        "close" => &[100.0, 106.0, 107.0, 104.0, 108.0],
+    }
+    .unwrap();
+
+    // Long above 105, bidding at the signal bar's low for two bars
+    let ast = StrategyAST {
+        root: Box::new(AstNode::Strategy {
+            long_entry: close_vs("gt_scalar", 105.0),
+            long_exit: None,
+            short_entry: None,
+            short_exit: None,
+            entry_order: Some(EntryOrder {
+                kind: OrderKind::Limit,
+                reference: PriceReference::Low,
+                atr_multiple: 0.0,
+                atr_period: 14,
+                expiry_bars: 2,
+            }),
+        }),
+        metadata: StrategyMetadata::default(),
+    };
+
+    let registry = Arc::new(FunctionRegistry::new());
+    let cache = Arc::new(IndicatorCache::new(100));
+    let result = Backtester::new(registry, cache, 10000.0).run(&ast, &df).unwrap();
+
+    // Bar 1 bids 104, bar 2 replaces it with 105, which bar 3 trades down to
+    let events: Vec<_> = result.order_events.iter().map(|e| (e.bar, e.status)).collect();
+    assert_eq!(
+        events,
+        vec![
+            (1, OrderStatus::Placed),
+            (2, OrderStatus::Cancelled),
+            (2, OrderStatus::Placed),
+            (3, OrderStatus::Filled),
+        ]
+    );
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(result.trades[0].entry_bar, 3);
+    assert_eq!(result.trades[0].entry_price, 105.0);
+}
+
+#[test]
+fn test_side_exit_leaves_hedged_lot_open() {
+    let config = TradeManagementConfig {
+        max_positions: 2,
+        allow_hedging: true,
+        ..TradeManagementConfig::signal_only()
+    };
+    let mut portfolio = Portfolio::new_with_trade_management(10000.0, config);
+    let prices = PriceBar::from_close(100.0);
+
+    let long = BarSignals { long_entry: true, ..Default::default() };
+    let short = BarSignals { short_entry: true, ..Default::default() };
+    let long_exit = BarSignals { long_exit: true, ..Default::default() };
+    portfolio.process_bar_signals(0, long, &prices).unwrap();
+    portfolio.process_bar_signals(1, short, &prices).unwrap();
+    portfolio.process_bar_signals(2, long_exit, &prices).unwrap();
+
+    assert_eq!(portfolio.get_trades().len(), 1);
+    assert_eq!(portfolio.get_trades()[0].direction, Direction::Long);
+    assert_eq!(portfolio.positions.len(), 1);
+    assert_eq!(portfolio.positions[0].direction, Direction::Short);
+}
+
+#[test]
+fn test_conflicting_entries_open_nothing() {
+    let mut portfolio = Portfolio::new(10000.0);
+    let both = BarSignals { long_entry: true, short_entry: true, ..Default::default() };
+
+    portfolio.process_bar_signals(0, both, &PriceBar::from_close(100.0)).unwrap();
+
+    assert!(portfolio.positions.is_empty());
+}
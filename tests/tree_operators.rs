@@ -0,0 +1,248 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tradebias::engines::generation::operators::{crossover, mutate, random_genome};
+use tradebias::engines::generation::semantic_mapper::SemanticMapper;
+use tradebias::engines::generation::tree_operators::{
+    hoist_mutation, parameter_mutation, point_mutation, shrink_mutation, subtree_crossover,
+};
+use tradebias::engines::generation::{Genome, StrategyAST};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, Value};
+
+const GENES: std::ops::Range<u32> = 0..1000;
+
+fn mapper(max_depth: usize) -> SemanticMapper {
+    SemanticMapper::new(Arc::new(FunctionRegistry::new()), max_depth)
+}
+
+fn genomes(count: usize, seed: u64) -> Vec<Genome> {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    (0..count).map(|_| random_genome(100, GENES, &mut rng)).collect()
+}
+
+fn decode(mapper: &SemanticMapper, genome: &Genome) -> StrategyAST {
+    mapper.create_strategy_ast(genome).unwrap()
+}
+
+/// Every subtree of `node`, printed, with how often it occurs
+fn subtrees(node: &AstNode, counts: &mut HashMap<String, usize>) {
+    *counts.entry(format!("{:?}", node)).or_insert(0) += 1;
+    match node {
+        AstNode::Const(_) => {}
+        AstNode::Call { args, .. } => args.iter().for_each(|arg| subtrees(arg, counts)),
+        AstNode::Rule { condition, action } => {
+            subtrees(condition, counts);
+            subtrees(action, counts);
+        }
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            [long_entry, long_exit, short_entry, short_exit].into_iter().flatten().for_each(|side| subtrees(side, counts))
+        }
+    }
+}
+
+/// Semantic similarity of two strategies: the share of subtrees they have in
+/// common, 1 for identical strategies and 0 for ones sharing nothing
+fn similarity(a: &StrategyAST, b: &StrategyAST) -> f64 {
+    let (mut left, mut right) = (HashMap::new(), HashMap::new());
+    subtrees(&a.root, &mut left);
+    subtrees(&b.root, &mut right);
+    let shared: usize = left.iter().map(|(tree, n)| (*n).min(right.get(tree).copied().unwrap_or(0))).sum();
+    let total: usize = left.values().sum::<usize>() + right.values().sum::<usize>() - shared;
+    shared as f64 / total as f64
+}
+
+fn size(node: &AstNode) -> usize {
+    let mut counts = HashMap::new();
+    subtrees(node, &mut counts);
+    counts.values().sum()
+}
+
+/// `node` with every constant replaced by zero
+fn shape(node: &AstNode) -> AstNode {
+    match node {
+        AstNode::Const(_) => AstNode::Const(Value::Integer(0)),
+        AstNode::Call { function, args } => AstNode::Call {
+            function: function.clone(),
+            args: args.iter().map(|arg| Box::new(shape(arg))).collect(),
+        },
+        AstNode::Rule { condition, action } => {
+            AstNode::Rule { condition: Box::new(shape(condition)), action: Box::new(shape(action)) }
+        }
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, .. } => {
+            let side = |side: &Option<Box<AstNode>>| side.as_ref().map(|node| Box::new(shape(node)));
+            AstNode::Strategy {
+                long_entry: side(long_entry),
+                long_exit: side(long_exit),
+                short_entry: side(short_entry),
+                short_exit: side(short_exit),
+                entry_order: None,
+            }
+        }
+    }
+}
+
+/// `node` with every function renamed `f`
+fn skeleton(node: &AstNode) -> AstNode {
+    match node {
+        AstNode::Call { args, .. } => AstNode::Call {
+            function: "f".to_string(),
+            args: args.iter().map(|arg| Box::new(skeleton(arg))).collect(),
+        },
+        AstNode::Strategy { long_entry, long_exit, short_entry, short_exit, entry_order } => {
+            let side = |side: &Option<Box<AstNode>>| side.as_ref().map(|node| Box::new(skeleton(node)));
+            AstNode::Strategy {
+                long_entry: side(long_entry),
+                long_exit: side(long_exit),
+                short_entry: side(short_entry),
+                short_exit: side(short_exit),
+                entry_order: *entry_order,
+            }
+        }
+        other => other.clone(),
+    }
+}
+
+#[test]
+fn test_encoding_inverts_decoding() {
+    let indicator_subset = mapper(4).with_indicators(vec!["RSI".to_string(), "MACD".to_string()]);
+    let entry_orders = mapper(4).with_entry_orders(21);
+
+    for mapper in [mapper(2), mapper(4), mapper(7), indicator_subset, entry_orders] {
+        for genome in genomes(200, 1) {
+            let ast = decode(&mapper, &genome);
+            let encoded = mapper.encode_strategy_ast(&ast).unwrap();
+            assert_eq!(decode(&mapper, &encoded).root, ast.root);
+        }
+    }
+}
+
+#[test]
+fn test_encoding_rejects_strategies_the_mapper_cannot_build() {
+    let deep = mapper(6);
+    let shallow = mapper(1);
+    let rejected = genomes(50, 2)
+        .iter()
+        .map(|genome| decode(&deep, genome))
+        .filter(|ast| shallow.encode_strategy_ast(ast).is_err())
+        .count();
+
+    // Trees nested past the shallow mapper's depth limit cannot be encoded for it
+    assert!(rejected > 0);
+}
+
+#[test]
+fn test_parameter_mutation_keeps_the_structure() {
+    let mapper = mapper(4);
+    let mut rng = ChaCha12Rng::seed_from_u64(3);
+    let mut changed = 0;
+
+    for genome in genomes(100, 3) {
+        let parent = decode(&mapper, &genome);
+        let child = decode(&mapper, &parameter_mutation(&genome, &mapper, GENES, &mut rng));
+
+        assert_eq!(shape(&child.root), shape(&parent.root));
+        changed += usize::from(child.root != parent.root);
+    }
+
+    assert!(changed > 50);
+}
+
+#[test]
+fn test_point_mutation_keeps_the_arguments() {
+    let mapper = mapper(4);
+    let mut rng = ChaCha12Rng::seed_from_u64(4);
+    let mut changed = 0;
+
+    for genome in genomes(100, 4) {
+        let parent = decode(&mapper, &genome);
+        let child = decode(&mapper, &point_mutation(&genome, &mapper, &mut rng));
+
+        assert_eq!(skeleton(&child.root), skeleton(&parent.root));
+        changed += usize::from(child.root != parent.root);
+    }
+
+    assert!(changed > 50);
+}
+
+#[test]
+fn test_hoist_and_shrink_mutation_never_grow_a_strategy() {
+    let mapper = mapper(5);
+    let mut rng = ChaCha12Rng::seed_from_u64(5);
+    let (mut hoisted, mut shrunk) = (0, 0);
+
+    for genome in genomes(100, 5) {
+        let parent = size(&decode(&mapper, &genome).root);
+        let hoist = size(&decode(&mapper, &hoist_mutation(&genome, &mapper, &mut rng)).root);
+        let shrink = size(&decode(&mapper, &shrink_mutation(&genome, &mapper, GENES, &mut rng)).root);
+
+        assert!(hoist <= parent);
+        assert!(shrink <= parent);
+        hoisted += usize::from(hoist < parent);
+        shrunk += usize::from(shrink < parent);
+    }
+
+    assert!(hoisted > 50);
+    assert!(shrunk > 50);
+}
+
+#[test]
+fn test_subtree_crossover_children_resemble_their_parents() {
+    let mapper = mapper(4);
+    let mut rng = ChaCha12Rng::seed_from_u64(6);
+    let population = genomes(100, 6);
+    let (mut tree, mut positional) = (0.0, 0.0);
+
+    for pair in population.chunks(2) {
+        let parents = (decode(&mapper, &pair[0]), decode(&mapper, &pair[1]));
+
+        let (a, b) = subtree_crossover(&pair[0], &pair[1], &mapper, &mut rng);
+        tree += similarity(&decode(&mapper, &a), &parents.0) + similarity(&decode(&mapper, &b), &parents.1);
+
+        let (a, b) = crossover(&pair[0], &pair[1], &mut rng);
+        positional += similarity(&decode(&mapper, &a), &parents.0) + similarity(&decode(&mapper, &b), &parents.1);
+    }
+
+    let (tree, positional) = (tree / population.len() as f64, positional / population.len() as f64);
+    assert!(tree > 0.5, "subtree crossover similarity {}", tree);
+    assert!(tree > positional, "subtree {} vs single-point {}", tree, positional);
+}
+
+#[test]
+fn test_tree_mutations_preserve_more_than_gene_mutation() {
+    let mapper = mapper(4);
+    let mut rng = ChaCha12Rng::seed_from_u64(7);
+    let population = genomes(100, 7);
+    let mut totals: BTreeMap<&str, f64> = BTreeMap::new();
+
+    for genome in &population {
+        let parent = decode(&mapper, genome);
+        let mut gene_mutated = genome.clone();
+        while gene_mutated == *genome {
+            mutate(&mut gene_mutated, 0.05, GENES, &mut rng);
+        }
+
+        let children = [
+            ("point", point_mutation(genome, &mapper, &mut rng)),
+            ("hoist", hoist_mutation(genome, &mapper, &mut rng)),
+            ("shrink", shrink_mutation(genome, &mapper, GENES, &mut rng)),
+            ("parameter", parameter_mutation(genome, &mapper, GENES, &mut rng)),
+            ("gene", gene_mutated),
+        ];
+        for (name, child) in children {
+            *totals.entry(name).or_insert(0.0) += similarity(&decode(&mapper, &child), &parent);
+        }
+    }
+
+    let gene = totals["gene"];
+    for operator in ["point", "parameter"] {
+        assert!(totals[operator] > gene, "{} {} vs gene {}", operator, totals[operator], gene);
+    }
+    // Every tree operator keeps most of the parent on average
+    for (operator, total) in &totals {
+        if *operator != "gene" {
+            assert!(total / population.len() as f64 > 0.3, "{} {}", operator, total);
+        }
+    }
+}
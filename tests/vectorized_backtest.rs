@@ -0,0 +1,208 @@
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use tradebias::config::backtesting::{CommissionModel, ExecutionTiming, MarginConfig, SlippageModel};
+use tradebias::config::trade_management::{PositionSizing, StopLossConfig, TradeManagementConfig};
+use tradebias::data::IndicatorCache;
+use tradebias::engines::evaluation::backtester::Backtester;
+use tradebias::engines::generation::ast::{StrategyAST, StrategyMetadata};
+use tradebias::functions::registry::FunctionRegistry;
+use tradebias::types::{AstNode, ExitReason, StrategyResult, Value};
+
+/// A seeded random walk with open, high, low and close
+fn random_walk(bars: usize, seed: u64) -> DataFrame {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut open, mut high, mut low, mut close) = (vec![], vec![], vec![], vec![]);
+    let mut price = 100.0;
+
+    for _ in 0..bars {
+        let bar_open = price + rng.gen_range(-0.5..0.5);
+        price = (bar_open + rng.gen_range(-2.0..2.0f64)).max(1.0);
+        open.push(bar_open);
+        high.push(bar_open.max(price) + rng.gen_range(0.0..1.0));
+        low.push(bar_open.min(price) - rng.gen_range(0.0..1.0));
+        close.push(price);
+    }
+
+    df! { "open" => open, "high" => high, "low" => low, "close" => close }.unwrap()
+}
+
+fn call(function: &str, args: Vec<AstNode>) -> AstNode {
+    AstNode::Call { function: function.to_string(), args: args.into_iter().map(Box::new).collect() }
+}
+
+fn close() -> AstNode {
+    call("Close", vec![])
+}
+
+fn sma(period: i64) -> AstNode {
+    call("SMA", vec![close(), AstNode::Const(Value::Integer(period))])
+}
+
+fn strategy(root: AstNode) -> StrategyAST {
+    StrategyAST { root: Box::new(root), metadata: StrategyMetadata::default() }
+}
+
+/// Signed distance from the moving average: long above it, short below
+fn signed_rule() -> StrategyAST {
+    strategy(AstNode::Rule {
+        condition: Box::new(AstNode::Const(Value::Bool(true))),
+        action: Box::new(call("Subtract", vec![close(), sma(10)])),
+    })
+}
+
+/// Long above 102 and short below 98, silent in between
+fn sparse_rule() -> StrategyAST {
+    strategy(AstNode::Rule {
+        condition: Box::new(call("Or", vec![
+            call("gt_scalar", vec![close(), AstNode::Const(Value::Float(102.0))]),
+            call("lt_scalar", vec![close(), AstNode::Const(Value::Float(98.0))]),
+        ])),
+        action: Box::new(call("Subtract", vec![close(), AstNode::Const(Value::Float(100.0))])),
+    })
+}
+
+fn entry_exit_rules() -> StrategyAST {
+    strategy(AstNode::Strategy {
+        long_entry: Some(Box::new(call("cross_above", vec![close(), sma(10)]))),
+        long_exit: Some(Box::new(call("lt_scalar", vec![close(), AstNode::Const(Value::Float(99.0))]))),
+        short_entry: Some(Box::new(call("cross_below", vec![close(), sma(10)]))),
+        short_exit: Some(Box::new(call("gt_scalar", vec![close(), AstNode::Const(Value::Float(101.0))]))),
+        entry_order: None,
+    })
+}
+
+fn backtester(sizing: PositionSizing, allow_hedging: bool) -> Backtester {
+    let trade_management = TradeManagementConfig {
+        position_sizing: sizing,
+        allow_hedging,
+        ..TradeManagementConfig::signal_only()
+    };
+    let registry = Arc::new(FunctionRegistry::new());
+    let cache = Arc::new(IndicatorCache::new(100));
+    Backtester::new(registry, cache, 10000.0).with_trade_management(trade_management)
+}
+
+fn assert_same(fast: &StrategyResult, slow: &StrategyResult, context: &str) {
+    assert_eq!(fast.trades, slow.trades, "trades differ: {}", context);
+    assert_eq!(fast.ledger, slow.ledger, "ledger differs: {}", context);
+    assert_eq!(fast.equity_curve, slow.equity_curve, "equity curve differs: {}", context);
+    // A wiped-out account has a NaN Sharpe ratio on both paths
+    assert_eq!(fast.metrics.len(), slow.metrics.len(), "metrics differ: {}", context);
+    for (name, value) in &fast.metrics {
+        let other = slow.metrics[name];
+        assert!(*value == other || (value.is_nan() && other.is_nan()), "{} differs: {}", name, context);
+    }
+    assert_eq!(fast.order_events, slow.order_events, "order events differ: {}", context);
+}
+
+/// Run `backtester` with and without the fast path and require identical results
+fn assert_equivalent(backtester: Backtester, ast: &StrategyAST, data: &DataFrame, context: &str) -> StrategyResult {
+    assert!(backtester.uses_fast_path(ast), "expected the fast path: {}", context);
+    let fast = backtester.run(ast, data).unwrap();
+    let slow = backtester.with_fast_path(false).run(ast, data).unwrap();
+    assert_same(&fast, &slow, context);
+    fast
+}
+
+#[test]
+fn test_fast_path_matches_portfolio_across_timings_and_costs() {
+    let data = random_walk(400, 7);
+    let timings = [ExecutionTiming::SameBarClose, ExecutionTiming::NextBarOpen, ExecutionTiming::NextBarClose];
+    let costs = [
+        (CommissionModel::None, SlippageModel::None),
+        (CommissionModel::Percent { percent: 0.1 }, SlippageModel::BarRange { fraction: 0.1 }),
+        (CommissionModel::PerTrade { amount: 2.0 }, SlippageModel::Ticks { ticks: 2.0, tick_size: 0.01 }),
+    ];
+
+    for (name, ast) in [("signed", signed_rule()), ("sparse", sparse_rule()), ("rules", entry_exit_rules())] {
+        for timing in timings {
+            for (commission, slippage) in &costs {
+                let context = format!("{} {:?} {:?} {:?}", name, timing, commission, slippage);
+                let result = assert_equivalent(
+                    backtester(PositionSizing::Percent { percent: 10.0 }, false)
+                        .with_execution_timing(timing)
+                        .with_costs(commission.clone(), slippage.clone()),
+                    &ast,
+                    &data,
+                    &context,
+                );
+                assert!(result.trades.len() > 1, "too few trades to compare: {}", context);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fast_path_matches_portfolio_across_sizing() {
+    let data = random_walk(400, 11);
+    let sizings = [
+        PositionSizing::Percent { percent: 100.0 },
+        PositionSizing::Fixed { size: 2500.0 },
+        PositionSizing::Kelly { fraction: 0.5, lookback: 20, warmup_percent: 10.0 },
+    ];
+
+    for sizing in sizings {
+        for allow_hedging in [false, true] {
+            let context = format!("{:?} hedging {}", sizing, allow_hedging);
+            assert_equivalent(backtester(sizing.clone(), allow_hedging), &signed_rule(), &data, &context);
+            assert_equivalent(backtester(sizing.clone(), allow_hedging), &entry_exit_rules(), &data, &context);
+        }
+    }
+}
+
+#[test]
+fn test_fast_path_excludes_end_of_data_like_portfolio() {
+    let data = random_walk(150, 3);
+    let backtester = backtester(PositionSizing::Percent { percent: 10.0 }, false).with_end_of_data_in_stats(false);
+
+    let result = assert_equivalent(backtester, &signed_rule(), &data, "end of data excluded");
+    assert_eq!(result.trades.last().unwrap().exit_reason, ExitReason::EndOfData);
+}
+
+#[test]
+fn test_margin_call_falls_back_to_portfolio() {
+    // Short everything at 100 into a rally that wipes out the account
+    let closes: Vec<f64> = (0..20).map(|i| 100.0 + 10.0 * i as f64).collect();
+    let data = df! { "close" => closes }.unwrap();
+    let always_short = strategy(AstNode::Rule {
+        condition: Box::new(AstNode::Const(Value::Bool(true))),
+        action: Box::new(AstNode::Const(Value::Float(-1.0))),
+    });
+
+    let backtester = backtester(PositionSizing::Percent { percent: 100.0 }, false)
+        .with_margin(MarginConfig::default());
+    let result = assert_equivalent(backtester, &always_short, &data, "margin call");
+
+    assert_eq!(result.trades[0].exit_reason, ExitReason::MarginCall);
+}
+
+#[test]
+fn test_path_dependent_management_takes_the_slow_path() {
+    let with_stop = TradeManagementConfig {
+        stop_loss: StopLossConfig::FixedPercent { percent: 2.0 },
+        ..TradeManagementConfig::signal_only()
+    };
+    let pyramiding = TradeManagementConfig { max_positions: 3, ..TradeManagementConfig::signal_only() };
+    let base = || backtester(PositionSizing::Percent { percent: 10.0 }, false);
+    let ast = signed_rule();
+
+    assert!(base().uses_fast_path(&ast));
+    assert!(!base().with_trade_management(with_stop).uses_fast_path(&ast));
+    assert!(!base().with_trade_management(pyramiding).uses_fast_path(&ast));
+    assert!(!base().with_margin(MarginConfig { leverage: 3.0, maintenance_margin_percent: 0.5 }).uses_fast_path(&ast));
+    assert!(!base().with_fast_path(false).uses_fast_path(&ast));
+}
+
+#[test]
+fn test_fast_path_on_sample_data() {
+    let data = tradebias::data::CsvConnector::load("tests/data/BTC_1hour_sample.csv").unwrap();
+
+    for timing in [ExecutionTiming::SameBarClose, ExecutionTiming::NextBarOpen] {
+        let backtester = backtester(PositionSizing::Percent { percent: 50.0 }, false)
+            .with_execution_timing(timing)
+            .with_costs(CommissionModel::Percent { percent: 0.05 }, SlippageModel::None);
+        assert_equivalent(backtester, &entry_exit_rules(), &data, "sample data");
+    }
+}